@@ -17,11 +17,17 @@ use leptos_router::{
     components::{Route, Router, Routes},
     path,
 };
+use leptos::task::spawn_local;
+use module_auth::server_fns::refresh_session_server;
 use module_profiles::server_fns::get_session_user_profile;
+use std::time::Duration;
 use synapse_core::domain::profiles::Profile;
 
 pub type SessionUserProfile = Resource<Result<Option<Profile>, ServerFnError>>;
 
+/// How often an open app extends its session, well within its four hours.
+const SESSION_REFRESH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
 #[component]
 pub fn Shell(options: LeptosOptions) -> impl IntoView {
     provide_meta_context();
@@ -57,6 +63,26 @@ pub fn App() -> impl IntoView {
 
     provide_context(session_user);
 
+    // Keep the session of a logged in agent from expiring while the app is open
+    Effect::new(move |_| {
+        if !matches!(session_user.get(), Some(Ok(Some(_)))) {
+            return;
+        }
+        let handle = set_interval_with_handle(
+            || {
+                spawn_local(async {
+                    if let Err(e) = refresh_session_server().await {
+                        leptos::logging::log!("Session refresh failed: {e}");
+                    }
+                });
+            },
+            SESSION_REFRESH_INTERVAL,
+        );
+        if let Ok(handle) = handle {
+            on_cleanup(move || handle.clear());
+        }
+    });
+
     view! {
         <Router>
             <Routes fallback=SynapsePage>
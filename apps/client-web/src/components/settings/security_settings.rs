@@ -3,12 +3,166 @@
 
 use super::types::{ActiveSession, ConnectedApp};
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use module_auth::server_fns::{
+    delegate_server, key_history_server, list_delegations_server, list_sessions_server,
+    revoke_delegation_server, revoke_other_sessions_server, revoke_session_server,
+    rotate_key_server, set_recovery_key_server,
+};
+use module_auth::signing::{
+    encode_signing_key, generate_signing_key, get_agent, rotation_request, set_agent,
+    set_signing_key, sign_event,
+};
+use module_auth::types::{DelegateRequest, RevokeDelegationRequest, SetRecoveryKeyRequest};
+use synapse_core::domain::events::delegations::{
+    DelegationScope, delegation_event, revocation_event,
+};
+use synapse_core::domain::events::keys::recovery_key_event;
+use time::{Duration, OffsetDateTime};
 
 /// Security settings panel
 #[component]
 pub fn SecuritySettings() -> impl IntoView {
-    let sessions = ActiveSession::mock_sessions();
-    let apps = ConnectedApp::mock_apps();
+    let sessions = Resource::new(|| (), |_| list_sessions_server());
+    let delegations = Resource::new(|| (), |_| list_delegations_server());
+    let (app_name, set_app_name) = signal(String::new());
+    let (app_key, set_app_key) = signal(String::new());
+    let (posts_only, set_posts_only) = signal(false);
+    let (app_token, set_app_token) = signal(None::<String>);
+    let key_history = Resource::new(|| (), |_| key_history_server(None));
+    let (recovery_key, set_recovery_key) = signal(String::new());
+    let (new_private_key, set_new_private_key) = signal(None::<String>);
+
+    // Delegations federate, so the browser signs them with the agent's key
+    let connect_app = move |_| {
+        let Some(agent) = get_agent() else {
+            leptos::logging::log!("Signing key unavailable; log in again to connect apps");
+            return;
+        };
+        let scope = DelegationScope {
+            name: app_name.get().trim().to_string(),
+            module_kinds: if posts_only.get() { vec!["posts".to_string()] } else { Vec::new() },
+            expires_at: Some(OffsetDateTime::now_utc() + Duration::days(30)),
+            ..Default::default()
+        };
+        let delegate = app_key.get().trim().to_string();
+        let Ok(grant) = delegation_event(agent, delegate.clone(), &scope) else {
+            return;
+        };
+        let Some(agent_signature) = sign_event(&grant) else {
+            return;
+        };
+        let request = DelegateRequest {
+            id: grant.id,
+            created_at: grant.created_at,
+            delegate,
+            scope,
+            agent_signature,
+        };
+        spawn_local(async move {
+            match delegate_server(request).await {
+                Ok(response) => {
+                    set_app_name.set(String::new());
+                    set_app_key.set(String::new());
+                    set_app_token.set(Some(response.token));
+                }
+                Err(e) => leptos::logging::log!("Failed to connect app: {e}"),
+            }
+            delegations.refetch();
+        });
+    };
+
+    let revoke_app = move |id| {
+        let Some(agent) = get_agent() else {
+            leptos::logging::log!("Signing key unavailable; log in again to revoke apps");
+            return;
+        };
+        let revocation = revocation_event(agent, id);
+        let Some(agent_signature) = sign_event(&revocation) else {
+            return;
+        };
+        let request = RevokeDelegationRequest {
+            id: revocation.id,
+            created_at: revocation.created_at,
+            agent_signature,
+        };
+        spawn_local(async move {
+            if let Err(e) = revoke_delegation_server(id, request).await {
+                leptos::logging::log!("Failed to revoke app: {e}");
+            }
+            delegations.refetch();
+        });
+    };
+
+    // Key changes are chained, each naming the last one
+    let latest_key_change = move || {
+        key_history
+            .get_untracked()
+            .and_then(Result::ok)
+            .and_then(|history| history.latest_change)
+    };
+
+    // The new key is made in the browser and endorsed with the one in use
+    let rotate_key = move |_| {
+        let Some(agent) = get_agent() else {
+            leptos::logging::log!("Signing key unavailable; log in again to rotate keys");
+            return;
+        };
+        let Some(new_key) = generate_signing_key() else {
+            return;
+        };
+        let Some(request) = rotation_request(&agent, &new_key, latest_key_change()) else {
+            return;
+        };
+        spawn_local(async move {
+            match rotate_key_server(request).await {
+                Ok(history) => {
+                    set_signing_key(&new_key);
+                    set_agent(&agent, history.token.as_deref());
+                    set_new_private_key.set(Some(encode_signing_key(&new_key)));
+                }
+                Err(e) => leptos::logging::log!("Failed to rotate key: {e}"),
+            }
+            key_history.refetch();
+            sessions.refetch();
+        });
+    };
+
+    let commit_recovery_key = move |_| {
+        let Some(agent) = get_agent() else {
+            leptos::logging::log!("Signing key unavailable; log in again to set a recovery key");
+            return;
+        };
+        let key = recovery_key.get().trim().to_string();
+        let previous = latest_key_change();
+        let change = recovery_key_event(agent, key.clone(), previous);
+        let Some(agent_signature) = sign_event(&change) else {
+            return;
+        };
+        let request = SetRecoveryKeyRequest {
+            id: change.id,
+            created_at: change.created_at,
+            previous,
+            key,
+            agent_signature,
+        };
+        spawn_local(async move {
+            match set_recovery_key_server(request).await {
+                Ok(_) => set_recovery_key.set(String::new()),
+                Err(e) => leptos::logging::log!("Failed to set recovery key: {e}"),
+            }
+            key_history.refetch();
+        });
+    };
+
+    let revoke_others = move |_| {
+        spawn_local(async move {
+            if let Err(e) = revoke_other_sessions_server().await {
+                leptos::logging::log!("Failed to revoke sessions: {e}");
+            }
+            sessions.refetch();
+        });
+    };
 
     view! {
         <div class="space-y-6">
@@ -32,71 +186,93 @@ pub fn SecuritySettings() -> impl IntoView {
                             <p class="text-sm text-foreground/50">"Devices where you're currently logged in"</p>
                         </div>
                     </div>
-                    <button class="text-sm text-rose-400 hover:text-rose-300 transition-colors">
-                        "Sign out all"
+                    <button
+                        class="text-sm text-rose-400 hover:text-rose-300 transition-colors"
+                        on:click=revoke_others
+                    >
+                        "Sign out all others"
                     </button>
                 </div>
 
                 <div class="divide-y divide-border/30">
-                    {sessions.into_iter().map(|session| {
-                        let device_icon = match session.device_type.as_str() {
-                            "mobile" => r#"<path stroke-linecap="round" stroke-linejoin="round" d="M12 18h.01M8 21h8a2 2 0 002-2V5a2 2 0 00-2-2H8a2 2 0 00-2 2v14a2 2 0 002 2z"/>"#,
-                            _ => r#"<path stroke-linecap="round" stroke-linejoin="round" d="M9.75 17L9 20l-1 1h8l-1-1-.75-3M3 13h18M5 17h14a2 2 0 002-2V5a2 2 0 00-2-2H5a2 2 0 00-2 2v10a2 2 0 002 2z"/>"#,
-                        };
-                        
-                        view! {
-                            <div class="px-6 py-4 flex items-center justify-between">
-                                <div class="flex items-center gap-4">
-                                    <div class=format!(
-                                        "w-10 h-10 rounded-xl flex items-center justify-center {}",
-                                        if session.is_current { "bg-emerald-500/15" } else { "bg-foreground/5" }
-                                    )>
-                                        <svg
-                                            class=format!(
-                                                "w-5 h-5 {}",
-                                                if session.is_current { "text-emerald-400" } else { "text-foreground/40" }
-                                            )
-                                            fill="none"
-                                            viewBox="0 0 24 24"
-                                            stroke="currentColor"
-                                            stroke-width="2"
-                                            inner_html=device_icon
-                                        ></svg>
-                                    </div>
-                                    <div>
-                                        <div class="flex items-center gap-2">
-                                            <p class="font-medium text-foreground">{session.device_name}</p>
-                                            {if session.is_current {
+                    <Suspense fallback=move || view! {
+                        <p class="px-6 py-4 text-sm text-foreground/50">"Loading sessions..."</p>
+                    }>
+                        {move || sessions.get().map(|result| match result {
+                            Err(e) => view! {
+                                <p class="px-6 py-4 text-sm text-rose-400">{format!("Failed to load sessions: {e}")}</p>
+                            }.into_any(),
+                            Ok(response) => {
+                                let now = OffsetDateTime::now_utc();
+                                response.sessions.iter().map(|info| ActiveSession::from_info(info, now)).map(|session| {
+                                    let id = session.id;
+                                    let device_icon = match session.device_type.as_str() {
+                                        "mobile" => r#"<path stroke-linecap="round" stroke-linejoin="round" d="M12 18h.01M8 21h8a2 2 0 002-2V5a2 2 0 00-2-2H8a2 2 0 00-2 2v14a2 2 0 002 2z"/>"#,
+                                        _ => r#"<path stroke-linecap="round" stroke-linejoin="round" d="M9.75 17L9 20l-1 1h8l-1-1-.75-3M3 13h18M5 17h14a2 2 0 002-2V5a2 2 0 00-2-2H5a2 2 0 00-2 2v10a2 2 0 002 2z"/>"#,
+                                    };
+
+                                    view! {
+                                        <div class="px-6 py-4 flex items-center justify-between">
+                                            <div class="flex items-center gap-4">
+                                                <div class=format!(
+                                                    "w-10 h-10 rounded-xl flex items-center justify-center {}",
+                                                    if session.is_current { "bg-emerald-500/15" } else { "bg-foreground/5" }
+                                                )>
+                                                    <svg
+                                                        class=format!(
+                                                            "w-5 h-5 {}",
+                                                            if session.is_current { "text-emerald-400" } else { "text-foreground/40" }
+                                                        )
+                                                        fill="none"
+                                                        viewBox="0 0 24 24"
+                                                        stroke="currentColor"
+                                                        stroke-width="2"
+                                                        inner_html=device_icon
+                                                    ></svg>
+                                                </div>
+                                                <div>
+                                                    <div class="flex items-center gap-2">
+                                                        <p class="font-medium text-foreground">{session.device_name}</p>
+                                                        {if session.is_current {
+                                                            view! {
+                                                                <span class="text-xs text-emerald-400 bg-emerald-500/15 px-2 py-0.5 rounded-lg">
+                                                                    "Current"
+                                                                </span>
+                                                            }.into_any()
+                                                        } else {
+                                                            view! { <span></span> }.into_any()
+                                                        }}
+                                                    </div>
+                                                    <div class="flex items-center gap-2 text-xs text-foreground/40 mt-0.5">
+                                                        <span>{session.last_active}</span>
+                                                    </div>
+                                                </div>
+                                            </div>
+                                            {if !session.is_current {
                                                 view! {
-                                                    <span class="text-xs text-emerald-400 bg-emerald-500/15 px-2 py-0.5 rounded-lg">
-                                                        "Current"
-                                                    </span>
+                                                    <button
+                                                        class="px-3 py-1.5 text-sm text-foreground/60 hover:text-rose-400 hover:bg-rose-500/10 rounded-lg transition-colors"
+                                                        on:click=move |_| {
+                                                            spawn_local(async move {
+                                                                if let Err(e) = revoke_session_server(id).await {
+                                                                    leptos::logging::log!("Failed to revoke session: {e}");
+                                                                }
+                                                                sessions.refetch();
+                                                            });
+                                                        }
+                                                    >
+                                                        "Revoke"
+                                                    </button>
                                                 }.into_any()
                                             } else {
                                                 view! { <span></span> }.into_any()
                                             }}
                                         </div>
-                                        <div class="flex items-center gap-2 text-xs text-foreground/40 mt-0.5">
-                                            <span>{session.location}</span>
-                                            <span>"·"</span>
-                                            <span class="font-mono">{session.ip_address}</span>
-                                            <span>"·"</span>
-                                            <span>{session.last_active}</span>
-                                        </div>
-                                    </div>
-                                </div>
-                                {if !session.is_current {
-                                    view! {
-                                        <button class="px-3 py-1.5 text-sm text-foreground/60 hover:text-rose-400 hover:bg-rose-500/10 rounded-lg transition-colors">
-                                            "Revoke"
-                                        </button>
-                                    }.into_any()
-                                } else {
-                                    view! { <span></span> }.into_any()
-                                }}
-                            </div>
-                        }
-                    }).collect_view()}
+                                    }
+                                }).collect_view().into_any()
+                            }
+                        })}
+                    </Suspense>
                 </div>
             </div>
 
@@ -111,51 +287,186 @@ pub fn SecuritySettings() -> impl IntoView {
                         </div>
                         <div>
                             <h3 class="font-semibold text-foreground">"Connected Applications"</h3>
-                            <p class="text-sm text-foreground/50">"Third-party apps with access to your account"</p>
+                            <p class="text-sm text-foreground/50">"Devices, apps and bots that sign events for you with their own keys"</p>
                         </div>
                     </div>
                 </div>
 
                 <div class="divide-y divide-border/30">
-                    {apps.into_iter().map(|app| {
-                        let permissions = app.permissions.clone();
-                        view! {
-                            <div class="px-6 py-4">
-                                <div class="flex items-start justify-between gap-4">
-                                    <div class="flex items-start gap-4">
-                                        <div class="w-12 h-12 rounded-xl bg-foreground/5 flex items-center justify-center flex-shrink-0">
-                                            <svg class="w-6 h-6 text-foreground/40" fill="none" viewBox="0 0 24 24" stroke="currentColor" stroke-width="2">
-                                                <path stroke-linecap="round" stroke-linejoin="round" d="M4 6a2 2 0 012-2h2a2 2 0 012 2v2a2 2 0 01-2 2H6a2 2 0 01-2-2V6z"/>
-                                            </svg>
-                                        </div>
-                                        <div>
-                                            <p class="font-medium text-foreground">{app.name}</p>
-                                            <p class="text-sm text-foreground/50">{app.description}</p>
-                                            <div class="flex flex-wrap gap-1.5 mt-2">
-                                                {permissions.into_iter().map(|perm| {
-                                                    view! {
-                                                        <span class="text-xs text-foreground/50 bg-foreground/5 px-2 py-0.5 rounded">
-                                                            {perm}
-                                                        </span>
-                                                    }
-                                                }).collect_view()}
+                    <Suspense fallback=move || view! {
+                        <p class="px-6 py-4 text-sm text-foreground/50">"Loading applications..."</p>
+                    }>
+                        {move || delegations.get().map(|result| match result {
+                            Err(e) => view! {
+                                <p class="px-6 py-4 text-sm text-rose-400">{format!("Failed to load applications: {e}")}</p>
+                            }.into_any(),
+                            Ok(response) if response.delegations.is_empty() => view! {
+                                <p class="px-6 py-4 text-sm text-foreground/50">"No applications can act for you yet."</p>
+                            }.into_any(),
+                            Ok(response) => {
+                                let now = OffsetDateTime::now_utc();
+                                response.delegations.iter().map(|delegation| ConnectedApp::from_delegation(delegation, now)).map(|app| {
+                                    let id = app.id;
+                                    let permissions = app.permissions.clone();
+                                    view! {
+                                        <div class="px-6 py-4">
+                                            <div class="flex items-start justify-between gap-4">
+                                                <div class="flex items-start gap-4">
+                                                    <div class="w-12 h-12 rounded-xl bg-foreground/5 flex items-center justify-center flex-shrink-0">
+                                                        <svg class="w-6 h-6 text-foreground/40" fill="none" viewBox="0 0 24 24" stroke="currentColor" stroke-width="2">
+                                                            <path stroke-linecap="round" stroke-linejoin="round" d="M4 6a2 2 0 012-2h2a2 2 0 012 2v2a2 2 0 01-2 2H6a2 2 0 01-2-2V6z"/>
+                                                        </svg>
+                                                    </div>
+                                                    <div>
+                                                        <p class="font-medium text-foreground">{app.name}</p>
+                                                        <p class="text-sm text-foreground/50 font-mono">{app.description}</p>
+                                                        <div class="flex flex-wrap gap-1.5 mt-2">
+                                                            {permissions.into_iter().map(|perm| {
+                                                                view! {
+                                                                    <span class="text-xs text-foreground/50 bg-foreground/5 px-2 py-0.5 rounded">
+                                                                        {perm}
+                                                                    </span>
+                                                                }
+                                                            }).collect_view()}
+                                                        </div>
+                                                        <p class="text-xs text-foreground/30 mt-2">
+                                                            "Connected "{app.connected_date}" · "{app.expires}
+                                                        </p>
+                                                    </div>
+                                                </div>
+                                                <button
+                                                    class="px-3 py-1.5 text-sm text-rose-400 hover:bg-rose-500/10 rounded-lg transition-colors flex-shrink-0"
+                                                    on:click=move |_| revoke_app(id)
+                                                >
+                                                    "Revoke"
+                                                </button>
                                             </div>
-                                            <p class="text-xs text-foreground/30 mt-2">
-                                                "Connected "{app.connected_date}" · Last used "{app.last_used}
-                                            </p>
                                         </div>
-                                    </div>
-                                    <button class="px-3 py-1.5 text-sm text-rose-400 hover:bg-rose-500/10 rounded-lg transition-colors flex-shrink-0">
-                                        "Revoke"
-                                    </button>
-                                </div>
-                            </div>
-                        }
-                    }).collect_view()}
+                                    }
+                                }).collect_view().into_any()
+                            }
+                        })}
+                    </Suspense>
+                </div>
+
+                <div class="px-6 py-4 border-t border-border/30 bg-foreground/[0.02] space-y-3">
+                    <p class="text-sm font-medium text-foreground">"Connect an application"</p>
+                    <div class="grid gap-2 sm:grid-cols-2">
+                        <input
+                            class="px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-sm text-foreground"
+                            placeholder="Name"
+                            prop:value=app_name
+                            on:input=move |ev| set_app_name.set(event_target_value(&ev))
+                        />
+                        <input
+                            class="px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-sm text-foreground font-mono"
+                            placeholder="App public key"
+                            prop:value=app_key
+                            on:input=move |ev| set_app_key.set(event_target_value(&ev))
+                        />
+                    </div>
+                    <div class="flex items-center gap-2">
+                        <select
+                            class="px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-sm text-foreground"
+                            on:change=move |ev| set_posts_only.set(event_target_value(&ev) == "posts")
+                        >
+                            <option value="all">"All events, for 30 days"</option>
+                            <option value="posts">"Posts only, for 30 days"</option>
+                        </select>
+                        <button
+                            class="px-4 py-2 bg-brand/15 text-brand rounded-xl text-sm font-medium hover:bg-brand/25 transition-colors"
+                            on:click=connect_app
+                        >
+                            "Connect"
+                        </button>
+                    </div>
+                    {move || app_token.get().map(|token| view! {
+                        <div class="space-y-1">
+                            <p class="text-xs text-foreground/50">"Give this token to the application. It signs its events with its own key and includes the token."</p>
+                            <textarea
+                                class="w-full px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-xs text-foreground font-mono"
+                                readonly=true
+                                rows=4
+                            >
+                                {token}
+                            </textarea>
+                        </div>
+                    })}
                 </div>
+            </div>
 
-                <div class="px-6 py-3 border-t border-border/30 bg-foreground/[0.02]">
-                    <a href="/settings/applications" class="text-sm text-brand hover:underline">"Manage all applications →"</a>
+            // Signing Key
+            <div class="bg-card border border-border/50 rounded-2xl overflow-hidden">
+                <div class="px-6 py-4 border-b border-border/30">
+                    <div class="flex items-center gap-3">
+                        <div class="w-10 h-10 rounded-xl bg-emerald-500/15 flex items-center justify-center">
+                            <svg class="w-5 h-5 text-emerald-400" fill="none" viewBox="0 0 24 24" stroke="currentColor" stroke-width="2">
+                                <path stroke-linecap="round" stroke-linejoin="round" d="M4 4v5h.582m15.356 2A8.001 8.001 0 004.582 9m0 0H9m11 11v-5h-.581m0 0a8.003 8.003 0 01-15.357-2m15.357 2H15"/>
+                            </svg>
+                        </div>
+                        <div>
+                            <h3 class="font-semibold text-foreground">"Signing Key"</h3>
+                            <p class="text-sm text-foreground/50">"Move your identity to a new key if yours may be compromised"</p>
+                        </div>
+                    </div>
+                </div>
+
+                <div class="px-6 py-4 space-y-3">
+                    <Suspense fallback=move || view! {
+                        <p class="text-sm text-foreground/50">"Loading keys..."</p>
+                    }>
+                        {move || key_history.get().map(|result| match result {
+                            Err(e) => view! {
+                                <p class="text-sm text-rose-400">{format!("Failed to load keys: {e}")}</p>
+                            }.into_any(),
+                            Ok(history) => view! {
+                                <div class="space-y-1 text-sm">
+                                    <p class="text-foreground/50">"Current key"</p>
+                                    <p class="font-mono text-xs text-foreground break-all">{history.current_key}</p>
+                                    <p class="text-foreground/50 pt-2">"Recovery key"</p>
+                                    <p class="font-mono text-xs text-foreground break-all">
+                                        {history.recovery_key.unwrap_or_else(|| "Not set".to_string())}
+                                    </p>
+                                    <p class="text-xs text-foreground/30 pt-2">
+                                        {format!("{} key(s) used so far", history.periods.len())}
+                                    </p>
+                                </div>
+                            }.into_any(),
+                        })}
+                    </Suspense>
+
+                    <div class="flex items-center gap-2">
+                        <input
+                            class="flex-1 px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-sm text-foreground font-mono"
+                            placeholder="Recovery public key"
+                            prop:value=recovery_key
+                            on:input=move |ev| set_recovery_key.set(event_target_value(&ev))
+                        />
+                        <button
+                            class="px-4 py-2 bg-brand/15 text-brand rounded-xl text-sm font-medium hover:bg-brand/25 transition-colors"
+                            on:click=commit_recovery_key
+                        >
+                            "Set Recovery Key"
+                        </button>
+                    </div>
+                    <button
+                        class="px-4 py-2 bg-rose-500/15 text-rose-400 rounded-xl text-sm font-medium hover:bg-rose-500/25 transition-colors"
+                        on:click=rotate_key
+                    >
+                        "Rotate Key"
+                    </button>
+                    {move || new_private_key.get().map(|key| view! {
+                        <div class="space-y-1">
+                            <p class="text-xs text-foreground/50">"Your new private key. Save it now: your old key no longer logs in, and other sessions were signed out."</p>
+                            <textarea
+                                class="w-full px-3 py-2 bg-foreground/5 border border-border/50 rounded-xl text-xs text-foreground font-mono"
+                                readonly=true
+                                rows=2
+                            >
+                                {key}
+                            </textarea>
+                        </div>
+                    })}
                 </div>
             </div>
 
@@ -3,6 +3,9 @@
 
 use super::types::SettingsTab;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use module_auth::server_fns::logout_server;
+use module_auth::signing::clear_signing_key;
 
 /// Settings navigation sidebar
 #[component]
@@ -11,6 +14,16 @@ pub fn SettingsSidebar(
 ) -> impl IntoView {
     let tabs = SettingsTab::all();
 
+    let sign_out = move |_| {
+        spawn_local(async move {
+            if let Err(e) = logout_server().await {
+                leptos::logging::log!("Logout failed: {e}");
+            }
+            clear_signing_key();
+            let _ = window().location().set_href("/login");
+        });
+    };
+
     view! {
         <nav class="w-full lg:w-64 flex-shrink-0">
             <div class="bg-card border border-border/50 rounded-2xl overflow-hidden">
@@ -93,7 +106,10 @@ pub fn SettingsSidebar(
                         </svg>
                         <span>"Documentation"</span>
                     </button>
-                    <button class="w-full flex items-center gap-2 px-3 py-2 rounded-lg text-sm text-rose-400 hover:bg-rose-500/10 transition-colors">
+                    <button
+                        class="w-full flex items-center gap-2 px-3 py-2 rounded-lg text-sm text-rose-400 hover:bg-rose-500/10 transition-colors"
+                        on:click=sign_out
+                    >
                         <svg class="w-4 h-4" fill="none" viewBox="0 0 24 24" stroke="currentColor" stroke-width="2">
                             <path stroke-linecap="round" stroke-linejoin="round" d="M17 16l4-4m0 0l-4-4m4 4H7m6 4v1a3 3 0 01-3 3H6a3 3 0 01-3-3V7a3 3 0 013-3h4a3 3 0 013 3v1"/>
                         </svg>
@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+use module_auth::types::SessionInfo;
+use synapse_core::domain::events::delegations::Delegation;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
 /// Settings tab/section
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SettingsTab {
@@ -234,80 +239,116 @@ impl Default for ProfileSettingsData {
 /// Active session data
 #[derive(Clone, Debug)]
 pub struct ActiveSession {
-    pub id: String,
+    pub id: Uuid,
     pub device_name: String,
     pub device_type: String,
-    pub location: String,
-    pub ip_address: String,
     pub last_active: String,
     pub is_current: bool,
 }
 
 impl ActiveSession {
-    pub fn mock_sessions() -> Vec<ActiveSession> {
-        vec![
-            ActiveSession {
-                id: "session-1".to_string(),
-                device_name: "MacBook Pro".to_string(),
-                device_type: "desktop".to_string(),
-                location: "San Francisco, CA".to_string(),
-                ip_address: "192.168.1.xxx".to_string(),
-                last_active: "Now".to_string(),
-                is_current: true,
-            },
-            ActiveSession {
-                id: "session-2".to_string(),
-                device_name: "iPhone 15".to_string(),
-                device_type: "mobile".to_string(),
-                location: "San Francisco, CA".to_string(),
-                ip_address: "192.168.1.xxx".to_string(),
-                last_active: "2 hours ago".to_string(),
-                is_current: false,
+    pub fn from_info(info: &SessionInfo, now: OffsetDateTime) -> ActiveSession {
+        let (device_name, device_type) = describe_user_agent(info.user_agent.as_deref());
+        ActiveSession {
+            id: info.id,
+            device_name,
+            device_type: device_type.to_string(),
+            last_active: if info.current {
+                "Now".to_string()
+            } else {
+                time_ago(info.last_seen_at, now)
             },
-            ActiveSession {
-                id: "session-3".to_string(),
-                device_name: "Firefox on Linux".to_string(),
-                device_type: "desktop".to_string(),
-                location: "New York, NY".to_string(),
-                ip_address: "10.0.0.xxx".to_string(),
-                last_active: "3 days ago".to_string(),
-                is_current: false,
-            },
-        ]
+            is_current: info.current,
+        }
     }
 }
 
-/// Connected application
+/// Browser and platform named by a `User-Agent`, and whether it is "mobile" or
+/// "desktop".
+fn describe_user_agent(user_agent: Option<&str>) -> (String, &'static str) {
+    let Some(ua) = user_agent else {
+        return ("Unknown device".to_string(), "desktop");
+    };
+    let browser = if ua.contains("Edg/") {
+        "Edge"
+    } else if ua.contains("Firefox/") {
+        "Firefox"
+    } else if ua.contains("Chrome/") {
+        "Chrome"
+    } else if ua.contains("Safari/") {
+        "Safari"
+    } else {
+        "Browser"
+    };
+    let (platform, device_type) = if ua.contains("iPhone") {
+        ("iPhone", "mobile")
+    } else if ua.contains("iPad") {
+        ("iPad", "mobile")
+    } else if ua.contains("Android") {
+        ("Android", "mobile")
+    } else if ua.contains("Mac OS X") {
+        ("macOS", "desktop")
+    } else if ua.contains("Windows") {
+        ("Windows", "desktop")
+    } else if ua.contains("Linux") {
+        ("Linux", "desktop")
+    } else {
+        return (ua.to_string(), "desktop");
+    };
+    (format!("{browser} on {platform}"), device_type)
+}
+
+fn time_ago(then: OffsetDateTime, now: OffsetDateTime) -> String {
+    let elapsed = now - then;
+    let (count, unit) = if elapsed.whole_days() > 0 {
+        (elapsed.whole_days(), "day")
+    } else if elapsed.whole_hours() > 0 {
+        (elapsed.whole_hours(), "hour")
+    } else if elapsed.whole_minutes() > 0 {
+        (elapsed.whole_minutes(), "minute")
+    } else {
+        return "Just now".to_string();
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// Device or app key delegated to sign events for the agent
 #[derive(Clone, Debug)]
 pub struct ConnectedApp {
-    pub id: String,
+    pub id: Uuid,
     pub name: String,
+    /// Shortened delegate key.
     pub description: String,
     pub permissions: Vec<String>,
     pub connected_date: String,
-    pub last_used: String,
+    pub expires: String,
 }
 
 impl ConnectedApp {
-    pub fn mock_apps() -> Vec<ConnectedApp> {
-        vec![
-            ConnectedApp {
-                id: "app-1".to_string(),
-                name: "Matrix Mobile".to_string(),
-                description: "Official mobile application".to_string(),
-                permissions: vec!["Read profile".to_string(), "Post content".to_string(), "Send DMs".to_string()],
-                connected_date: "Jan 15, 2024".to_string(),
-                last_used: "2 hours ago".to_string(),
-            },
-            ConnectedApp {
-                id: "app-2".to_string(),
-                name: "Analytics Dashboard".to_string(),
-                description: "Track your engagement metrics".to_string(),
-                permissions: vec!["Read profile".to_string(), "Read posts".to_string()],
-                connected_date: "Feb 20, 2024".to_string(),
-                last_used: "1 week ago".to_string(),
+    pub fn from_delegation(delegation: &Delegation, now: OffsetDateTime) -> ConnectedApp {
+        let scope = &delegation.scope;
+        let mut permissions: Vec<String> = scope
+            .module_kinds
+            .iter()
+            .map(|kind| format!("All {kind} events"))
+            .chain(scope.event_types.iter().cloned())
+            .collect();
+        if permissions.is_empty() {
+            permissions.push("All events".to_string());
+        }
+        let key = &delegation.delegate;
+        ConnectedApp {
+            id: delegation.id,
+            name: scope.name.clone(),
+            description: format!("Key {}…{}", &key[..key.len().min(8)], &key[key.len().saturating_sub(6)..]),
+            permissions,
+            connected_date: time_ago(delegation.created_at, now),
+            expires: match scope.expires_at {
+                Some(expires_at) => format!("Expires in {} days", (expires_at - now).whole_days().max(0)),
+                None => "Never expires".to_string(),
             },
-        ]
+        }
     }
 }
 
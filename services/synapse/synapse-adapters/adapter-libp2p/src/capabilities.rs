@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! SNP capability exchange.
+//!
+//! When a peer connects we ask it for the SNP versions and modules it supports
+//! and settle on the newest version both sides speak. Requests to the peer are
+//! stamped with that version; until the exchange completes, and for Synapses
+//! that predate it, `SNP_BASELINE_VERSION` is used.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use libp2p::PeerId;
+use protocol_snp::{
+    CAPABILITIES, Destination::Synapse, GET_CAPABILITIES, SNP_BASELINE_VERSION, SnpCapabilities,
+    SnpMessage, SnpPayload::Command, SnpPayload::Reply, negotiate_version,
+};
+use synapse_core::TransportError;
+use synapse_core::domain::events::Event;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::control::Control;
+use crate::errors::Libp2pAdapterError;
+
+/// Negotiated capabilities of connected peers.
+pub type PeerCapabilities = Arc<DashMap<PeerId, NegotiatedCapabilities>>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NegotiatedCapabilities {
+    /// SNP version requests to the peer are stamped with.
+    pub version: &'static str,
+    /// What the peer advertised.
+    pub advertised: SnpCapabilities,
+}
+
+impl NegotiatedCapabilities {
+    /// Settles on a version with a peer advertising `advertised`, if there is one
+    /// both sides speak.
+    pub fn negotiate(advertised: SnpCapabilities) -> Option<Self> {
+        let version = negotiate_version(&advertised.versions)?;
+        Some(Self {
+            version,
+            advertised,
+        })
+    }
+}
+
+/// SNP version to stamp on a request to `peer`.
+pub fn version_for(capabilities: &PeerCapabilities, peer: &PeerId) -> &'static str {
+    capabilities
+        .get(peer)
+        .map(|c| c.version)
+        .unwrap_or(SNP_BASELINE_VERSION)
+}
+
+/// The event answering a capabilities request.
+pub fn capabilities_event(capabilities: &SnpCapabilities) -> Result<Event, Libp2pAdapterError> {
+    Ok(Event::new()
+        .with_event_type(CAPABILITIES)
+        .with_module_kind("core")
+        .with_content(serde_json::to_string(capabilities)?)
+        .build())
+}
+
+/// Reads the capabilities out of a reply to a capabilities request. Synapses
+/// that do not know the request answer without a capabilities event.
+pub fn parse_reply(events: &[Event]) -> Result<SnpCapabilities, Libp2pAdapterError> {
+    match events
+        .iter()
+        .find(|e| e.event_type == CAPABILITIES)
+        .and_then(|e| e.content.as_deref())
+    {
+        Some(content) => Ok(serde_json::from_str(content)?),
+        None => Ok(SnpCapabilities::baseline()),
+    }
+}
+
+/// Asks `peer` for its capabilities through the swarm behind `ctrl_tx` and
+/// records the negotiated result.
+pub async fn exchange(
+    ctrl_tx: &mpsc::Sender<Control>,
+    capabilities: &PeerCapabilities,
+    peer: PeerId,
+) -> Result<(), TransportError> {
+    let request = SnpMessage::new(
+        Synapse {
+            id: peer.to_string(),
+        },
+        Command {
+            action: GET_CAPABILITIES.to_string(),
+            event: Event::new()
+                .with_event_type(GET_CAPABILITIES)
+                .with_module_kind("core")
+                .build(),
+        },
+    );
+    let (ret_tx, ret_rx) = oneshot::channel();
+    ctrl_tx
+        .send(Control::SendSnp {
+            peer,
+            request,
+            ret: ret_tx,
+        })
+        .await
+        .map_err(|_| TransportError::Other("swarm control channel closed".into()))?;
+    let response = ret_rx
+        .await
+        .map_err(|_| TransportError::Other("swarm dropped response".into()))??;
+
+    let advertised = match response.payload {
+        Reply {
+            ok: true, events, ..
+        } => parse_reply(&events)?,
+        // A failed exchange leaves us speaking the baseline version
+        Reply { ok: false, .. } => SnpCapabilities::baseline(),
+        _ => return Err(TransportError::Other("unexpected response payload".into())),
+    };
+    let negotiated = NegotiatedCapabilities::negotiate(advertised)
+        .ok_or_else(|| TransportError::Protocol(format!("no SNP version in common with {peer}")))?;
+    tracing::debug!("speaking SNP {} with {peer}", negotiated.version);
+    capabilities.insert(peer, negotiated);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_snp::SNP_VERSION;
+    use synapse_core::domain::modules::ModuleCapability;
+
+    #[test]
+    fn test_capabilities_round_trip_and_negotiate() {
+        let local = SnpCapabilities::local(vec![ModuleCapability {
+            kind: "posts".to_string(),
+            version: "0.1.0".to_string(),
+            event_types: vec![],
+        }]);
+        let event = capabilities_event(&local).unwrap();
+
+        let negotiated = NegotiatedCapabilities::negotiate(parse_reply(&[event]).unwrap()).unwrap();
+        assert_eq!(negotiated.version, SNP_VERSION);
+        assert!(negotiated.advertised.supports_module("posts"));
+        assert!(!negotiated.advertised.supports_module("chess"));
+    }
+
+    #[test]
+    fn test_silent_peer_falls_back_to_baseline() {
+        let negotiated = NegotiatedCapabilities::negotiate(parse_reply(&[]).unwrap()).unwrap();
+
+        assert_eq!(negotiated.version, SNP_BASELINE_VERSION);
+        assert!(negotiated.advertised.supports_module("chess"));
+    }
+}
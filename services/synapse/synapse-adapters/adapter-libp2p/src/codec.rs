@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Request-response codec for SNP streams, encoding messages as JSON or CBOR
+//! depending on the stream protocol negotiated with the peer.
+
+use std::io;
+
+use async_trait::async_trait;
+use futures::prelude::*;
+use libp2p::StreamProtocol;
+use libp2p::request_response::Codec;
+use protocol_snp::{Encoding, SnpMessage};
+
+/// Largest request accepted from a peer.
+pub const MAX_REQUEST_SIZE: u64 = 1024 * 1024;
+/// Largest response accepted from a peer.
+pub const MAX_RESPONSE_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Clone, Debug, Default)]
+pub struct SnpCodec;
+
+async fn read_message<T>(protocol: &StreamProtocol, io: &mut T, limit: u64) -> io::Result<SnpMessage>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut bytes = Vec::new();
+    io.take(limit).read_to_end(&mut bytes).await?;
+    Encoding::of_protocol(protocol.as_ref()).decode(&bytes)
+}
+
+async fn write_message<T>(protocol: &StreamProtocol, io: &mut T, message: SnpMessage) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+{
+    let bytes = Encoding::of_protocol(protocol.as_ref()).encode(&message)?;
+    io.write_all(&bytes).await
+}
+
+#[async_trait]
+impl Codec for SnpCodec {
+    type Protocol = StreamProtocol;
+    type Request = SnpMessage;
+    type Response = SnpMessage;
+
+    async fn read_request<T>(&mut self, protocol: &StreamProtocol, io: &mut T) -> io::Result<SnpMessage>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_message(protocol, io, MAX_REQUEST_SIZE).await
+    }
+
+    async fn read_response<T>(&mut self, protocol: &StreamProtocol, io: &mut T) -> io::Result<SnpMessage>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_message(protocol, io, MAX_RESPONSE_SIZE).await
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+        request: SnpMessage,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_message(protocol, io, request).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        protocol: &StreamProtocol,
+        io: &mut T,
+        response: SnpMessage,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        write_message(protocol, io, response).await
+    }
+}
@@ -6,34 +6,58 @@ use crate::transport::TransportConfig;
 
 use base64::{Engine as _, engine::general_purpose};
 
-use libp2p::ping;
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::behaviour::toggle::Toggle;
+use libp2p::{autonat, connection_limits, dcutr, gossipsub, identify, ping, relay};
 use libp2p::request_response::Event as ReqResEvent;
-use libp2p::request_response::json::Behaviour as JsonBehaviour;
+use libp2p::request_response::Behaviour as ReqResBehaviour;
 use libp2p::{Multiaddr, identity};
 
-use libp2p_kad::{self, Event as KadEvent, store::MemoryStore};
+use libp2p_kad::{self, Event as KadEvent};
 use libp2p_swarm_derive::NetworkBehaviour;
 
+use crate::codec::SnpCodec;
+use crate::record_store::DurableStore;
 use protocol_snp::SnpMessage;
+use std::convert::Infallible;
+use std::time::Duration;
 use synapse_config::SynapseConfig;
 
 #[derive(NetworkBehaviour)]
 #[behaviour(out_event = "Libp2pEvent")]
 pub struct Libp2pBehaviour {
+    pub limits: connection_limits::Behaviour,
     //pub ping: ping::Behaviour,
-    pub kad: libp2p_kad::Behaviour<MemoryStore>,
-    pub req_res: JsonBehaviour<SnpMessage, SnpMessage>,
+    pub kad: libp2p_kad::Behaviour<DurableStore>,
+    pub req_res: ReqResBehaviour<SnpCodec>,
+    pub gossipsub: gossipsub::Behaviour,
+    pub identify: Toggle<identify::Behaviour>,
+    pub autonat: Toggle<autonat::Behaviour>,
+    pub relay_server: Toggle<relay::Behaviour>,
+    pub relay_client: Toggle<relay::client::Behaviour>,
+    pub dcutr: Toggle<dcutr::Behaviour>,
 }
 
-type JsonReqResEvent = ReqResEvent<SnpMessage, SnpMessage>;
+type SnpReqResEvent = ReqResEvent<SnpMessage, SnpMessage>;
 
 #[derive(Debug)]
 pub enum Libp2pEvent {
     Ping(ping::Event),
     Kad(KadEvent),
-    ReqRes(JsonReqResEvent),
+    ReqRes(SnpReqResEvent),
+    Gossipsub(gossipsub::Event),
+    Identify(Box<identify::Event>),
+    Autonat(autonat::Event),
+    RelayServer(relay::Event),
+    RelayClient(relay::client::Event),
+    Dcutr(dcutr::Event),
 }
 
+impl From<Infallible> for Libp2pEvent {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
 impl From<ping::Event> for Libp2pEvent {
     fn from(e: ping::Event) -> Self {
         Libp2pEvent::Ping(e)
@@ -44,11 +68,42 @@ impl From<KadEvent> for Libp2pEvent {
         Libp2pEvent::Kad(e)
     }
 }
-impl From<JsonReqResEvent> for Libp2pEvent {
-    fn from(e: JsonReqResEvent) -> Self {
+impl From<SnpReqResEvent> for Libp2pEvent {
+    fn from(e: SnpReqResEvent) -> Self {
         Libp2pEvent::ReqRes(e)
     }
 }
+impl From<identify::Event> for Libp2pEvent {
+    fn from(e: identify::Event) -> Self {
+        Libp2pEvent::Identify(Box::new(e))
+    }
+}
+impl From<gossipsub::Event> for Libp2pEvent {
+    fn from(e: gossipsub::Event) -> Self {
+        Libp2pEvent::Gossipsub(e)
+    }
+}
+
+impl From<autonat::Event> for Libp2pEvent {
+    fn from(e: autonat::Event) -> Self {
+        Libp2pEvent::Autonat(e)
+    }
+}
+impl From<relay::Event> for Libp2pEvent {
+    fn from(e: relay::Event) -> Self {
+        Libp2pEvent::RelayServer(e)
+    }
+}
+impl From<relay::client::Event> for Libp2pEvent {
+    fn from(e: relay::client::Event) -> Self {
+        Libp2pEvent::RelayClient(e)
+    }
+}
+impl From<dcutr::Event> for Libp2pEvent {
+    fn from(e: dcutr::Event) -> Self {
+        Libp2pEvent::Dcutr(e)
+    }
+}
 
 pub fn parse_config(config: &SynapseConfig) -> Result<TransportConfig, Libp2pAdapterError> {
     let s = std::fs::read_to_string(&config.identity.private_key_path)?;
@@ -67,12 +122,64 @@ pub fn parse_config(config: &SynapseConfig) -> Result<TransportConfig, Libp2pAda
         announce_addrs.push(addr);
     }
 
+    let mut relay_addrs: Vec<Multiaddr> = Vec::new();
+    for s in config.p2p.relays.clone() {
+        let addr: Multiaddr = s.parse().expect("Invalid Multiaddr in LIBP2P_RELAYS");
+        relay_addrs.push(addr);
+    }
+
     let listen_addr: Multiaddr = config.p2p.listen_addrs.clone();
+    let quic_listen_addr = if config.p2p.enable_quic {
+        quic_listen_addr(&listen_addr)
+    } else {
+        None
+    };
 
     Ok(TransportConfig {
         keypair,
         bootstrap_addrs,
         listen_addr,
+        quic_listen_addr,
         announce_addrs,
+        max_connections: config.p2p.max_connections,
+        idle_connection_timeout: Duration::from_secs(config.p2p.idle_connection_timeout_secs),
+        dht_store_path: config.p2p.dht_store_path.clone(),
+        enable_identify: config.p2p.enable_identify,
+        enable_autonat: config.p2p.enable_autonat,
+        enable_relay_server: config.p2p.enable_relay_server,
+        enable_relay_client: config.p2p.enable_relay_client,
+        relay_addrs,
+        enable_dcutr: config.p2p.enable_dcutr,
     })
 }
+
+/// QUIC counterpart of a TCP listen address: the same IP and port over UDP.
+pub fn quic_listen_addr(tcp_addr: &Multiaddr) -> Option<Multiaddr> {
+    let mut quic = Multiaddr::empty();
+    let mut has_port = false;
+    for protocol in tcp_addr.iter() {
+        match protocol {
+            Protocol::Tcp(port) => {
+                quic.push(Protocol::Udp(port));
+                quic.push(Protocol::QuicV1);
+                has_port = true;
+            }
+            other => quic.push(other),
+        }
+    }
+    has_port.then_some(quic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quic_listen_addr() {
+        let tcp: Multiaddr = "/ip4/0.0.0.0/tcp/4000".parse().unwrap();
+        let quic: Multiaddr = "/ip4/0.0.0.0/udp/4000/quic-v1".parse().unwrap();
+
+        assert_eq!(quic_listen_addr(&tcp), Some(quic));
+        assert_eq!(quic_listen_addr(&"/ip4/0.0.0.0".parse().unwrap()), None);
+    }
+}
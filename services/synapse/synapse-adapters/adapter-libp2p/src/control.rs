@@ -24,4 +24,18 @@ pub enum Control {
         key: Vec<u8>,
         ret: oneshot::Sender<Vec<PeerId>>,
     },
+    Dial {
+        peer: PeerId,
+        addresses: Vec<String>,
+    },
+    Subscribe {
+        topic: String,
+    },
+    Unsubscribe {
+        topic: String,
+    },
+    Publish {
+        topic: String,
+        message: SnpMessage,
+    },
 }
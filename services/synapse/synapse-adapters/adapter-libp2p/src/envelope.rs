@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! SNP envelope signing and verification.
+//!
+//! Every `SnpMessage` leaving this Synapse is signed with the node keypair loaded
+//! in `parse_config`. The sender's public key travels in `agent_public_key`
+//! (URL-safe base64 protobuf, the same encoding as `identity.public_key` in the
+//! Synapse config) and the signature over `SnpMessage::signing_payload` travels
+//! in `signature`. A receiver checks that the key belongs to the `PeerId` the
+//! message arrived from before trusting anything inside it.
+
+use crate::errors::Libp2pAdapterError;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use libp2p::PeerId;
+use libp2p::identity::{Keypair, PublicKey};
+use protocol_snp::SnpMessage;
+
+/// Signs `message` with `keypair`, overwriting `agent_public_key` and `signature`.
+pub fn seal(keypair: &Keypair, mut message: SnpMessage) -> Result<SnpMessage, Libp2pAdapterError> {
+    message.agent_public_key = encode_public_key(&keypair.public());
+    let signature = keypair.sign(&message.signing_payload())?;
+    message.signature = URL_SAFE_NO_PAD.encode(signature);
+    Ok(message)
+}
+
+/// Verifies that `message` was signed by the Synapse behind `peer`.
+pub fn verify(peer: &PeerId, message: &SnpMessage) -> Result<(), Libp2pAdapterError> {
+    let public_key = decode_public_key(&message.agent_public_key)
+        .map_err(|e| Libp2pAdapterError::InvalidEnvelope(format!("invalid sender key: {e}")))?;
+
+    if public_key.to_peer_id() != *peer {
+        return Err(Libp2pAdapterError::InvalidEnvelope(format!(
+            "sender key does not belong to peer {peer}"
+        )));
+    }
+
+    let signature = URL_SAFE_NO_PAD
+        .decode(&message.signature)
+        .map_err(|e| Libp2pAdapterError::InvalidEnvelope(format!("invalid signature encoding: {e}")))?;
+
+    if !public_key.verify(&message.signing_payload(), &signature) {
+        return Err(Libp2pAdapterError::InvalidEnvelope(
+            "signature verification failed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Encodes a libp2p public key the way Synapse public keys are shared.
+pub fn encode_public_key(public_key: &PublicKey) -> String {
+    URL_SAFE_NO_PAD.encode(public_key.encode_protobuf())
+}
+
+/// Decodes a Synapse public key produced by [`encode_public_key`].
+pub fn decode_public_key(s: &str) -> Result<PublicKey, Libp2pAdapterError> {
+    let bytes = URL_SAFE_NO_PAD.decode(s)?;
+    Ok(PublicKey::try_decode_protobuf(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_snp::{Destination, SnpPayload};
+    use synapse_core::domain::events::Event;
+
+    fn command() -> SnpMessage {
+        SnpMessage::new(
+            Destination::Local,
+            SnpPayload::Command {
+                action: "posts:list_posts".to_string(),
+                event: Event::new()
+                    .with_event_type("posts:list_posts")
+                    .with_module_kind("posts")
+                    .build(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_sealed_message_verifies_for_sender() {
+        let keypair = Keypair::generate_secp256k1();
+        let sealed = seal(&keypair, command()).unwrap();
+
+        assert!(verify(&keypair.public().to_peer_id(), &sealed).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_other_peer() {
+        let keypair = Keypair::generate_secp256k1();
+        let other = Keypair::generate_secp256k1();
+        let sealed = seal(&keypair, command()).unwrap();
+
+        assert!(verify(&other.public().to_peer_id(), &sealed).is_err());
+    }
+
+    #[test]
+    fn test_rejects_tampered_payload() {
+        let keypair = Keypair::generate_secp256k1();
+        let mut sealed = seal(&keypair, command()).unwrap();
+        if let SnpPayload::Command { event, .. } = &mut sealed.payload {
+            event.agent = "someone-else".to_string();
+        }
+
+        assert!(verify(&keypair.public().to_peer_id(), &sealed).is_err());
+    }
+
+    #[test]
+    fn test_rejects_placeholder_envelope() {
+        let keypair = Keypair::generate_secp256k1();
+        let mut message = command();
+        message.agent_public_key = "agent_public_key".to_string();
+        message.signature = "signature".to_string();
+
+        assert!(verify(&keypair.public().to_peer_id(), &message).is_err());
+    }
+}
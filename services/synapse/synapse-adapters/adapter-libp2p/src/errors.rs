@@ -15,6 +15,9 @@ pub enum Libp2pAdapterError {
     #[error(transparent)]
     Libp2pDecodingError(#[from] libp2p::identity::DecodingError),
 
+    #[error(transparent)]
+    Libp2pSigningError(#[from] libp2p::identity::SigningError),
+
     #[error(transparent)]
     Libp2pTransportError(#[from] libp2p::core::transport::TransportError<std::io::Error>),
 
@@ -23,6 +26,12 @@ pub enum Libp2pAdapterError {
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid envelope: {0}")]
+    InvalidEnvelope(String),
 }
 
 impl From<Libp2pAdapterError> for TransportError {
@@ -34,8 +43,11 @@ impl From<Libp2pAdapterError> for TransportError {
                 TransportError::Protocol(e.to_string())
             }
             Libp2pAdapterError::Libp2pDecodingError(e) => TransportError::Io(e.to_string()),
+            Libp2pAdapterError::Libp2pSigningError(e) => TransportError::Protocol(e.to_string()),
             Libp2pAdapterError::Libp2pTransportError(e) => TransportError::Protocol(e.to_string()),
             Libp2pAdapterError::Libp2pNoiseError(e) => TransportError::Protocol(e.to_string()),
+            Libp2pAdapterError::Json(e) => TransportError::Protocol(e.to_string()),
+            Libp2pAdapterError::InvalidEnvelope(e) => TransportError::Protocol(e),
         }
     }
 }
@@ -1,10 +1,16 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod capabilities;
+pub mod codec;
 pub mod config;
 pub mod control;
 pub mod discovery;
+pub mod envelope;
 pub mod errors;
+pub mod peers;
+pub mod record_store;
+pub mod subscriptions;
 pub mod swarm;
 pub mod transport;
 
@@ -14,14 +20,24 @@ use std::sync::Arc;
 use synapse_config::SynapseConfig;
 use synapse_core::TransportError;
 use synapse_core::ports::federation::MessageHandler;
+use synapse_core::ports::federation::subscriptions::SubscriptionStore;
+use synapse_core::ports::peers::peers::PeerStore;
 
 pub async fn create_libp2p_transport(
     config: SynapseConfig,
     handler: Arc<dyn MessageHandler + Send + Sync>,
     known_peers: Arc<DashMap<String, String>>,
+    peer_store: Arc<dyn PeerStore>,
+    subscription_store: Arc<dyn SubscriptionStore>,
 ) -> Result<Libp2pTransport, TransportError> {
     let transport_config = parse_config(&config)?;
-    let transport = Libp2pTransport::new(transport_config, handler, known_peers);
+    let transport = Libp2pTransport::new(
+        transport_config,
+        handler,
+        known_peers,
+        peer_store,
+        subscription_store,
+    );
     Ok(transport)
 }
 
@@ -29,8 +45,12 @@ pub async fn initialize_p2p(
     config: SynapseConfig,
     handler: Arc<dyn MessageHandler + Send + Sync>,
     known_peers: Arc<DashMap<String, String>>,
+    peer_store: Arc<dyn PeerStore>,
+    subscription_store: Arc<dyn SubscriptionStore>,
 ) -> Result<Libp2pTransport, TransportError> {
-    let mut transport = create_libp2p_transport(config, handler, known_peers).await?;
+    let mut transport =
+        create_libp2p_transport(config, handler, known_peers, peer_store, subscription_store)
+            .await?;
     transport.start().await?;
     Ok(transport)
 }
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Keeps the peer store in step with the swarm and redials known Synapses.
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use libp2p::PeerId;
+use synapse_core::ports::peers::peers::PeerStore;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::control::Control;
+
+/// Dials the best known peers from previous runs, up to `limit` of them.
+pub async fn redial_known_peers(
+    peer_store: Arc<dyn PeerStore>,
+    ctrl_tx: mpsc::Sender<Control>,
+    known_peers: Arc<DashMap<String, String>>,
+    limit: u32,
+) {
+    let peers = match peer_store.list_peers(limit).await {
+        Ok(peers) => peers,
+        Err(e) => {
+            warn!("failed to load known peers: {e}");
+            return;
+        }
+    };
+    info!("Redialing {} known peers", peers.len());
+
+    for peer in peers {
+        if let Some(public_key) = &peer.public_key {
+            known_peers.insert(public_key.clone(), peer.peer_id.clone());
+        }
+        if !peer.should_redial() {
+            continue;
+        }
+        let Ok(peer_id) = PeerId::from_str(&peer.peer_id) else {
+            warn!("discarding malformed peer id {}", peer.peer_id);
+            continue;
+        };
+        let dial = Control::Dial {
+            peer: peer_id,
+            addresses: peer.addresses,
+        };
+        if ctrl_tx.send(dial).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Records a failed dial and, unless the peer has failed too often, dials it
+/// again after its backoff delay.
+pub async fn redial_after_failure(
+    peer_store: Arc<dyn PeerStore>,
+    ctrl_tx: mpsc::Sender<Control>,
+    peer_id: PeerId,
+) {
+    let peer = match peer_store.record_failure(&peer_id.to_string()).await {
+        Ok(peer) => peer,
+        Err(e) => {
+            warn!("failed to record dial failure for {peer_id}: {e}");
+            return;
+        }
+    };
+    if !peer.should_redial() {
+        debug!("giving up on {peer_id} after {} failures", peer.failures);
+        return;
+    }
+
+    let delay = peer.redial_delay();
+    debug!("redialing {peer_id} in {delay:?}");
+    tokio::time::sleep(delay).await;
+    let _ = ctrl_tx
+        .send(Control::Dial {
+            peer: peer_id,
+            addresses: peer.addresses,
+        })
+        .await;
+}
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Kademlia record store that survives restarts.
+//!
+//! Records live in a `MemoryStore` as before; mutations mark the store dirty and
+//! the swarm loop calls [`DurableStore::flush`] periodically to write a snapshot
+//! to disk. The snapshot is loaded when the swarm is created, so provider records
+//! for hosted profiles are still served (and republished by Kademlia) after a
+//! restart. Expiry is stored as wall-clock time since `Instant`s do not outlive
+//! the process.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use libp2p::{Multiaddr, PeerId};
+use libp2p_kad::store::{MemoryStore, RecordStore, Result as StoreResult};
+use libp2p_kad::{ProviderRecord, Record, RecordKey};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::errors::Libp2pAdapterError;
+
+/// How often the swarm loop flushes the record store to disk.
+pub const RECORD_STORE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Default)]
+struct Snapshot {
+    records: Vec<StoredRecord>,
+    providers: Vec<StoredProvider>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    key: String,
+    value: String,
+    publisher: Option<String>,
+    /// Unix timestamp in seconds.
+    expires_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredProvider {
+    key: String,
+    provider: String,
+    addresses: Vec<String>,
+    /// Unix timestamp in seconds.
+    expires_at: Option<i64>,
+}
+
+pub struct DurableStore {
+    inner: MemoryStore,
+    /// Snapshot location; `None` keeps records in memory only.
+    path: Option<PathBuf>,
+    /// Keys with at least one provider record, since `MemoryStore` cannot list them.
+    provider_keys: HashSet<RecordKey>,
+    dirty: bool,
+}
+
+impl DurableStore {
+    /// Opens the store for `local_id`, loading the snapshot at `path` if there is one.
+    pub fn open(local_id: PeerId, path: Option<PathBuf>) -> Self {
+        let mut store = Self {
+            inner: MemoryStore::new(local_id),
+            path,
+            provider_keys: HashSet::new(),
+            dirty: false,
+        };
+        if let Err(e) = store.load() {
+            warn!("failed to load Kademlia records: {e}");
+        }
+        store.dirty = false;
+        store
+    }
+
+    fn load(&mut self) -> Result<(), Libp2pAdapterError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !path.exists() {
+            return Ok(());
+        }
+        let snapshot: Snapshot = serde_json::from_slice(&std::fs::read(path)?)?;
+        let now = OffsetDateTime::now_utc();
+
+        let (mut records, mut providers) = (0, 0);
+        for stored in snapshot.records {
+            let Some(expires) = to_instant(stored.expires_at, now) else {
+                continue;
+            };
+            let record = Record {
+                key: RecordKey::new(&URL_SAFE_NO_PAD.decode(stored.key)?),
+                value: URL_SAFE_NO_PAD.decode(stored.value)?,
+                publisher: stored.publisher.and_then(|p| p.parse().ok()),
+                expires,
+            };
+            if self.put(record).is_ok() {
+                records += 1;
+            }
+        }
+        for stored in snapshot.providers {
+            let Some(expires) = to_instant(stored.expires_at, now) else {
+                continue;
+            };
+            let Ok(provider) = stored.provider.parse() else {
+                continue;
+            };
+            let record = ProviderRecord {
+                key: RecordKey::new(&URL_SAFE_NO_PAD.decode(stored.key)?),
+                provider,
+                expires,
+                addresses: stored
+                    .addresses
+                    .iter()
+                    .filter_map(|a| a.parse::<Multiaddr>().ok())
+                    .collect(),
+            };
+            if self.add_provider(record).is_ok() {
+                providers += 1;
+            }
+        }
+        info!("Loaded {records} Kademlia records and {providers} provider records");
+        Ok(())
+    }
+
+    /// Writes a snapshot to disk if anything changed since the last flush.
+    pub fn flush(&mut self) -> Result<(), Libp2pAdapterError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let snapshot = Snapshot {
+            records: self
+                .inner
+                .records()
+                .map(|r| StoredRecord {
+                    key: URL_SAFE_NO_PAD.encode(r.key.as_ref()),
+                    value: URL_SAFE_NO_PAD.encode(&r.value),
+                    publisher: r.publisher.map(|p| p.to_string()),
+                    expires_at: r.expires.map(|e| to_unix(e, now)),
+                })
+                .collect(),
+            providers: self
+                .provider_keys
+                .iter()
+                .flat_map(|key| self.inner.providers(key))
+                .map(|p| StoredProvider {
+                    key: URL_SAFE_NO_PAD.encode(p.key.as_ref()),
+                    provider: p.provider.to_string(),
+                    addresses: p.addresses.iter().map(|a| a.to_string()).collect(),
+                    expires_at: p.expires.map(|e| to_unix(e, now)),
+                })
+                .collect(),
+        };
+
+        // Write to a temporary file first so a crash never leaves a torn snapshot
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&snapshot)?)?;
+        std::fs::rename(&tmp, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Converts a stored expiry back to a monotonic deadline. Returns `None` for
+/// records that have already expired.
+fn to_instant(expires_at: Option<i64>, now: OffsetDateTime) -> Option<Option<Instant>> {
+    let Some(expires_at) = expires_at else {
+        return Some(None);
+    };
+    let remaining = expires_at - now.unix_timestamp();
+    if remaining <= 0 {
+        return None;
+    }
+    Some(Some(Instant::now() + Duration::from_secs(remaining as u64)))
+}
+
+fn to_unix(expires: Instant, now: OffsetDateTime) -> i64 {
+    let remaining = expires.saturating_duration_since(Instant::now());
+    now.unix_timestamp() + remaining.as_secs() as i64
+}
+
+impl RecordStore for DurableStore {
+    type RecordsIter<'a> = <MemoryStore as RecordStore>::RecordsIter<'a>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        self.inner.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> StoreResult<()> {
+        self.inner.put(r)?;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        self.inner.remove(k);
+        self.dirty = true;
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.inner.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> StoreResult<()> {
+        let key = record.key.clone();
+        self.inner.add_provider(record)?;
+        self.provider_keys.insert(key);
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        self.inner.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.inner.provided()
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        self.inner.remove_provider(k, p);
+        if self.inner.providers(k).is_empty() {
+            self.provider_keys.remove(k);
+        }
+        self.dirty = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("menexus-{name}-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_provider_records_survive_reopen() {
+        let local_id = PeerId::random();
+        let path = snapshot_path("kad");
+        let key = RecordKey::new(&"profile-public-key");
+
+        let mut store = DurableStore::open(local_id, Some(path.clone()));
+        store
+            .add_provider(ProviderRecord {
+                key: key.clone(),
+                provider: local_id,
+                expires: Some(Instant::now() + Duration::from_secs(3600)),
+                addresses: vec!["/ip4/127.0.0.1/tcp/4000".parse().unwrap()],
+            })
+            .unwrap();
+        store.flush().unwrap();
+
+        let reopened = DurableStore::open(local_id, Some(path.clone()));
+        let providers = reopened.providers(&key);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].provider, local_id);
+        assert_eq!(reopened.provided().count(), 1);
+    }
+
+    #[test]
+    fn test_expired_records_are_not_loaded() {
+        let now = OffsetDateTime::now_utc();
+
+        assert!(to_instant(Some(now.unix_timestamp() - 1), now).is_none());
+        assert_eq!(to_instant(None, now), Some(None));
+        assert!(to_instant(Some(now.unix_timestamp() + 60), now).unwrap().is_some());
+    }
+}
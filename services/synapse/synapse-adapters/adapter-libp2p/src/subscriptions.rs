@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Subscriptions to events published by remote Synapses.
+//!
+//! Live events arrive over gossipsub on the publisher's event topic. Anything
+//! published while we were disconnected is replayed with
+//! `synapse:list_all_events` requests, page by page, starting shortly before
+//! the newest event seen. Creation times are chosen by clients, so events do
+//! not arrive in order; duplicates are recognized by id among the events seen
+//! within `REPLAY_OVERLAP` of the newest one.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use libp2p::PeerId;
+use protocol_snp::{
+    Destination::Synapse,
+    SnpMessage,
+    SnpPayload::{Command, Reply},
+};
+use synapse_core::TransportError;
+use synapse_core::domain::events::{Event, MAX_CLOCK_SKEW};
+use synapse_core::ports::events::event_repository::{EventCursor, EventFilter, EventOrder};
+use synapse_core::ports::federation::MessageHandler;
+use synapse_core::ports::federation::subscriptions::{FollowedModule, SubscriptionStore};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::control::Control;
+
+/// Number of events requested per replay page.
+pub const REPLAY_PAGE_SIZE: u32 = 500;
+
+/// How far before the newest event seen replay starts, and how long the ids
+/// of delivered events are remembered. Covers the clock skew allowed for
+/// client-supplied creation times.
+pub const REPLAY_OVERLAP: Duration = Duration::minutes(10);
+
+/// Subscriptions held by this Synapse, keyed by topic.
+pub type Subscriptions = Arc<DashMap<String, RemoteSubscription>>;
+
+#[derive(Clone, Debug)]
+pub struct RemoteSubscription {
+    pub publisher: PeerId,
+    pub publisher_public_key: String,
+    pub module_kind: String,
+    pub module_slug: Option<String>,
+    /// Cursor replay starts after when no event has been seen yet.
+    pub since: Option<EventCursor>,
+    /// Creation time of the newest event delivered so far.
+    pub newest_seen: Option<OffsetDateTime>,
+    /// Events delivered within `REPLAY_OVERLAP` of `newest_seen`, by id.
+    pub recent: HashMap<Uuid, OffsetDateTime>,
+}
+
+impl RemoteSubscription {
+    pub fn new(
+        publisher: PeerId,
+        publisher_public_key: String,
+        module_kind: String,
+        module_slug: Option<String>,
+        since: Option<EventCursor>,
+    ) -> Self {
+        Self {
+            publisher,
+            publisher_public_key,
+            module_kind,
+            module_slug,
+            since,
+            newest_seen: None,
+            recent: HashMap::new(),
+        }
+    }
+
+    /// The subscription as kept in a `SubscriptionStore`.
+    pub fn followed(&self, topic: &str) -> FollowedModule {
+        FollowedModule {
+            topic: topic.to_string(),
+            synapse_public_key: self.publisher_public_key.clone(),
+            module_kind: self.module_kind.clone(),
+            module_slug: self.module_slug.clone(),
+            since: self.since.clone(),
+            newest_seen: self.newest_seen,
+        }
+    }
+
+    /// Cursor the first replay page starts after.
+    pub fn replay_start(&self) -> Option<EventCursor> {
+        match self.newest_seen {
+            Some(newest) => Some(EventCursor::Timestamp(newest - REPLAY_OVERLAP)),
+            None => self.since.clone(),
+        }
+    }
+
+    /// The request for the page of events that follows `after`.
+    pub fn replay_request(&self, after: Option<EventCursor>) -> Event {
+        let filter = EventFilter {
+            module_kind: Some(self.module_kind.clone()),
+            module_slug: self.module_slug.clone(),
+            after,
+            order: EventOrder::OldestFirst,
+            limit: Some(REPLAY_PAGE_SIZE),
+            ..Default::default()
+        };
+        Event::new()
+            .with_event_type("synapse:list_all_events")
+            .with_module_kind("core")
+            .with_data(filter.to_data())
+            .build()
+    }
+
+    /// Records `event` as seen at `now`. Returns false if it was already
+    /// delivered, so duplicates from replay and live delivery are dropped.
+    ///
+    /// Creation times past `MAX_CLOCK_SKEW` from `now` count as that bound, so
+    /// an event dated in the future cannot move replay past events published
+    /// before it.
+    pub fn observe(&mut self, event: &Event, now: OffsetDateTime) -> bool {
+        if self.recent.contains_key(&event.id) {
+            return false;
+        }
+        let created_at = event.created_at.min(now + MAX_CLOCK_SKEW);
+        let newest = self
+            .newest_seen
+            .map_or(created_at, |newest| newest.max(created_at));
+        self.newest_seen = Some(newest);
+        self.recent.insert(event.id, created_at);
+        self.recent
+            .retain(|_, created_at| *created_at >= newest - REPLAY_OVERLAP);
+        true
+    }
+}
+
+/// Hands `event` to the handler if it is new for the subscription on `topic`.
+pub async fn deliver(
+    subscriptions: &Subscriptions,
+    store: &Arc<dyn SubscriptionStore>,
+    handler: &Arc<dyn MessageHandler + Send + Sync>,
+    topic: &str,
+    event: Event,
+) {
+    let (publisher_public_key, advanced) = {
+        let Some(mut subscription) = subscriptions.get_mut(topic) else {
+            return;
+        };
+        let previous = subscription.newest_seen;
+        if !subscription.observe(&event, OffsetDateTime::now_utc()) {
+            return;
+        }
+        let advanced = subscription
+            .newest_seen
+            .filter(|newest| Some(*newest) != previous);
+        (subscription.publisher_public_key.clone(), advanced)
+    };
+    if let Some(newest_seen) = advanced
+        && let Err(e) = store.record_seen(topic, newest_seen).await
+    {
+        warn!("failed to record replay position of {topic}: {e}");
+    }
+    if let Err(e) = handler
+        .handle_publication(publisher_public_key, event)
+        .await
+    {
+        warn!("failed to handle publication on {topic}: {e}");
+    }
+}
+
+/// Replays events missed on `topic` through the swarm behind `ctrl_tx`, one
+/// page at a time until a page comes back short.
+pub async fn replay(
+    ctrl_tx: &mpsc::Sender<Control>,
+    subscriptions: &Subscriptions,
+    store: &Arc<dyn SubscriptionStore>,
+    handler: &Arc<dyn MessageHandler + Send + Sync>,
+    topic: &str,
+) -> Result<(), TransportError> {
+    let Some(subscription) = subscriptions.get(topic).map(|s| s.clone()) else {
+        return Ok(());
+    };
+
+    let mut after = subscription.replay_start();
+    loop {
+        let events = replay_page(ctrl_tx, &subscription, after).await?;
+        let last = match events.last() {
+            Some(last) if events.len() >= REPLAY_PAGE_SIZE as usize => Some(last.id),
+            _ => None,
+        };
+        for event in events {
+            deliver(subscriptions, store, handler, topic, event).await;
+        }
+        // Stop once the page was the last one, or the subscription was dropped
+        match last {
+            Some(id) if subscriptions.contains_key(topic) => after = Some(EventCursor::Id(id)),
+            _ => return Ok(()),
+        }
+    }
+}
+
+/// Fetches the page of events of `subscription` that follows `after`.
+async fn replay_page(
+    ctrl_tx: &mpsc::Sender<Control>,
+    subscription: &RemoteSubscription,
+    after: Option<EventCursor>,
+) -> Result<Vec<Event>, TransportError> {
+    let request = SnpMessage::new(
+        Synapse {
+            id: subscription.publisher.to_string(),
+        },
+        Command {
+            action: "synapse:list_all_events".to_string(),
+            event: subscription.replay_request(after),
+        },
+    );
+    let (ret_tx, ret_rx) = oneshot::channel();
+    ctrl_tx
+        .send(Control::SendSnp {
+            peer: subscription.publisher,
+            request,
+            ret: ret_tx,
+        })
+        .await
+        .map_err(|_| TransportError::Other("swarm control channel closed".into()))?;
+    let response = ret_rx
+        .await
+        .map_err(|_| TransportError::Other("swarm dropped response".into()))??;
+
+    match response.payload {
+        Reply {
+            ok: true, events, ..
+        } => Ok(events),
+        Reply {
+            error: Some(error), ..
+        } => Err(TransportError::Other(error)),
+        _ => Err(TransportError::Other("unexpected response payload".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription() -> RemoteSubscription {
+        RemoteSubscription::new(
+            PeerId::random(),
+            "publisher".to_string(),
+            "posts".to_string(),
+            Some("general".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_observe_drops_duplicates_but_not_late_events() {
+        let mut subscription = subscription();
+        let newer = Event::new().build();
+        let mut late = Event::new().build();
+        late.created_at = newer.created_at - Duration::minutes(1);
+
+        let now = OffsetDateTime::now_utc();
+        assert!(subscription.observe(&newer, now));
+        assert!(!subscription.observe(&newer, now));
+        assert!(subscription.observe(&late, now));
+        assert!(!subscription.observe(&late, now));
+        assert_eq!(subscription.newest_seen, Some(newer.created_at));
+    }
+
+    #[test]
+    fn test_observe_forgets_ids_outside_the_overlap() {
+        let mut subscription = subscription();
+        let mut old = Event::new().build();
+        old.created_at -= REPLAY_OVERLAP * 2;
+        subscription.observe(&old, OffsetDateTime::now_utc());
+        subscription.observe(&Event::new().build(), OffsetDateTime::now_utc());
+
+        assert!(!subscription.recent.contains_key(&old.id));
+    }
+
+    #[test]
+    fn test_replay_resumes_before_newest_seen() {
+        let mut subscription = subscription();
+        let seen = Event::new().build();
+        subscription.observe(&seen, OffsetDateTime::now_utc());
+
+        let filter =
+            EventFilter::from_event(&subscription.replay_request(subscription.replay_start()))
+                .unwrap();
+        assert_eq!(
+            filter.after,
+            Some(EventCursor::Timestamp(seen.created_at - REPLAY_OVERLAP))
+        );
+        assert_eq!(filter.module_slug.as_deref(), Some("general"));
+        assert_eq!(filter.order, EventOrder::OldestFirst);
+        assert_eq!(filter.limit, Some(REPLAY_PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_future_event_does_not_skip_replay() {
+        let mut subscription = subscription();
+        let now = OffsetDateTime::now_utc();
+        let mut future = Event::new().build();
+        future.created_at = now + Duration::days(365);
+
+        assert!(subscription.observe(&future, now));
+        assert_eq!(subscription.newest_seen, Some(now + MAX_CLOCK_SKEW));
+        assert_eq!(
+            subscription.replay_start(),
+            Some(EventCursor::Timestamp(now + MAX_CLOCK_SKEW - REPLAY_OVERLAP))
+        );
+    }
+}
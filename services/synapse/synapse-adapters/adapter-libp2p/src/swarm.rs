@@ -1,46 +1,78 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+use crate::capabilities::{self, PeerCapabilities};
+use crate::codec::SnpCodec;
 use crate::config::Libp2pEvent;
 use crate::control::Control;
 use crate::discovery::setup_bootstrap;
+use crate::envelope;
 use crate::errors::Libp2pAdapterError;
+use crate::peers;
+use crate::record_store::{DurableStore, RECORD_STORE_FLUSH_INTERVAL};
+use crate::subscriptions::{self, Subscriptions};
 use crate::{config::Libp2pBehaviour, transport::TransportConfig};
 use dashmap::DashMap;
 use futures::StreamExt;
 use libp2p::StreamProtocol;
 use libp2p::request_response::OutboundRequestId;
 use libp2p::request_response::ProtocolSupport;
-use libp2p::request_response::{json, Config as ReqResConfig};
+use libp2p::request_response::{self, Config as ReqResConfig};
 use libp2p::request_response::{Event as ReqResEvent, Message as ReqResMessage};
+use libp2p::connection_limits::{self, ConnectionLimits};
+use libp2p::identify;
+use libp2p::multiaddr::Protocol;
+use libp2p::{autonat, dcutr, relay};
+use libp2p::swarm::behaviour::toggle::Toggle;
+use libp2p::gossipsub::{self, IdentTopic, MessageAuthenticity, PublishError, ValidationMode};
 use libp2p::{
+    Multiaddr, PeerId,
+    identity::Keypair,
     noise, ping,
-    swarm::{Swarm, SwarmEvent},
+    swarm::{DialError, Swarm, SwarmEvent, dial_opts::DialOpts},
     tcp, yamux,
 };
 use libp2p_kad::{
     self, Config as KadConfig, Event as KademliaEvent, GetProvidersOk, Mode, QueryId, QueryResult,
-    store::MemoryStore,
 };
 use protocol_snp::{
     Destination::{Local, Multicast, Synapse},
+    Encoding, GET_CAPABILITIES, SNP_BASELINE_VERSION, SUPPORTED_SNP_VERSIONS, SnpCapabilities, SnpErrorCode,
     SnpMessage,
     SnpPayload::{Command, Reply},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
-use synapse_core::TransportError;
 use synapse_core::domain::events::Event;
+use synapse_core::{CoreError, TransportError};
 use synapse_core::ports::federation::MessageHandler;
-use time::OffsetDateTime;
+use synapse_core::ports::federation::subscriptions::SubscriptionStore;
+use synapse_core::ports::peers::peers::PeerStore;
 use tokio::sync::{mpsc, oneshot};
 use tracing::info;
-use uuid::Uuid;
 
+/// Protocol version advertised over identify.
+pub const IDENTIFY_PROTOCOL_VERSION: &str = "/menexus/1.0.0";
+
+/// Builds the swarm: TCP, QUIC and relayed transports, Kademlia, SNP
+/// request-response, gossipsub and, if enabled, identify and NAT traversal.
+///
+/// AutoNAT confirms which observed addresses are publicly reachable. A Synapse
+/// behind NAT listens through the relays in `relay_addrs` so peers can reach it
+/// over a circuit, which DCUtR then tries to upgrade to a direct connection.
+/// Publicly reachable Synapses can offer the relay server to others.
 pub fn create_swarm(config: TransportConfig) -> Result<Swarm<Libp2pBehaviour>, Libp2pAdapterError> {
     info!("Creating swarm for config: {config:?}");
     let kad_cfg = KadConfig::default();
+    let limits = ConnectionLimits::default().with_max_established(Some(config.max_connections));
+    let idle_connection_timeout = config.idle_connection_timeout;
+    let dht_store_path = config.dht_store_path.clone();
+    let enable_identify = config.enable_identify;
+    let enable_autonat = config.enable_autonat;
+    let enable_relay_server = config.enable_relay_server;
+    let enable_relay_client = config.enable_relay_client;
+    let enable_dcutr = config.enable_dcutr && enable_relay_client;
     let mut swarm = libp2p::SwarmBuilder::with_existing_identity(config.keypair)
         .with_tokio()
         .with_tcp(
@@ -48,31 +80,78 @@ pub fn create_swarm(config: TransportConfig) -> Result<Swarm<Libp2pBehaviour>, L
             noise::Config::new,
             yamux::Config::default,
         )?
-        .with_behaviour(|key| {
+        // QUIC is always available for dialing; listening is opt-in
+        .with_quic()
+        .with_relay_client(noise::Config::new, yamux::Config::default)?
+        .with_behaviour(|key, relay_client| -> Result<Libp2pBehaviour, Box<dyn std::error::Error + Send + Sync>> {
+            let peer_id = key.public().to_peer_id();
             let mut kad = libp2p_kad::Behaviour::with_config(
                 key.public().to_peer_id(),
-                MemoryStore::new(key.public().to_peer_id()),
+                DurableStore::open(key.public().to_peer_id(), dht_store_path),
                 kad_cfg,
             );
             kad.set_mode(Some(Mode::Server));
-            let protocols = [(
-                StreamProtocol::new("/menexus/snp/1.0.0"),
-                ProtocolSupport::Full,
-            )];
+            // Every supported SNP version gets a stream protocol per encoding, newest
+            // version first and CBOR before JSON, so older Synapses can still open
+            // streams to us
+            let protocols = SUPPORTED_SNP_VERSIONS
+                .iter()
+                .flat_map(|version| Encoding::PREFERRED.map(|encoding| (version, encoding)))
+                .map(|(version, encoding)| {
+                    StreamProtocol::try_from_owned(protocol_snp::snp_protocol(version, encoding))
+                        .map(|protocol| (protocol, ProtocolSupport::Full))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
             // Use a longer request timeout to allow for network latency and handler I/O
             let req_res_config = ReqResConfig::default()
                 .with_request_timeout(Duration::from_secs(120));
             let req_res =
-                json::Behaviour::<SnpMessage, SnpMessage>::new(protocols, req_res_config);
+                request_response::Behaviour::with_codec(SnpCodec, protocols, req_res_config);
+            // Published events are signed by the Synapse identity so subscribers can
+            // check them against the publisher named in the topic
+            let gossipsub_config = gossipsub::ConfigBuilder::default()
+                .validation_mode(ValidationMode::Strict)
+                .build()?;
+            let gossipsub =
+                gossipsub::Behaviour::new(MessageAuthenticity::Signed(key.clone()), gossipsub_config)?;
+            // Identify tells peers which addresses we listen on and which they reached us at
+            let identify = enable_identify.then(|| {
+                identify::Behaviour::new(identify::Config::new(
+                    IDENTIFY_PROTOCOL_VERSION.to_string(),
+                    key.public(),
+                ))
+            });
+            // The relay transport is always built; the toggle decides whether
+            // we dial and listen through relays at all
+            let autonat = enable_autonat
+                .then(|| autonat::Behaviour::new(peer_id, autonat::Config::default()));
+            let relay_server = enable_relay_server
+                .then(|| relay::Behaviour::new(peer_id, relay::Config::default()));
             Ok(Libp2pBehaviour {
+                limits: connection_limits::Behaviour::new(limits),
                 //ping: ping::Behaviour::default(),
                 kad,
                 req_res,
+                gossipsub,
+                identify: Toggle::from(identify),
+                autonat: Toggle::from(autonat),
+                relay_server: Toggle::from(relay_server),
+                relay_client: Toggle::from(enable_relay_client.then_some(relay_client)),
+                dcutr: Toggle::from(enable_dcutr.then(|| dcutr::Behaviour::new(peer_id))),
             })
         })?
-        .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::from_secs(u64::MAX)))
+        .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(idle_connection_timeout))
         .build();
     swarm.listen_on(config.listen_addr)?;
+    if let Some(quic_addr) = config.quic_listen_addr {
+        swarm.listen_on(quic_addr)?;
+    }
+    if enable_relay_client {
+        for relay_addr in config.relay_addrs {
+            info!("Listening through relay {relay_addr}");
+            swarm.listen_on(relay_addr.with(Protocol::P2pCircuit))?;
+        }
+    }
 
     // Add external/announce addresses so peers can reach us
     for addr in config.announce_addrs {
@@ -85,14 +164,47 @@ pub fn create_swarm(config: TransportConfig) -> Result<Swarm<Libp2pBehaviour>, L
     Ok(swarm)
 }
 
+/// State shared between the swarm loop and the tasks it spawns.
+pub struct SwarmDeps {
+    pub handler: Arc<dyn MessageHandler + Send + Sync>,
+    pub known_peers: Arc<DashMap<String, String>>,
+    pub keypair: Keypair,
+    pub subscriptions: Subscriptions,
+    pub subscription_store: Arc<dyn SubscriptionStore>,
+    pub peer_store: Arc<dyn PeerStore>,
+    pub capabilities: PeerCapabilities,
+    /// Number of known peers redialed on startup.
+    pub redial_limit: u32,
+}
+
 pub async fn run_swarm(
     mut swarm: Swarm<Libp2pBehaviour>,
     mut rx: mpsc::Receiver<Control>,
     ctrl_tx: mpsc::Sender<Control>,
-    handler: Arc<dyn MessageHandler + Send + Sync>,
-    known_peers: Arc<DashMap<String, String>>,
+    deps: SwarmDeps,
 ) -> Result<(), Libp2pAdapterError> {
     info!("Running swarm...");
+    let SwarmDeps {
+        handler,
+        known_peers,
+        keypair,
+        subscriptions,
+        subscription_store,
+        peer_store,
+        capabilities,
+        redial_limit,
+    } = deps;
+    // Peers with a redial already scheduled
+    let mut pending_redials: HashSet<PeerId> = HashSet::new();
+    let mut flush_records = tokio::time::interval(RECORD_STORE_FLUSH_INTERVAL);
+
+    tokio::spawn(peers::redial_known_peers(
+        peer_store.clone(),
+        ctrl_tx.clone(),
+        known_peers.clone(),
+        redial_limit,
+    ));
+
     let mut pending: HashMap<
         OutboundRequestId,
         oneshot::Sender<Result<SnpMessage, TransportError>>,
@@ -102,14 +214,32 @@ pub async fn run_swarm(
 
     loop {
         tokio::select! {
+            _ = flush_records.tick() => {
+                if let Err(e) = swarm.behaviour_mut().kad.store_mut().flush() {
+                    tracing::warn!("failed to persist Kademlia records: {e}");
+                }
+            },
             Some(ctrl) = rx.recv() => {
                 match ctrl {
-                    Control::SendSnp { peer, request, ret } => {
-                        let req_id = swarm.behaviour_mut().req_res.send_request(&peer, request);
-                        pending.insert(req_id, ret);
+                    Control::SendSnp { peer, mut request, ret } => {
+                        request.version = capabilities::version_for(&capabilities, &peer).to_string();
+                        match envelope::seal(&keypair, request) {
+                            Ok(request) => {
+                                let req_id = swarm.behaviour_mut().req_res.send_request(&peer, request);
+                                pending.insert(req_id, ret);
+                            }
+                            Err(e) => {
+                                let _ = ret.send(Err(e.into()));
+                            }
+                        }
                     }
                     Control::SendResponse { channel, response } => {
-                        let _ = swarm.behaviour_mut().req_res.send_response(channel, response);
+                        match envelope::seal(&keypair, response) {
+                            Ok(response) => {
+                                let _ = swarm.behaviour_mut().req_res.send_response(channel, response);
+                            }
+                            Err(e) => tracing::warn!("failed to sign response: {e}"),
+                        }
                     }
                     Control::Provide {key} => {
                         if let Err(e) = swarm.behaviour_mut().kad.start_providing(key.into()) {
@@ -120,6 +250,48 @@ pub async fn run_swarm(
                         let query_id = swarm.behaviour_mut().kad.get_providers(key.into());
                         provider_queries.insert(query_id, ret);
                     }
+                    Control::Dial { peer, addresses } => {
+                        pending_redials.remove(&peer);
+                        if swarm.is_connected(&peer) {
+                            continue;
+                        }
+                        let addresses: Vec<Multiaddr> =
+                            addresses.iter().filter_map(|a| a.parse().ok()).collect();
+                        for address in &addresses {
+                            swarm.behaviour_mut().kad.add_address(&peer, address.clone());
+                        }
+                        let opts = DialOpts::peer_id(peer).addresses(addresses).build();
+                        if let Err(e) = swarm.dial(opts) {
+                            tracing::debug!("dial {peer} failed: {e}");
+                        }
+                    }
+                    Control::Subscribe { topic } => {
+                        if let Err(e) = swarm.behaviour_mut().gossipsub.subscribe(&IdentTopic::new(topic)) {
+                            tracing::warn!("gossipsub subscribe failed: {e}");
+                        }
+                    }
+                    Control::Unsubscribe { topic } => {
+                        swarm.behaviour_mut().gossipsub.unsubscribe(&IdentTopic::new(topic));
+                    }
+                    Control::Publish { topic, message } => {
+                        let data = match envelope::seal(&keypair, message)
+                            .and_then(|message| Ok(serde_json::to_vec(&message)?))
+                        {
+                            Ok(data) => data,
+                            Err(e) => {
+                                tracing::warn!("failed to sign publication: {e}");
+                                continue;
+                            }
+                        };
+                        match swarm.behaviour_mut().gossipsub.publish(IdentTopic::new(topic.clone()), data) {
+                            Ok(_) => {}
+                            // Nobody follows this topic right now
+                            Err(PublishError::NoPeersSubscribedToTopic) => {
+                                tracing::debug!("no subscribers for {topic}");
+                            }
+                            Err(e) => tracing::warn!("gossipsub publish on {topic} failed: {e}"),
+                        }
+                    }
                 }
             },
             event = swarm.select_next_some() => {
@@ -156,104 +328,226 @@ pub async fn run_swarm(
                     SwarmEvent::ConnectionEstablished {
                         peer_id,
                         connection_id: _,
-                        endpoint: _,
-                        num_established: _,
+                        endpoint,
+                        num_established,
                         concurrent_dial_errors: _,
                         established_in: _,
                     } => {
                         info!("Connected to {peer_id}");
-                        let req = SnpMessage {
-                            version: "1.0.0".to_string(),
-                            id: Uuid::new_v4(),
-                            correlation_id: Uuid::new_v4(),
-                            destination: Synapse { id: peer_id.to_string() },
-                            agent_public_key: "agent_public_key".to_string(),
-                            timestamp: OffsetDateTime::now_utc(),
-                            payload: Command {
+                        pending_redials.remove(&peer_id);
+                        // Only dialed addresses are worth redialing; inbound ones use ephemeral ports
+                        let address = endpoint
+                            .is_dialer()
+                            .then(|| endpoint.get_remote_address().to_string());
+                        let peer_store = peer_store.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = peer_store
+                                .record_connected(&peer_id.to_string(), address.as_deref())
+                                .await
+                            {
+                                tracing::warn!("failed to record connection to {peer_id}: {e}");
+                            }
+                        });
+                        if num_established.get() == 1 {
+                            let exchange_tx = ctrl_tx.clone();
+                            let capabilities = capabilities.clone();
+                            tokio::spawn(async move {
+                                if let Err(e) = capabilities::exchange(&exchange_tx, &capabilities, peer_id).await {
+                                    tracing::warn!("capability exchange with {peer_id} failed: {e}");
+                                }
+                            });
+                            // Catch up on anything the peer published while we were apart
+                            let topics: Vec<String> = subscriptions
+                                .iter()
+                                .filter(|entry| entry.value().publisher == peer_id)
+                                .map(|entry| entry.key().clone())
+                                .collect();
+                            for topic in topics {
+                                let ctrl_tx = ctrl_tx.clone();
+                                let subscriptions = subscriptions.clone();
+                                let store = subscription_store.clone();
+                                let handler = handler.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) =
+                                        subscriptions::replay(&ctrl_tx, &subscriptions, &store, &handler, &topic).await
+                                    {
+                                        tracing::warn!("replay of {topic} failed: {e}");
+                                    }
+                                });
+                            }
+                        }
+                        let mut req = SnpMessage::new(
+                            Synapse { id: peer_id.to_string() },
+                            Command {
                                 action: "synapse:get_public_key".to_string(),
                                 event: Event::new()
                                     .with_event_type("synapse:get_public_key")
                                     .with_module_kind("core")
                                     .build()
                             },
-                            signature: "signature".to_string(),
-                        };
-                        let _ = swarm.behaviour_mut().req_res.send_request(&peer_id, req);
+                        );
+                        req.version = SNP_BASELINE_VERSION.to_string();
+                        match envelope::seal(&keypair, req) {
+                            Ok(req) => {
+                                let _ = swarm.behaviour_mut().req_res.send_request(&peer_id, req);
+                            }
+                            Err(e) => tracing::warn!("failed to sign public key request: {e}"),
+                        }
                     }
 
 
-                    SwarmEvent::ConnectionClosed { peer_id, .. } => info!("Disconnected from {peer_id}"),
+                    SwarmEvent::ConnectionClosed { peer_id, num_established, .. } => {
+                        info!("Disconnected from {peer_id}");
+                        if num_established == 0 {
+                            // Renegotiated on reconnection, the peer may have upgraded meanwhile
+                            capabilities.remove(&peer_id);
+                        }
+                    }
+                    SwarmEvent::OutgoingConnectionError { peer_id: Some(peer_id), error, .. } => {
+                        // Dials refused by our own limits or already in progress are not the peer's fault
+                        let unreachable = matches!(
+                            error,
+                            DialError::Transport(_) | DialError::WrongPeerId { .. }
+                        );
+                        tracing::debug!("outgoing connection to {peer_id} failed: {error}");
+                        if unreachable
+                            && !swarm.is_connected(&peer_id)
+                            && pending_redials.insert(peer_id)
+                        {
+                            tokio::spawn(peers::redial_after_failure(
+                                peer_store.clone(),
+                                ctrl_tx.clone(),
+                                peer_id,
+                            ));
+                        }
+                    }
+                    SwarmEvent::Behaviour(Libp2pEvent::Identify(event)) => {
+                        if let identify::Event::Received { peer_id, info, .. } = *event {
+                            tracing::debug!("Identified {peer_id} ({}) listening on {:?}", info.agent_version, info.listen_addrs);
+                            for address in info.listen_addrs {
+                                swarm.behaviour_mut().kad.add_address(&peer_id, address);
+                            }
+                        }
+                    }
+                    SwarmEvent::Behaviour(Libp2pEvent::Autonat(autonat::Event::StatusChanged { old, new })) => {
+                        info!("NAT status changed from {old:?} to {new:?}");
+                    }
+                    SwarmEvent::Behaviour(Libp2pEvent::RelayClient(relay::client::Event::ReservationReqAccepted { relay_peer_id, renewal: false, .. })) => {
+                        info!("Reserved a slot on relay {relay_peer_id}");
+                    }
+                    SwarmEvent::Behaviour(Libp2pEvent::RelayServer(relay::Event::ReservationReqAccepted { src_peer_id, renewed: false })) => {
+                        info!("Relaying for {src_peer_id}");
+                    }
+                    SwarmEvent::Behaviour(Libp2pEvent::Dcutr(event)) => match event.result {
+                        Ok(_) => info!("Upgraded relayed connection to {} to a direct one", event.remote_peer_id),
+                        Err(e) => tracing::debug!("hole punching to {} failed: {e}", event.remote_peer_id),
+                    },
+                    SwarmEvent::Behaviour(Libp2pEvent::Gossipsub(gossipsub::Event::Message { message, .. })) => {
+                        let topic = message.topic.as_str().to_string();
+                        let event = match verify_publication(&message) {
+                            Ok(event) => event,
+                            Err(e) => {
+                                tracing::warn!("rejecting publication on {topic}: {e}");
+                                continue;
+                            }
+                        };
+                        let subscriptions = subscriptions.clone();
+                        let store = subscription_store.clone();
+                        let handler = handler.clone();
+                        tokio::spawn(async move {
+                            subscriptions::deliver(&subscriptions, &store, &handler, &topic, event).await;
+                        });
+                    }
                     SwarmEvent::Behaviour(Libp2pEvent::ReqRes(ev)) => match ev {
                         ReqResEvent::Message { peer, message, .. } => match message {
                             ReqResMessage::Request { request, channel, .. } => {
                                 // Inbound request: spawn handler task to avoid blocking the swarm
                                 info!("Received incoming message: {:?}", request);
-                                match request.payload {
+                                if let Err(e) = envelope::verify(&peer, &request) {
+                                    tracing::warn!("rejecting request from {peer}: {e}");
+                                    let resp = SnpMessage::error_reply(
+                                        &request,
+                                        Synapse { id: peer.to_string() },
+                                        SnpErrorCode::Unauthorized,
+                                        e.to_string(),
+                                    );
+                                    let _ = ctrl_tx.send(Control::SendResponse { channel, response: resp }).await;
+                                    continue;
+                                }
+                                if !protocol_snp::is_supported_version(&request.version) {
+                                    let resp = SnpMessage::error_reply(
+                                        &request,
+                                        Synapse { id: peer.to_string() },
+                                        SnpErrorCode::UnsupportedVersion,
+                                        format!(
+                                            "unsupported SNP version {}, supported: {}",
+                                            request.version,
+                                            SUPPORTED_SNP_VERSIONS.join(", ")
+                                        ),
+                                    );
+                                    let _ = ctrl_tx.send(Control::SendResponse { channel, response: resp }).await;
+                                    continue;
+                                }
+                                match request.payload.clone() {
                                     Command { action: _, event } => {
                                         // Spawn handler in background task to prevent blocking the swarm event loop
                                         let handler = handler.clone();
                                         let ctrl_tx = ctrl_tx.clone();
                                         let peer_str = peer.to_string();
                                         tokio::spawn(async move {
-                                            let response = match handler.handle_message(event).await {
-                                                Ok(saved) => {
-                                                    SnpMessage {
-                                                        version: "1.0.0".to_string(),
-                                                        id: Uuid::new_v4(),
-                                                        correlation_id: Uuid::new_v4(),
-                                                        destination: Synapse { id: peer_str },
-                                                        agent_public_key: "agent_public_key".to_string(),
-                                                        timestamp: OffsetDateTime::now_utc(),
-                                                        payload: Reply {
-                                                            ok: true,
-                                                            events: saved,
-                                                            error: None,
-                                                        },
-                                                        signature: "signature".to_string(),
-                                                    }
-                                                }
+                                            let result = if event.event_type == GET_CAPABILITIES {
+                                                let local = SnpCapabilities::local(handler.module_capabilities());
+                                                capabilities::capabilities_event(&local)
+                                                    .map(|event| vec![event])
+                                                    .map_err(|e| CoreError::Other(e.to_string()))
+                                            } else {
+                                                handler.handle_message(event).await
+                                            };
+                                            let payload = match result {
+                                                Ok(saved) => Reply {
+                                                    ok: true,
+                                                    events: saved,
+                                                    error: None,
+                                                    code: None,
+                                                },
                                                 Err(err) => {
                                                     tracing::warn!("ingest/handle failed: {err:?}");
-                                                    SnpMessage {
-                                                        version: "1.0.0".to_string(),
-                                                        id: Uuid::new_v4(),
-                                                        correlation_id: Uuid::new_v4(),
-                                                        destination: Synapse { id: peer_str },
-                                                        agent_public_key: "agent_public_key".to_string(),
-                                                        timestamp: OffsetDateTime::now_utc(),
-                                                        payload: Reply {
-                                                            ok: false,
-                                                            events: vec![],
-                                                            error: Some(err.to_string()),
-                                                        },
-                                                        signature: "signature".to_string(),
+                                                    Reply {
+                                                        ok: false,
+                                                        events: vec![],
+                                                        error: Some(err.to_string()),
+                                                        code: Some(SnpErrorCode::from(&err)),
                                                     }
                                                 }
                                             };
+                                            let response = SnpMessage::reply_to(
+                                                &request,
+                                                Synapse { id: peer_str },
+                                                payload,
+                                            );
                                             info!("Sending outbound response: {:?}", response);
                                             let _ = ctrl_tx.send(Control::SendResponse { channel, response }).await;
                                         });
                                     }
                                     _ => {
-                                        let resp = SnpMessage {
-                                            version: "1.0.0".to_string(),
-                                            id: Uuid::new_v4(),
-                                            correlation_id: Uuid::new_v4(),
-                                            destination: Synapse { id: peer.to_string() },
-                                            agent_public_key: "agent_public_key".to_string(),
-                                            timestamp: OffsetDateTime::now_utc(),
-                                            payload: Reply {
-                                                ok: false,
-                                                events: vec![],
-                                                error: Some("unsupported payload".into()),
-                                            },
-                                            signature: "signature".to_string(),
-                                        };
+                                        let resp = SnpMessage::error_reply(
+                                            &request,
+                                            Synapse { id: peer.to_string() },
+                                            SnpErrorCode::InvalidRequest,
+                                            "unsupported payload",
+                                        );
                                         let _ = ctrl_tx.send(Control::SendResponse { channel, response: resp }).await;
                                     }
                                 }
                             }
                             ReqResMessage::Response { request_id, response } => {
+                                if let Err(e) = envelope::verify(&peer, &response) {
+                                    tracing::warn!("rejecting response from {peer}: {e}");
+                                    if let Some(ch) = pending.remove(&request_id) {
+                                        let _ = ch.send(Err(e.into()));
+                                    }
+                                    continue;
+                                }
                                 if let Some(ch) = pending.remove(&request_id) {
                                     let _ = ch.send(Ok(response.clone()));
                                 }
@@ -262,7 +556,14 @@ pub async fn run_swarm(
                                         match evt.event_type.as_str() {
                                             "synapse:return_public_key" => {
                                                 if let Some(pk) = evt.content.clone() {
-                                                    known_peers.insert(pk, peer.to_string());
+                                                    known_peers.insert(pk.clone(), peer.to_string());
+                                                    let peer_store = peer_store.clone();
+                                                    let peer_id = peer.to_string();
+                                                    tokio::spawn(async move {
+                                                        if let Err(e) = peer_store.record_public_key(&peer_id, &pk).await {
+                                                            tracing::warn!("failed to record public key of {peer_id}: {e}");
+                                                        }
+                                                    });
                                                 }
                                             }
                                             _ => {}
@@ -291,3 +592,148 @@ pub async fn run_swarm(
         }
     }
 }
+
+
+/// Checks that a gossiped event was sealed by the Synapse its topic belongs to.
+fn verify_publication(message: &gossipsub::Message) -> Result<Event, Libp2pAdapterError> {
+    let invalid = |reason: &str| Libp2pAdapterError::InvalidEnvelope(reason.to_string());
+
+    let source = message.source.ok_or_else(|| invalid("unsigned publication"))?;
+    let snp: SnpMessage = serde_json::from_slice(&message.data)
+        .map_err(|e| invalid(&format!("malformed publication: {e}")))?;
+    envelope::verify(&source, &snp)?;
+    if !protocol_snp::is_supported_version(&snp.version) {
+        return Err(invalid("unsupported SNP version"));
+    }
+
+    let topic = message.topic.as_str();
+    if !matches!(&snp.destination, Multicast { topic: t } if t == topic) {
+        return Err(invalid("destination does not match topic"));
+    }
+    let publisher = protocol_snp::event_topic_publisher(topic)
+        .ok_or_else(|| invalid("not an event topic"))?;
+    if envelope::decode_public_key(publisher)?.to_peer_id() != source {
+        return Err(invalid("publisher does not own topic"));
+    }
+
+    match snp.payload {
+        Command { event, .. } => Ok(event),
+        _ => Err(invalid("unsupported payload")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_config(quic: bool) -> TransportConfig {
+        TransportConfig {
+            keypair: Keypair::generate_secp256k1(),
+            bootstrap_addrs: vec![],
+            listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
+            quic_listen_addr: quic.then(|| "/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap()),
+            announce_addrs: vec![],
+            max_connections: 8,
+            idle_connection_timeout: Duration::from_secs(30),
+            dht_store_path: None,
+            enable_identify: true,
+            enable_autonat: true,
+            enable_relay_server: false,
+            enable_relay_client: true,
+            relay_addrs: vec![],
+            enable_dcutr: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swarms_connect_over_quic_and_identify() {
+        let mut listener = create_swarm(local_config(true)).unwrap();
+        let mut dialer = create_swarm(local_config(false)).unwrap();
+        let listener_id = *listener.local_peer_id();
+
+        let quic_addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = listener.select_next_some().await
+                && address.iter().any(|p| matches!(p, libp2p::multiaddr::Protocol::QuicV1))
+            {
+                break address;
+            }
+        };
+        dialer.dial(quic_addr).unwrap();
+
+        let identified = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    _ = listener.select_next_some() => {}
+                    event = dialer.select_next_some() => {
+                        if let SwarmEvent::Behaviour(Libp2pEvent::Identify(event)) = event
+                            && let identify::Event::Received { peer_id, info, .. } = *event
+                        {
+                            break (peer_id, info.protocol_version);
+                        }
+                    }
+                }
+            }
+        })
+        .await
+        .expect("swarms did not identify each other");
+
+        assert_eq!(identified, (listener_id, IDENTIFY_PROTOCOL_VERSION.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_synapse_behind_nat_is_reachable_through_relay() {
+        let mut relay = create_swarm(TransportConfig {
+            enable_relay_server: true,
+            ..local_config(false)
+        })
+        .unwrap();
+        let relay_id = *relay.local_peer_id();
+        let relay_addr = loop {
+            if let SwarmEvent::NewListenAddr { address, .. } = relay.select_next_some().await {
+                break address;
+            }
+        };
+        // Reservations only hand out confirmed addresses of the relay
+        relay.add_external_address(relay_addr.clone());
+        let relay_addr = relay_addr.with(Protocol::P2p(relay_id));
+
+        let mut home = create_swarm(TransportConfig {
+            relay_addrs: vec![relay_addr.clone()],
+            ..local_config(false)
+        })
+        .unwrap();
+        let home_id = *home.local_peer_id();
+        let mut dialer = create_swarm(local_config(false)).unwrap();
+
+        let relayed = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                tokio::select! {
+                    _ = relay.select_next_some() => {}
+                    event = home.select_next_some() => {
+                        if let SwarmEvent::Behaviour(Libp2pEvent::RelayClient(
+                            relay::client::Event::ReservationReqAccepted { .. },
+                        )) = event
+                        {
+                            let circuit = relay_addr
+                                .clone()
+                                .with(Protocol::P2pCircuit)
+                                .with(Protocol::P2p(home_id));
+                            dialer.dial(circuit).unwrap();
+                        }
+                    }
+                    event = dialer.select_next_some() => {
+                        if let SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } = event
+                            && peer_id == home_id
+                        {
+                            break endpoint.is_relayed();
+                        }
+                    }
+                }
+            }
+        })
+        .await
+        .expect("dialer did not reach the home Synapse");
+
+        assert!(relayed);
+    }
+}
@@ -3,35 +3,45 @@
 
 use std::sync::Arc;
 
+use crate::capabilities::PeerCapabilities;
 use crate::control::Control;
+use crate::envelope;
+use crate::subscriptions::{self, RemoteSubscription, Subscriptions};
 use crate::{
     errors::Libp2pAdapterError,
-    swarm::{create_swarm, run_swarm},
+    swarm::{SwarmDeps, create_swarm, run_swarm},
 };
 use async_trait::async_trait;
-use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
 use dashmap::DashMap;
-use libp2p::identity::PublicKey;
 use libp2p::{Multiaddr, PeerId, identity::Keypair};
 use protocol_snp::{
     Destination::{Local, Multicast, Synapse},
-    SnpMessage,
+    SnpErrorCode, SnpMessage,
     SnpPayload::{Command, Reply},
 };
 use synapse_core::domain::events::Event;
-use synapse_core::ports::federation::MessageHandler;
+use synapse_core::ports::events::event_repository::EventCursor;
+use synapse_core::ports::federation::subscriptions::SubscriptionStore;
+use synapse_core::ports::federation::{EventSubscriptions, MessageHandler};
+use synapse_core::ports::peers::peers::PeerStore;
+use std::path::PathBuf;
+use std::time::Duration;
 use synapse_core::{TransportError, ports::federation::FederationTransport};
 use time::OffsetDateTime;
 use tokio::sync::{mpsc, oneshot};
 use tracing::warn;
-use uuid::Uuid;
 
 pub struct Libp2pTransport {
     config: TransportConfig,
     tx: mpsc::Sender<Control>,
     rx: Option<mpsc::Receiver<Control>>,
-    inbound_handler: Arc<dyn MessageHandler>,
+    inbound_handler: Arc<dyn MessageHandler + Send + Sync>,
     known_peers: Arc<DashMap<String, String>>,
+    peer_store: Arc<dyn PeerStore>,
+    subscriptions: Subscriptions,
+    subscription_store: Arc<dyn SubscriptionStore>,
+    capabilities: PeerCapabilities,
+    public_key: String,
 }
 
 #[derive(Clone, Debug)]
@@ -39,7 +49,20 @@ pub struct TransportConfig {
     pub keypair: Keypair,
     pub bootstrap_addrs: Vec<Multiaddr>,
     pub listen_addr: Multiaddr,
+    /// QUIC listen address; `None` disables QUIC listening.
+    pub quic_listen_addr: Option<Multiaddr>,
     pub announce_addrs: Vec<Multiaddr>,
+    pub max_connections: u32,
+    pub idle_connection_timeout: Duration,
+    /// Where Kademlia records are persisted; `None` keeps them in memory only.
+    pub dht_store_path: Option<PathBuf>,
+    pub enable_identify: bool,
+    pub enable_autonat: bool,
+    pub enable_relay_server: bool,
+    pub enable_relay_client: bool,
+    /// Relays to reserve a slot on and listen through; needs the relay client.
+    pub relay_addrs: Vec<Multiaddr>,
+    pub enable_dcutr: bool,
 }
 
 impl Libp2pTransport {
@@ -47,32 +70,89 @@ impl Libp2pTransport {
         config: TransportConfig,
         handler: Arc<dyn MessageHandler + Send + Sync>,
         known_peers: Arc<DashMap<String, String>>,
+        peer_store: Arc<dyn PeerStore>,
+        subscription_store: Arc<dyn SubscriptionStore>,
     ) -> Self {
         let (tx, rx) = mpsc::channel::<Control>(64);
+        let public_key = envelope::encode_public_key(&config.keypair.public());
         Self {
             config,
             tx,
             rx: Some(rx),
             inbound_handler: handler,
             known_peers,
+            peer_store,
+            subscriptions: Arc::new(DashMap::new()),
+            subscription_store,
+            capabilities: Arc::new(DashMap::new()),
+            public_key,
         }
     }
 
     pub async fn start(&mut self) -> Result<(), Libp2pAdapterError> {
         let swarm = create_swarm(self.config.clone())?;
+        // In place before any connection, so replay runs once publishers connect
+        let resumed = self.restore_subscriptions().await;
         let rx = self.rx.take().expect("transport already started");
         let ctrl_tx = self.tx.clone();
-        let handler = self.inbound_handler.clone();
-        let known_peers = self.known_peers.clone();
+        let deps = SwarmDeps {
+            handler: self.inbound_handler.clone(),
+            known_peers: self.known_peers.clone(),
+            keypair: self.config.keypair.clone(),
+            subscriptions: self.subscriptions.clone(),
+            subscription_store: self.subscription_store.clone(),
+            peer_store: self.peer_store.clone(),
+            capabilities: self.capabilities.clone(),
+            redial_limit: self.config.max_connections,
+        };
 
         tokio::spawn(async move {
-            if let Err(e) = run_swarm(swarm, rx, ctrl_tx, handler, known_peers).await {
+            if let Err(e) = run_swarm(swarm, rx, ctrl_tx, deps).await {
                 warn!("libp2p swarm exited with error: {e:?}");
             }
         });
+        for topic in resumed {
+            if self.tx.send(Control::Subscribe { topic }).await.is_err() {
+                warn!("swarm control channel closed while resuming subscriptions");
+                break;
+            }
+        }
         Ok(())
     }
 
+    /// Restores the subscriptions followed before the restart, returning their
+    /// topics.
+    async fn restore_subscriptions(&self) -> Vec<String> {
+        let followed = match self.subscription_store.list_subscriptions().await {
+            Ok(followed) => followed,
+            Err(e) => {
+                warn!("failed to load subscriptions: {e}");
+                return Vec::new();
+            }
+        };
+        let mut topics = Vec::new();
+        for followed in followed {
+            let publisher = match peer_id_from_urlsafe_b64_pk(&followed.synapse_public_key) {
+                Ok(publisher) => publisher,
+                Err(e) => {
+                    warn!("skipping subscription {}: {e}", followed.topic);
+                    continue;
+                }
+            };
+            let mut subscription = RemoteSubscription::new(
+                publisher,
+                followed.synapse_public_key,
+                followed.module_kind,
+                followed.module_slug,
+                followed.since,
+            );
+            subscription.newest_seen = followed.newest_seen;
+            self.subscriptions.insert(followed.topic.clone(), subscription);
+            topics.push(followed.topic);
+        }
+        topics
+    }
+
     pub fn public_key_for_peer(&self, peer_id: &str) -> Option<String> {
         self.known_peers
             .iter()
@@ -126,26 +206,26 @@ impl FederationTransport for Libp2pTransport {
         let peer_id = peer_id_from_urlsafe_b64_pk(&synapse_public_key)
             .map_err(|e| TransportError::Other(e.to_string()))?;
 
-        // Extract signature from event for the SNP message envelope
-        let event_signature = event.agent_signature.clone().unwrap_or_default();
-        
-        let req = SnpMessage {
-            version: "1.0.0".to_string(),
-            id: Uuid::new_v4(),
-            correlation_id: Uuid::new_v4(),
-            destination: Synapse {
+        // Don't bother the peer with modules it told us it doesn't have
+        if let Some(kind) = event.module_kind.as_deref()
+            && let Some(negotiated) = self.capabilities.get(&peer_id)
+            && !negotiated.advertised.supports_module(kind)
+        {
+            return Err(TransportError::Protocol(format!(
+                "module '{kind}' is not installed on {synapse_public_key}"
+            )));
+        }
+
+        // The envelope is signed with the Synapse identity inside the swarm loop
+        let req = SnpMessage::new(
+            Synapse {
                 id: peer_id.to_string(),
             },
-            // Use the actual agent public key from the event
-            agent_public_key: event.agent.clone(),
-            timestamp: OffsetDateTime::now_utc(),
-            payload: Command {
+            Command {
                 action: event.event_type.clone(),
                 event: event.clone(),
             },
-            // Use the event's signature for the message envelope
-            signature: event_signature,
-        };
+        );
 
         let (ret_tx, ret_rx) = oneshot::channel();
         self.tx
@@ -162,11 +242,25 @@ impl FederationTransport for Libp2pTransport {
             .map_err(|_| TransportError::Other("swarm dropped response".into()))?
             .map_err(|e| TransportError::Other(e.to_string()))?;
 
-        let _ = resp;
         match resp.payload {
+            // Events may expire in flight; callers never see them
             Reply {
                 ok: true, events, ..
-            } => Ok(events),
+            } => {
+                let now = OffsetDateTime::now_utc();
+                Ok(events.into_iter().filter(|e| !e.is_expired(now)).collect())
+            }
+            Reply {
+                ok: false,
+                error: Some(error),
+                code:
+                    Some(
+                        SnpErrorCode::UnsupportedVersion
+                        | SnpErrorCode::UnknownModule
+                        | SnpErrorCode::UnsupportedAction,
+                    ),
+                ..
+            } => Err(TransportError::Protocol(error)),
             Reply {
                 ok: false,
                 error: Some(error),
@@ -177,8 +271,105 @@ impl FederationTransport for Libp2pTransport {
     }
 }
 
+#[async_trait]
+impl EventSubscriptions for Libp2pTransport {
+    async fn subscribe(
+        &self,
+        synapse_public_key: String,
+        module_kind: String,
+        module_slug: Option<String>,
+        since: Option<EventCursor>,
+    ) -> Result<(), TransportError> {
+        let publisher = peer_id_from_urlsafe_b64_pk(&synapse_public_key)
+            .map_err(|e| TransportError::Other(e.to_string()))?;
+        let topic =
+            protocol_snp::event_topic(&synapse_public_key, &module_kind, module_slug.as_deref());
+
+        let subscription =
+            RemoteSubscription::new(publisher, synapse_public_key, module_kind, module_slug, since);
+        self.subscription_store
+            .save_subscription(&subscription.followed(&topic))
+            .await
+            .map_err(|e| TransportError::Other(e.to_string()))?;
+        self.subscriptions.insert(topic.clone(), subscription);
+        self.tx
+            .send(Control::Subscribe {
+                topic: topic.clone(),
+            })
+            .await
+            .map_err(|_| TransportError::Other("swarm control channel closed".into()))?;
+
+        // The subscription stays in place if the publisher is unreachable; replay
+        // runs again once a connection to it is established.
+        if let Err(e) = subscriptions::replay(
+            &self.tx,
+            &self.subscriptions,
+            &self.subscription_store,
+            &self.inbound_handler,
+            &topic,
+        )
+        .await
+        {
+            warn!("initial replay of {topic} failed: {e}");
+        }
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        synapse_public_key: String,
+        module_kind: String,
+        module_slug: Option<String>,
+    ) -> Result<(), TransportError> {
+        let topic =
+            protocol_snp::event_topic(&synapse_public_key, &module_kind, module_slug.as_deref());
+        self.subscription_store
+            .remove_subscription(&topic)
+            .await
+            .map_err(|e| TransportError::Other(e.to_string()))?;
+        if self.subscriptions.remove(&topic).is_none() {
+            return Ok(());
+        }
+        self.tx
+            .send(Control::Unsubscribe { topic })
+            .await
+            .map_err(|_| TransportError::Other("swarm control channel closed".into()))
+    }
+
+    async fn publish(&self, event: Event) -> Result<(), TransportError> {
+        let Some(module_kind) = event.module_kind.as_deref() else {
+            return Ok(());
+        };
+
+        // Followers of a single slug and of the whole module each get a copy
+        let mut topics = vec![protocol_snp::event_topic(&self.public_key, module_kind, None)];
+        if let Some(slug) = event.module_slug.as_deref() {
+            topics.push(protocol_snp::event_topic(
+                &self.public_key,
+                module_kind,
+                Some(slug),
+            ));
+        }
+
+        for topic in topics {
+            let message = SnpMessage::new(
+                Multicast {
+                    topic: topic.clone(),
+                },
+                Command {
+                    action: event.event_type.clone(),
+                    event: event.clone(),
+                },
+            );
+            self.tx
+                .send(Control::Publish { topic, message })
+                .await
+                .map_err(|_| TransportError::Other("swarm control channel closed".into()))?;
+        }
+        Ok(())
+    }
+}
+
 fn peer_id_from_urlsafe_b64_pk(s: &str) -> Result<PeerId, Libp2pAdapterError> {
-    let bytes = URL_SAFE_NO_PAD.decode(s)?;
-    let pk = PublicKey::try_decode_protobuf(&bytes)?;
-    Ok(pk.to_peer_id())
+    Ok(envelope::decode_public_key(s)?.to_peer_id())
 }
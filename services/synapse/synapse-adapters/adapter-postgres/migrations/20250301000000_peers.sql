@@ -0,0 +1,15 @@
+-- Synapses seen on the network, kept across restarts for redialing
+
+CREATE TABLE IF NOT EXISTS peers (
+  peer_id       TEXT PRIMARY KEY,          -- libp2p PeerId
+  public_key    TEXT,                      -- Synapse public key, once reported
+  addresses     TEXT[] NOT NULL DEFAULT '{}',
+  first_seen    TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_seen     TIMESTAMPTZ,
+  last_failure  TIMESTAMPTZ,
+  failures      INT NOT NULL DEFAULT 0,    -- consecutive failed dials
+  reputation    INT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_peers_public_key ON peers (public_key);
+CREATE INDEX IF NOT EXISTS idx_peers_reputation ON peers (reputation DESC, last_seen DESC);
@@ -0,0 +1,3 @@
+-- Expired events are hidden from queries and pruned in the background
+
+CREATE INDEX IF NOT EXISTS idx_events_expiration ON events (expiration) WHERE expiration IS NOT NULL;
@@ -0,0 +1,15 @@
+-- Reactions held on events, materialized from reactions:add / reactions:remove
+-- events as they are recorded. Removed reactions keep their row so an older
+-- add delivered late cannot bring them back.
+
+CREATE TABLE IF NOT EXISTS reactions (
+  target        UUID NOT NULL,             -- event reacted to
+  emoji         TEXT NOT NULL,
+  agent         TEXT NOT NULL,
+  active        BOOL NOT NULL,
+  event_id      UUID NOT NULL,             -- latest reactions:* event applied
+  updated_at    TIMESTAMPTZ NOT NULL,      -- its created_at
+  PRIMARY KEY (target, emoji, agent)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reactions_active ON reactions (target, emoji) WHERE active;
@@ -0,0 +1,24 @@
+-- Full-text search over event content and profile fields
+
+ALTER TABLE events
+  ADD COLUMN IF NOT EXISTS search TSVECTOR
+  GENERATED ALWAYS AS (to_tsvector('english', coalesce(content, ''))) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_events_search ON events USING GIN (search);
+
+-- Profile fields live in the Automerge docs; the searchable ones are copied out
+-- whenever a doc is written. Profiles stored before this migration have no
+-- indexed_at and are indexed at startup.
+
+ALTER TABLE profiles
+  ADD COLUMN IF NOT EXISTS handle       TEXT,
+  ADD COLUMN IF NOT EXISTS display_name TEXT,
+  ADD COLUMN IF NOT EXISTS bio          TEXT,
+  ADD COLUMN IF NOT EXISTS indexed_at   TIMESTAMPTZ,
+  ADD COLUMN IF NOT EXISTS search TSVECTOR
+  GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', coalesce(handle, '') || ' ' || coalesce(display_name, '')), 'A') ||
+    setweight(to_tsvector('simple', coalesce(bio, '')), 'B')
+  ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_profiles_search ON profiles USING GIN (search);
@@ -0,0 +1,10 @@
+-- Sessions remember the device they were opened from and when they were last
+-- refreshed, so an agent can tell its sessions apart and revoke them.
+
+ALTER TABLE sessions
+  ADD COLUMN IF NOT EXISTS user_agent   TEXT,
+  ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_sessions_agent ON sessions (agent, last_seen_at DESC);
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON sessions (expires_at);
+CREATE INDEX IF NOT EXISTS idx_challenges_expires_at ON challenges (expires_at);
@@ -0,0 +1,23 @@
+-- Delegations of agents to device and app keys, materialized from
+-- auth:delegate / auth:revoke_delegation events as they are recorded.
+-- Revocations are kept apart from grants, since one may arrive before, or
+-- without, the grant it takes back.
+
+CREATE TABLE IF NOT EXISTS delegations (
+  id            UUID PRIMARY KEY,          -- auth:delegate event
+  agent         TEXT NOT NULL,
+  delegate      TEXT NOT NULL,
+  scope         JSONB NOT NULL,
+  expires_at    TIMESTAMPTZ,               -- from the scope, for listing
+  created_at    TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_delegations_agent ON delegations (agent, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS delegation_revocations (
+  delegation    UUID NOT NULL,             -- auth:delegate event revoked
+  agent         TEXT NOT NULL,             -- agent of the revocation
+  event_id      UUID NOT NULL,             -- auth:revoke_delegation event
+  revoked_at    TIMESTAMPTZ NOT NULL,      -- its created_at
+  PRIMARY KEY (delegation, agent)
+);
@@ -0,0 +1,12 @@
+-- Modules of remote Synapses followed over gossipsub, kept across restarts
+-- along with the point event replay resumes from
+
+CREATE TABLE IF NOT EXISTS subscriptions (
+  topic               TEXT PRIMARY KEY,          -- gossipsub event topic
+  synapse_public_key  TEXT NOT NULL,             -- publisher
+  module_kind         TEXT NOT NULL,
+  module_slug         TEXT,
+  since               JSONB,                     -- EventCursor replay starts after
+  newest_seen         TIMESTAMPTZ,               -- newest event delivered so far
+  created_at          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
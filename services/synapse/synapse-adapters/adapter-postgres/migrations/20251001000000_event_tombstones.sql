@@ -0,0 +1,9 @@
+-- Expired events are pruned to tombstones rather than deleted: their payload
+-- is cleared but the row keeps its id and signed header, so events referring
+-- to it through previous, targets or revisions stay intact
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS pruned_at TIMESTAMPTZ;
+
+DROP INDEX IF EXISTS idx_events_expiration;
+CREATE INDEX IF NOT EXISTS idx_events_unpruned_expiration ON events (expiration)
+  WHERE expiration IS NOT NULL AND pruned_at IS NULL;
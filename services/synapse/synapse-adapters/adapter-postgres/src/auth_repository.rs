@@ -10,6 +10,7 @@ use synapse_core::domain::crypto::CryptoChallenge;
 use synapse_core::domain::profiles::Profile;
 use synapse_core::ports::auth::SessionRepository;
 use synapse_core::ports::profiles::profile_repository::{ProfilesDocStore, ProfilesRepository};
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub struct PostgresAuthRepository {
@@ -25,82 +26,194 @@ impl PostgresAuthRepository {
 #[async_trait]
 impl SessionRepository for PostgresAuthRepository {
     async fn store_session(&self, session: Session) -> Result<Session, PersistenceError> {
-        let row = sqlx::query!(
+        sqlx::query_as!(
+            Session,
             r#"
-        INSERT INTO sessions (id, agent, created_at, expires_at, revoked)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING id, agent, created_at, expires_at, revoked
+        INSERT INTO sessions (id, agent, created_at, expires_at, revoked, user_agent, last_seen_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, agent, created_at, expires_at, revoked, user_agent, last_seen_at
         "#,
             session.id,
             session.agent,
             session.created_at,
             session.expires_at,
-            session.revoked
+            session.revoked,
+            session.user_agent,
+            session.last_seen_at
         )
         .fetch_one(&self.pool)
         .await
-        .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(Session {
-            id: row.id,
-            agent: row.agent,
-            created_at: row.created_at,
-            expires_at: row.expires_at,
-            revoked: row.revoked,
-        })
+        .map_err(|e| PersistenceError::Other(e.to_string()))
     }
     async fn get_session(&self, id: Uuid) -> Result<Session, PersistenceError> {
-        let row = sqlx::query!(
+        sqlx::query_as!(
+            Session,
             r#"
-        SELECT id, agent, created_at, expires_at, revoked
+        SELECT id, agent, created_at, expires_at, revoked, user_agent, last_seen_at
         FROM sessions
         WHERE id = $1
-
         "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
-        .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(Session {
-            id: row.id,
-            agent: row.agent,
-            created_at: row.created_at,
-            expires_at: row.expires_at,
-            revoked: row.revoked,
-        })
+        .map_err(|e| PersistenceError::Other(e.to_string()))?
+        .ok_or(PersistenceError::NotFound)
     }
     async fn revoke_session(&self, id: Uuid) -> Result<Session, PersistenceError> {
-        let row = sqlx::query!(
+        sqlx::query_as!(
+            Session,
             r#"
             UPDATE sessions
             SET revoked = TRUE
             WHERE id = $1
-            RETURNING id, agent, created_at, expires_at, revoked
+            RETURNING id, agent, created_at, expires_at, revoked, user_agent, last_seen_at
             "#,
             id,
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await
-        .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(Session {
-            id: row.id,
-            agent: row.agent,
-            created_at: row.created_at,
-            expires_at: row.expires_at,
-            revoked: row.revoked,
-        })
+        .map_err(|e| PersistenceError::Other(e.to_string()))?
+        .ok_or(PersistenceError::NotFound)
     }
     async fn delete_session(&self, id: Uuid) -> Result<(), PersistenceError> {
-        let row = sqlx::query!(
+        sqlx::query!(
             r#"
-        DELETE FROM sessions 
+        DELETE FROM sessions
         WHERE id = $1
         "#,
             id,
         )
-        .fetch_one(&self.pool)
+        .execute(&self.pool)
         .await
         .map_err(|e| PersistenceError::Other(e.to_string()))?;
         Ok(())
     }
+    async fn refresh_session(
+        &self,
+        id: Uuid,
+        expires_at: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Session, PersistenceError> {
+        sqlx::query_as!(
+            Session,
+            r#"
+            UPDATE sessions
+            SET expires_at = $2, last_seen_at = $3
+            WHERE id = $1 AND NOT revoked AND expires_at > $3
+            RETURNING id, agent, created_at, expires_at, revoked, user_agent, last_seen_at
+            "#,
+            id,
+            expires_at,
+            now
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?
+        .ok_or(PersistenceError::NotFound)
+    }
+    async fn list_sessions(
+        &self,
+        agent: &str,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Session>, PersistenceError> {
+        sqlx::query_as!(
+            Session,
+            r#"
+            SELECT id, agent, created_at, expires_at, revoked, user_agent, last_seen_at
+            FROM sessions
+            WHERE agent = $1 AND NOT revoked AND expires_at > $2
+            ORDER BY last_seen_at DESC, id
+            "#,
+            agent,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))
+    }
+    async fn revoke_other_sessions(
+        &self,
+        agent: &str,
+        keep: Uuid,
+    ) -> Result<u64, PersistenceError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET revoked = TRUE
+            WHERE agent = $1 AND id <> $2 AND NOT revoked
+            "#,
+            agent,
+            keep
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+    async fn prune_sessions(&self, now: OffsetDateTime) -> Result<u64, PersistenceError> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM sessions
+            WHERE revoked OR expires_at <= $1
+            "#,
+            now
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unit_of_work::tests::pool;
+    use synapse_core::domain::auth::SESSION_TTL;
+    use time::Duration;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_session_lifecycle() {
+        let pool = pool().await;
+        let repo = PostgresAuthRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let now = OffsetDateTime::now_utc();
+
+        let phone = Session::new(&agent, Some("Phone".to_string()), now - Duration::hours(1));
+        let laptop = Session::new(&agent, Some("Laptop".to_string()), now);
+        let old = Session::new(&agent, None, now - SESSION_TTL - Duration::hours(1));
+        for session in [&phone, &laptop, &old] {
+            repo.store_session(session.clone()).await.unwrap();
+        }
+
+        let listed = repo.list_sessions(&agent, now).await.unwrap();
+        let ids: Vec<Uuid> = listed.iter().map(|session| session.id).collect();
+        assert_eq!(ids, vec![laptop.id, phone.id]);
+        assert!(matches!(
+            repo.refresh_session(old.id, now + SESSION_TTL, now).await,
+            Err(PersistenceError::NotFound)
+        ));
+
+        let later = now + Duration::hours(2);
+        let refreshed = repo
+            .refresh_session(phone.id, later + SESSION_TTL, later)
+            .await
+            .unwrap();
+        assert!(refreshed.last_seen_at > now);
+        assert!(refreshed.is_active(later + Duration::hours(3)));
+
+        assert_eq!(repo.revoke_other_sessions(&agent, phone.id).await.unwrap(), 2);
+        let listed = repo.list_sessions(&agent, later).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, phone.id);
+
+        assert!(repo.prune_sessions(later).await.unwrap() >= 2);
+        assert!(matches!(
+            repo.get_session(laptop.id).await,
+            Err(PersistenceError::NotFound)
+        ));
+        assert!(repo.get_session(phone.id).await.unwrap().is_active(later));
+    }
 }
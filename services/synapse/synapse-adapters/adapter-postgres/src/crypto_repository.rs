@@ -6,6 +6,7 @@ use sqlx::{Pool, Postgres};
 use synapse_core::PersistenceError;
 use synapse_core::domain::crypto::CryptoChallenge;
 use synapse_core::ports::crypto::CryptoRepository;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub struct PostgresCryptoRepository {
@@ -52,7 +53,8 @@ impl CryptoRepository for PostgresCryptoRepository {
     }
 
     async fn get_challenge(&self, id: Uuid) -> Result<Option<CryptoChallenge>, PersistenceError> {
-        let row = sqlx::query!(
+        sqlx::query_as!(
+            CryptoChallenge,
             r#"
         SELECT id, agent, nonce, created_at, expires_at
         FROM challenges
@@ -60,18 +62,78 @@ impl CryptoRepository for PostgresCryptoRepository {
         "#,
             id
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))
+    }
+
+    async fn consume_challenge(
+        &self,
+        id: Uuid,
+        agent: &str,
+    ) -> Result<Option<CryptoChallenge>, PersistenceError> {
+        sqlx::query_as!(
+            CryptoChallenge,
+            r#"
+        DELETE FROM challenges
+        WHERE id = $1 AND agent = $2
+        RETURNING id, agent, nonce, created_at, expires_at
+        "#,
+            id,
+            agent
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))
+    }
+
+    async fn prune_challenges(&self, now: OffsetDateTime) -> Result<u64, PersistenceError> {
+        let result = sqlx::query!(
+            r#"
+        DELETE FROM challenges
+        WHERE expires_at <= $1
+        "#,
+            now
+        )
+        .execute(&self.pool)
         .await
         .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+}
 
-        let stored = CryptoChallenge {
-            id: row.id,
-            agent: row.agent,
-            nonce: row.nonce,
-            created_at: row.created_at,
-            expires_at: row.expires_at,
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unit_of_work::tests::pool;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_challenge_is_consumed_once() {
+        let pool = pool().await;
+        let repo = PostgresCryptoRepository::new(pool);
+        let challenge = CryptoChallenge::builder()
+            .with_agent(Uuid::new_v4().to_string())
+            .with_nonce("nonce")
+            .build();
+        repo.store_challenge(challenge.clone()).await.unwrap();
+
+        // Another agent cannot use up the challenge
+        let other = Uuid::new_v4().to_string();
+        assert!(repo.consume_challenge(challenge.id, &other).await.unwrap().is_none());
 
-        Ok(Some(stored))
+        let consumed = repo
+            .consume_challenge(challenge.id, &challenge.agent)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(consumed.agent, challenge.agent);
+        assert!(
+            repo.consume_challenge(challenge.id, &challenge.agent)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(repo.get_challenge(challenge.id).await.unwrap().is_none());
     }
 }
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use sqlx::{PgConnection, Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::domain::events::delegations::{Delegation, DelegationChange};
+use synapse_core::ports::delegations::delegation_repository::DelegationRepository;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub struct PostgresDelegationRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresDelegationRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DelegationRepository for PostgresDelegationRepository {
+    async fn list_delegations(
+        &self,
+        agent: &str,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Delegation>, PersistenceError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT d.id, d.agent, d.delegate, d.scope, d.created_at
+            FROM delegations d
+            WHERE d.agent = $1
+              AND (d.expires_at IS NULL OR d.expires_at > $2)
+              AND NOT EXISTS (
+                  SELECT 1 FROM delegation_revocations r
+                  WHERE r.delegation = d.id AND r.agent = d.agent
+              )
+            ORDER BY d.created_at DESC
+            "#,
+            agent,
+            now
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(Delegation {
+                    id: row.id,
+                    agent: row.agent,
+                    delegate: row.delegate,
+                    scope: serde_json::from_value(row.scope)
+                        .map_err(|e| PersistenceError::Other(e.to_string()))?,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    async fn is_revoked(&self, delegation: Uuid, agent: &str) -> Result<bool, PersistenceError> {
+        let revoked = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM delegation_revocations WHERE delegation = $1 AND agent = $2
+            ) as "revoked!"
+            "#,
+            delegation,
+            agent
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(revoked)
+    }
+}
+
+/// Applies `change` to the materialized delegations over `conn`. Grants and
+/// revocations are recorded once; the earliest revocation is kept.
+pub(crate) async fn apply_delegation(
+    conn: &mut PgConnection,
+    change: &DelegationChange,
+) -> Result<(), PersistenceError> {
+    match change {
+        DelegationChange::Grant(delegation) => {
+            let scope: JsonValue = serde_json::to_value(&delegation.scope)
+                .map_err(|e| PersistenceError::Other(e.to_string()))?;
+            sqlx::query!(
+                r#"
+                INSERT INTO delegations (id, agent, delegate, scope, expires_at, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+                delegation.id,
+                delegation.agent,
+                delegation.delegate,
+                scope,
+                delegation.scope.expires_at,
+                delegation.created_at
+            )
+            .execute(conn)
+            .await
+        }
+        DelegationChange::Revoke(revocation) => {
+            sqlx::query!(
+                r#"
+                INSERT INTO delegation_revocations (delegation, agent, event_id, revoked_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (delegation, agent) DO UPDATE
+                SET event_id = EXCLUDED.event_id,
+                    revoked_at = EXCLUDED.revoked_at
+                WHERE delegation_revocations.revoked_at > EXCLUDED.revoked_at
+                "#,
+                revocation.delegation,
+                revocation.agent,
+                revocation.event_id,
+                revocation.at
+            )
+            .execute(conn)
+            .await
+        }
+    }
+    .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events_repository::PostgresEventsRepository;
+    use crate::unit_of_work::tests::pool;
+    use synapse_core::domain::events::delegations::{
+        DelegationScope, delegation_event, revocation_event,
+    };
+    use synapse_core::ports::events::event_repository::EventRepository;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_revoked_delegation_is_not_listed() {
+        let pool = pool().await;
+        let events = PostgresEventsRepository::new(pool.clone());
+        let delegations = PostgresDelegationRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let scope = |name: &str| DelegationScope {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let phone = delegation_event(&agent, "02phone", &scope("Phone")).unwrap();
+        let bot = delegation_event(&agent, "02bot", &scope("Bot")).unwrap();
+        events.record(phone.clone()).await.unwrap();
+        events.record(bot.clone()).await.unwrap();
+
+        // Only the agent of a delegation can revoke it
+        events.record(revocation_event("02mallory", bot.id)).await.unwrap();
+        events.record(revocation_event(&agent, phone.id)).await.unwrap();
+
+        let listed = delegations
+            .list_delegations(&agent, OffsetDateTime::now_utc())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].delegate, "02bot");
+        assert!(delegations.is_revoked(phone.id, &agent).await.unwrap());
+        assert!(!delegations.is_revoked(bot.id, &agent).await.unwrap());
+    }
+}
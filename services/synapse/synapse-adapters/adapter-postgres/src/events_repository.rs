@@ -4,13 +4,21 @@
 use async_trait::async_trait;
 use serde_json::Value as JsonValue;
 use sqlx::postgres::PgRow;
-use sqlx::{Pool, Postgres, query};
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres};
 use synapse_core::PersistenceError;
+use synapse_core::domain::events::delegations::DelegationChange;
+use synapse_core::domain::events::reactions::ReactionChange;
 use synapse_core::domain::events::{Event, ObjectRef};
-use synapse_core::ports::events::event_repository::{EventFilter, EventRepository};
+use synapse_core::ports::events::event_repository::{
+    EventCursor, EventFilter, EventOrder, EventRepository,
+};
+use std::collections::HashMap;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::delegations_repository::apply_delegation;
+use crate::reactions_repository::apply_reaction;
+
 pub struct PostgresEventsRepository {
     pool: Pool<Postgres>,
 }
@@ -22,69 +30,123 @@ impl PostgresEventsRepository {
 }
 
 #[derive(Debug)]
-struct EventRow {
-    id: Uuid,
-    created_at: OffsetDateTime,
-    event_type: String,
-    module_kind: Option<String>,
-    module_slug: Option<String>,
-    agent: String,
-    agent_signature: Option<String>,
+pub(crate) struct EventRow {
+    pub(crate) id: Uuid,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) event_type: String,
+    pub(crate) module_kind: Option<String>,
+    pub(crate) module_slug: Option<String>,
+    pub(crate) agent: String,
+    pub(crate) agent_signature: Option<String>,
     // the alias `"target?: JsonValue"` maps to this field
-    target: Option<JsonValue>,
-    previous: Option<Uuid>,
-    content: Option<String>,
+    pub(crate) target: Option<JsonValue>,
+    pub(crate) previous: Option<Uuid>,
+    pub(crate) content: Option<String>,
     // `"artifacts?: Vec<String>"` maps here
-    artifacts: Option<Vec<String>>,
+    pub(crate) artifacts: Option<Vec<String>>,
     // `"metadata?: JsonValue"` maps here
-    metadata: Option<JsonValue>,
+    pub(crate) metadata: Option<JsonValue>,
     // `"links?: Vec<String>"` maps here
-    links: Option<Vec<String>>,
+    pub(crate) links: Option<Vec<String>>,
     // `"data?: Vec<u8>"` maps here
-    data: Option<Vec<u8>>,
-    expiration: Option<OffsetDateTime>,
+    pub(crate) data: Option<Vec<u8>>,
+    pub(crate) expiration: Option<OffsetDateTime>,
 }
 
 #[async_trait]
 impl EventRepository for PostgresEventsRepository {
     async fn record(&self, event: Event) -> Result<Event, PersistenceError> {
-        // --- Prepare SQL-friendly values ---
+        // The event and what is materialized from it are written together
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| PersistenceError::Other(err.to_string()))?;
+        let stored = insert_event(&mut tx, event).await?;
+        tx.commit()
+            .await
+            .map_err(|err| PersistenceError::Other(err.to_string()))?;
+        Ok(stored)
+    }
+
+    async fn count_by_metadata(
+        &self,
+        event_type: &str,
+        key: &str,
+        values: &[String],
+    ) -> Result<HashMap<String, u64>, PersistenceError> {
+        count_by_metadata(&self.pool, event_type, key, values).await
+    }
 
-        // target -> Option<serde_json::Value>
-        let target_json: Option<JsonValue> = event
-            .target
-            .as_ref()
-            .map(serde_json::to_value)
-            .transpose()
-            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, PersistenceError> {
+        select_event(&self.pool, id).await
+    }
+
+    async fn prune_expired(&self, now: OffsetDateTime) -> Result<u64, PersistenceError> {
+        tombstone_expired(&self.pool, now).await
+    }
+
+    async fn retrieve(&self, filter: EventFilter) -> Result<Vec<Event>, PersistenceError> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|err| PersistenceError::Other(err.to_string()))?;
+        select_events(&mut conn, filter).await
+    }
+}
 
-        // metadata -> Option<serde_json::Value>
-        let metadata_json: Option<JsonValue> = event
-            .metadata
-            .as_ref()
-            .map(serde_json::to_value)
-            .transpose()
-            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+/// Inserts `event` over `conn`, a pooled connection or an open transaction,
+/// and applies it to the materialized reactions or delegations if it changes
+/// them.
+pub(crate) async fn insert_event(
+    conn: &mut PgConnection,
+    event: Event,
+) -> Result<Event, PersistenceError> {
+    let reaction =
+        ReactionChange::of(&event).map_err(|err| PersistenceError::Other(err.to_string()))?;
+    let delegation =
+        DelegationChange::of(&event).map_err(|err| PersistenceError::Other(err.to_string()))?;
 
-        // artifacts: Option<Vec<String>>
-        let artifacts_arr: Option<Vec<String>> = event.artifacts.clone();
+    // --- Prepare SQL-friendly values ---
 
-        // links: Option<Vec<String>>
-        let links_arr: Option<Vec<String>> = event.links.clone();
+    // target -> Option<serde_json::Value>
+    let target_json: Option<JsonValue> = event
+        .target
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
 
-        // data: Option<Vec<u8>>
-        let data_bytes: Option<Vec<u8>> = event.data.clone();
+    // metadata -> Option<serde_json::Value>
+    let metadata_json: Option<JsonValue> = event
+        .metadata
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
 
-        // --- INSERT + RETURN ---
+    // artifacts: Option<Vec<String>>
+    let artifacts_arr: Option<Vec<String>> = event.artifacts.clone();
 
-        let row = sqlx::query!(
-            r#"
+    // links: Option<Vec<String>>
+    let links_arr: Option<Vec<String>> = event.links.clone();
+
+    // data: Option<Vec<u8>>
+    let data_bytes: Option<Vec<u8>> = event.data.clone();
+
+    // --- INSERT + RETURN; an existing id leaves the stored row alone ---
+
+    let row = sqlx::query_as!(
+        EventRow,
+        r#"
             INSERT INTO events
                 (id, created_at, event_type, module_kind, module_slug, agent, agent_signature,
                  target, previous, content, artifacts, metadata, links, data, expiration)
             VALUES
                 ($1, $2, $3, $4, $5, $6, $7,
                  $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (id) DO NOTHING
             RETURNING
                 id,
                 created_at,
@@ -102,345 +164,567 @@ impl EventRepository for PostgresEventsRepository {
                 data        as "data?: Vec<u8>",          -- nullable BYTEA  -> Option<Vec<u8>>
                 expiration
             "#,
-            event.id,
-            event.created_at, // OffsetDateTime <-> TIMESTAMPTZ
-            event.event_type,
-            event.module_kind,
-            event.module_slug,
-            event.agent,
-            event.agent_signature,
-            target_json, // Option<JsonValue>
-            event.previous,
-            event.content,
-            artifacts_arr.as_deref(), // Option<Vec<String>> -> Option<&[String]>
-            metadata_json,            // Option<JsonValue>
-            links_arr.as_deref(),     // Option<Vec<String>> -> Option<&[String]>
-            data_bytes.as_deref(),    // Option<Vec<u8>> -> Option<&[u8]>
-            event.expiration
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|err| PersistenceError::Other(err.to_string()))?;
+        event.id,
+        event.created_at, // OffsetDateTime <-> TIMESTAMPTZ
+        event.event_type,
+        event.module_kind,
+        event.module_slug,
+        event.agent,
+        event.agent_signature,
+        target_json, // Option<JsonValue>
+        event.previous,
+        event.content,
+        artifacts_arr.as_deref(), // Option<Vec<String>> -> Option<&[String]>
+        metadata_json,            // Option<JsonValue>
+        links_arr.as_deref(),     // Option<Vec<String>> -> Option<&[String]>
+        data_bytes.as_deref(),    // Option<Vec<u8>> -> Option<&[u8]>
+        event.expiration
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| PersistenceError::Other(err.to_string()))?;
 
-        // --- Convert SQL row back into your Event types ---
-
-        // target JSON -> Option<ObjectRef>
-        let target: Option<ObjectRef> = row.target.and_then(|v| serde_json::from_value(v).ok());
-
-        // metadata JSON -> Option<HashMap<String, String>>
-        let metadata = row.metadata.and_then(|v| serde_json::from_value(v).ok());
-
-        // links already returned as Option<Vec<String>>
-        let links: Option<Vec<String>> = row.links;
-
-        Ok(Event {
-            id: row.id,
-            created_at: row.created_at,
-            event_type: row.event_type,
-            module_kind: row.module_kind,
-            module_slug: row.module_slug,
-            agent: row.agent,
-            agent_signature: row.agent_signature,
-            target,
-            previous: row.previous,
-            content: row.content,
-            artifacts: row.artifacts, // now Option<Vec<String>>
-            metadata,
-            links,          // Option<Vec<Url>>
-            data: row.data, // Option<Vec<u8>>
-            expiration: row.expiration,
-        })
+    if let Some(row) = row {
+        if let Some(change) = &reaction {
+            apply_reaction(conn, change).await?;
+        }
+        if let Some(change) = &delegation {
+            apply_delegation(conn, change).await?;
+        }
+        return event_from_row(row);
     }
 
-    async fn retrieve(&self, filter: EventFilter) -> Result<Vec<Event>, PersistenceError> {
-        let EventFilter {
+    // Re-delivery of the same event is a no-op; anything else under its id is not
+    match select_event(&mut *conn, event.id).await? {
+        Some(stored) if stored.is_replay_of(&event) => Ok(stored),
+        _ => Err(PersistenceError::Constraint(format!(
+            "event {} already exists with a different payload",
+            event.id
+        ))),
+    }
+}
+
+/// Looks up the event with `id` through `executor`, a pool or an open transaction.
+pub(crate) async fn select_event<'e, E>(
+    executor: E,
+    id: Uuid,
+) -> Result<Option<Event>, PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query_as!(
+        EventRow,
+        r#"
+        SELECT
+            id,
+            created_at,
             event_type,
             module_kind,
             module_slug,
-        } = filter;
-
-        let rows: Vec<EventRow> = match (
-            event_type.as_deref(),
-            module_kind.as_deref(),
-            module_slug.as_deref(),
-        ) {
-            // 1) No filters: all events
-            (None, None, None) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                "#
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+            agent,
+            agent_signature,
+            target      as "target?: JsonValue",
+            previous,
+            content,
+            artifacts   as "artifacts?: Vec<String>",
+            metadata    as "metadata?: JsonValue",
+            links       as "links?: Vec<String>",
+            data        as "data?: Vec<u8>",
+            expiration
+        FROM events
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|err| PersistenceError::Other(err.to_string()))?
+    .map(event_from_row)
+    .transpose()
+}
 
-            // 2) Only event_type
-            (Some(event_type), None, None) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE event_type = $1
-                "#,
-                event_type
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+/// Counts live events per metadata value through `executor`, a pool or an open
+/// transaction.
+pub(crate) async fn count_by_metadata<'e, E>(
+    executor: E,
+    event_type: &str,
+    key: &str,
+    values: &[String],
+) -> Result<HashMap<String, u64>, PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    let rows = sqlx::query!(
+        r#"
+        SELECT e.metadata->>$2 as "value!", COUNT(*) as "count!"
+        FROM events e
+        WHERE e.event_type = $1
+          AND e.metadata->>$2 = ANY($3)
+          AND (e.expiration IS NULL OR e.expiration > now())
+          AND NOT EXISTS (
+              SELECT 1 FROM events d
+              WHERE d.target @> jsonb_build_object('Event', e.id)
+                AND d.event_type = e.module_kind || ':delete'
+          )
+        GROUP BY 1
+        "#,
+        event_type,
+        key,
+        values
+    )
+    .fetch_all(executor)
+    .await
+    .map_err(|err| PersistenceError::Other(err.to_string()))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.value, row.count as u64))
+        .collect())
+}
 
-            // 3) Only module_kind
-            (None, Some(module_kind), None) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE module_kind = $1
-                "#,
-                module_kind
-            )
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+/// Prunes events expired at `now` to tombstones through `executor`, a pool or
+/// an open transaction. Returns how many were pruned.
+pub(crate) async fn tombstone_expired<'e, E>(
+    executor: E,
+    now: OffsetDateTime,
+) -> Result<u64, PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    // Only the payload goes; the rest is still referred to and signed over
+    let result = sqlx::query!(
+        r#"
+        UPDATE events
+        SET content = NULL, artifacts = NULL, links = NULL, data = NULL, pruned_at = $1
+        WHERE expiration <= $1 AND pruned_at IS NULL
+        "#,
+        now
+    )
+    .execute(executor)
+        .await
+        .map_err(|err| PersistenceError::Other(err.to_string()))?;
+    Ok(result.rows_affected())
+}
 
-            // 4) Only module_slug
-            (None, None, Some(module_slug)) => sqlx::query_as!(
+/// Runs `filter` over `conn`, a pooled connection or an open transaction.
+pub(crate) async fn select_events(
+    conn: &mut PgConnection,
+    filter: EventFilter,
+) -> Result<Vec<Event>, PersistenceError> {
+    // --- Prepare SQL-friendly values; NULL disables a condition ---
+
+    let target_json: Option<JsonValue> = filter
+        .target
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+    let targets_json: Option<Vec<JsonValue>> = filter
+        .targets
+        .as_ref()
+        .map(|targets| targets.iter().map(serde_json::to_value).collect())
+        .transpose()
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+    let metadata_json: Option<JsonValue> = filter
+        .metadata
+        .as_ref()
+        .map(serde_json::to_value)
+        .transpose()
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+    let (before_id, before_ts) = split_cursor(filter.before.as_ref());
+    let (after_id, after_ts) = split_cursor(filter.after.as_ref());
+    let limit = i64::from(filter.page_limit());
+
+    // Keyset pagination over (created_at, id)
+    let rows: Vec<EventRow> = match filter.order {
+            EventOrder::NewestFirst => sqlx::query_as!(
                 EventRow,
                 r#"
                 SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE module_slug = $1
+                    e.id,
+                    e.created_at,
+                    e.event_type,
+                    e.module_kind,
+                    e.module_slug,
+                    e.agent,
+                    e.agent_signature,
+                    e.target      as "target?: JsonValue",
+                    e.previous,
+                    e.content,
+                    e.artifacts   as "artifacts?: Vec<String>",
+                    e.metadata    as "metadata?: JsonValue",
+                    e.links       as "links?: Vec<String>",
+                    e.data        as "data?: Vec<u8>",
+                    e.expiration
+                FROM events e
+                WHERE ($1::text IS NULL OR e.event_type = $1)
+                  AND ($2::text IS NULL OR e.module_kind = $2)
+                  AND ($3::text IS NULL OR e.module_slug = $3)
+                  AND ($4::text IS NULL OR e.agent = $4)
+                  AND ($5::jsonb IS NULL OR e.target = $5)
+                  AND ($6::uuid IS NULL OR e.previous = $6)
+                  AND ($7::timestamptz IS NULL OR e.created_at >= $7)
+                  AND ($8::timestamptz IS NULL OR e.created_at < $8)
+                  AND ($9::jsonb IS NULL OR e.metadata @> $9)
+                  AND ($10::uuid IS NULL OR (e.created_at, e.id) < (SELECT c.created_at, c.id FROM events c WHERE c.id = $10))
+                  AND ($11::timestamptz IS NULL OR e.created_at < $11)
+                  AND ($12::uuid IS NULL OR (e.created_at, e.id) > (SELECT c.created_at, c.id FROM events c WHERE c.id = $12))
+                  AND ($13::timestamptz IS NULL OR e.created_at > $13)
+                  AND ($15::jsonb[] IS NULL OR e.target = ANY($15))
+                  AND (e.expiration IS NULL OR e.expiration > now())
+                  -- Deleted events are hidden
+                  AND NOT EXISTS (
+                      SELECT 1 FROM events d
+                      WHERE d.target @> jsonb_build_object('Event', e.id)
+                        AND d.event_type = e.module_kind || ':delete'
+                  )
+                ORDER BY e.created_at DESC, e.id DESC
+                LIMIT $14
                 "#,
-                module_slug
+                filter.event_type,
+                filter.module_kind,
+                filter.module_slug,
+                filter.agent,
+                target_json,
+                filter.previous,
+                filter.created_since,
+                filter.created_until,
+                metadata_json,
+                before_id,
+                before_ts,
+                after_id,
+                after_ts,
+                limit,
+                targets_json.as_deref()
             )
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *conn)
             .await
             .map_err(|err| PersistenceError::Other(err.to_string()))?,
 
-            // 5) event_type + module_kind
-            (Some(event_type), Some(module_kind), None) => sqlx::query_as!(
+            EventOrder::OldestFirst => sqlx::query_as!(
                 EventRow,
                 r#"
                 SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE event_type = $1
-                  AND module_kind = $2
+                    e.id,
+                    e.created_at,
+                    e.event_type,
+                    e.module_kind,
+                    e.module_slug,
+                    e.agent,
+                    e.agent_signature,
+                    e.target      as "target?: JsonValue",
+                    e.previous,
+                    e.content,
+                    e.artifacts   as "artifacts?: Vec<String>",
+                    e.metadata    as "metadata?: JsonValue",
+                    e.links       as "links?: Vec<String>",
+                    e.data        as "data?: Vec<u8>",
+                    e.expiration
+                FROM events e
+                WHERE ($1::text IS NULL OR e.event_type = $1)
+                  AND ($2::text IS NULL OR e.module_kind = $2)
+                  AND ($3::text IS NULL OR e.module_slug = $3)
+                  AND ($4::text IS NULL OR e.agent = $4)
+                  AND ($5::jsonb IS NULL OR e.target = $5)
+                  AND ($6::uuid IS NULL OR e.previous = $6)
+                  AND ($7::timestamptz IS NULL OR e.created_at >= $7)
+                  AND ($8::timestamptz IS NULL OR e.created_at < $8)
+                  AND ($9::jsonb IS NULL OR e.metadata @> $9)
+                  AND ($10::uuid IS NULL OR (e.created_at, e.id) < (SELECT c.created_at, c.id FROM events c WHERE c.id = $10))
+                  AND ($11::timestamptz IS NULL OR e.created_at < $11)
+                  AND ($12::uuid IS NULL OR (e.created_at, e.id) > (SELECT c.created_at, c.id FROM events c WHERE c.id = $12))
+                  AND ($13::timestamptz IS NULL OR e.created_at > $13)
+                  AND ($15::jsonb[] IS NULL OR e.target = ANY($15))
+                  AND (e.expiration IS NULL OR e.expiration > now())
+                  -- Deleted events are hidden
+                  AND NOT EXISTS (
+                      SELECT 1 FROM events d
+                      WHERE d.target @> jsonb_build_object('Event', e.id)
+                        AND d.event_type = e.module_kind || ':delete'
+                  )
+                ORDER BY e.created_at ASC, e.id ASC
+                LIMIT $14
                 "#,
-                event_type,
-                module_kind
+                filter.event_type,
+                filter.module_kind,
+                filter.module_slug,
+                filter.agent,
+                target_json,
+                filter.previous,
+                filter.created_since,
+                filter.created_until,
+                metadata_json,
+                before_id,
+                before_ts,
+                after_id,
+                after_ts,
+                limit,
+                targets_json.as_deref()
             )
-            .fetch_all(&self.pool)
+            .fetch_all(&mut *conn)
             .await
             .map_err(|err| PersistenceError::Other(err.to_string()))?,
+        };
 
-            // 6) event_type + module_slug
-            (Some(event_type), None, Some(module_slug)) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE event_type = $1
-                  AND module_slug = $2
-                "#,
-                event_type,
-                module_slug
-            )
-            .fetch_all(&self.pool)
+    let mut events = rows
+        .into_iter()
+        .map(event_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_latest_edits(conn, &mut events).await?;
+    Ok(events)
+}
+
+/// Attaches to each of `events` its latest edit. The events themselves are
+/// returned as recorded, so their signatures still cover their content.
+pub(crate) async fn attach_latest_edits<'a>(
+    conn: &mut PgConnection,
+    events: impl IntoIterator<Item = &'a mut Event>,
+) -> Result<(), PersistenceError> {
+    let events: Vec<&mut Event> = events.into_iter().collect();
+    let ids: Vec<Uuid> = events.iter().map(|event| event.id).collect();
+    let rows = sqlx::query_as!(
+        EventRow,
+        r#"
+        SELECT
+            latest.id           as "id!",
+            latest.created_at   as "created_at!",
+            latest.event_type   as "event_type!",
+            latest.module_kind,
+            latest.module_slug,
+            latest.agent        as "agent!",
+            latest.agent_signature,
+            latest.target       as "target?: JsonValue",
+            latest.previous,
+            latest.content,
+            latest.artifacts    as "artifacts?: Vec<String>",
+            latest.metadata     as "metadata?: JsonValue",
+            latest.links        as "links?: Vec<String>",
+            latest.data         as "data?: Vec<u8>",
+            latest.expiration
+        FROM unnest($1::uuid[]) AS original(id)
+        CROSS JOIN LATERAL (
+            SELECT r.*
+            FROM events r
+            WHERE r.target @> jsonb_build_object('Event', original.id)
+              AND r.event_type = r.module_kind || ':edit'
+            ORDER BY r.created_at DESC, r.id DESC
+            LIMIT 1
+        ) latest
+        "#,
+        &ids
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| PersistenceError::Other(err.to_string()))?;
+
+    let mut edits = HashMap::new();
+    for row in rows {
+        let edit = event_from_row(row)?;
+        if let Some(ObjectRef::Event(original)) = edit.target {
+            edits.insert(original, edit);
+        }
+    }
+    for event in events {
+        event.latest_edit = edits.remove(&event.id).map(Box::new);
+    }
+    Ok(())
+}
+
+/// Maps a stored row back into an `Event`.
+pub(crate) fn event_from_row(row: EventRow) -> Result<Event, PersistenceError> {
+    let target: Option<ObjectRef> = row
+        .target
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|err| PersistenceError::Other(err.to_string()))?;
+
+    let metadata = row
+        .metadata
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|err| PersistenceError::Other(err.to_string()))?;
+
+    Ok(Event {
+        id: row.id,
+        created_at: row.created_at,
+        event_type: row.event_type,
+        module_kind: row.module_kind,
+        module_slug: row.module_slug,
+        agent: row.agent,
+        agent_signature: row.agent_signature,
+        latest_edit: None,
+        target,
+        previous: row.previous,
+        content: row.content,
+        artifacts: row.artifacts,
+        metadata,
+        links: row.links,
+        data: row.data,
+        expiration: row.expiration,
+    })
+}
+
+/// Splits a cursor into the `(id, timestamp)` query parameters.
+fn split_cursor(cursor: Option<&EventCursor>) -> (Option<Uuid>, Option<OffsetDateTime>) {
+    match cursor {
+        Some(EventCursor::Id(id)) => (Some(*id), None),
+        Some(EventCursor::Timestamp(ts)) => (None, Some(*ts)),
+        None => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unit_of_work::tests::{pool, profile_event};
+    use synapse_core::domain::crypto::signature::{sign_event, verify_event_signature};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_expired_events_are_hidden_and_pruned() {
+        let pool = pool().await;
+        let repo = PostgresEventsRepository::new(pool);
+        let mut event = profile_event(&Uuid::new_v4().to_string());
+        event.content = Some("gone soon".to_string());
+        event.data = Some(vec![1, 2, 3]);
+        event.expiration = Some(OffsetDateTime::now_utc() - time::Duration::seconds(1));
+        event.agent_signature = Some("signature".to_string());
+        repo.record(event.clone()).await.unwrap();
+        let mut reply = profile_event(&event.agent);
+        reply.previous = Some(event.id);
+        repo.record(reply.clone()).await.unwrap();
+
+        let found = repo
+            .retrieve(EventFilter {
+                agent: Some(event.agent.clone()),
+                ..Default::default()
+            })
             .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, reply.id);
 
-            // 7) module_kind + module_slug
-            (None, Some(module_kind), Some(module_slug)) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE module_kind = $1
-                  AND module_slug = $2
-                "#,
-                module_kind,
-                module_slug
-            )
-            .fetch_all(&self.pool)
+        assert!(repo.prune_expired(OffsetDateTime::now_utc()).await.unwrap() >= 1);
+        assert_eq!(repo.prune_expired(OffsetDateTime::now_utc()).await.unwrap(), 0);
+
+        // The payload is gone but the signed header and links to it remain
+        let tombstone = repo.find(event.id).await.unwrap().unwrap();
+        assert_eq!(tombstone.content, None);
+        assert_eq!(tombstone.data, None);
+        assert_eq!(tombstone.agent_signature, event.agent_signature);
+        let reply = repo.find(reply.id).await.unwrap().unwrap();
+        assert_eq!(reply.previous, Some(event.id));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_reads_attach_edits_and_hide_deletes() {
+        let pool = pool().await;
+        let repo = PostgresEventsRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let mut edited = profile_event(&agent);
+        edited.content = Some("draft".to_string());
+        let deleted = profile_event(&agent);
+        let revision = |event_type: &str, original: &Event| {
+            Event::new()
+                .with_event_type(event_type)
+                .with_module_kind("profiles")
+                .with_agent(agent.clone())
+                .with_target(ObjectRef::Event(original.id))
+                .with_content("final")
+                .build()
+        };
+        for event in [
+            edited.clone(),
+            deleted.clone(),
+            revision("profiles:edit", &edited),
+            revision("profiles:delete", &deleted),
+        ] {
+            repo.record(event).await.unwrap();
+        }
+
+        let found = repo
+            .retrieve(EventFilter {
+                event_type: Some("profiles:set_profile".to_string()),
+                agent: Some(agent.clone()),
+                ..Default::default()
+            })
             .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+            .unwrap();
 
-            // 8) event_type + module_kind + module_slug
-            (Some(event_type), Some(module_kind), Some(module_slug)) => sqlx::query_as!(
-                EventRow,
-                r#"
-                SELECT
-                    id,
-                    created_at,
-                    event_type,
-                    module_kind,
-                    module_slug,
-                    agent,
-                    agent_signature,
-                    target      as "target?: JsonValue",
-                    previous,
-                    content,
-                    artifacts   as "artifacts?: Vec<String>",
-                    metadata    as "metadata?: JsonValue",
-                    links       as "links?: Vec<String>",
-                    data        as "data?: Vec<u8>",
-                    expiration
-                FROM events
-                WHERE event_type = $1
-                  AND module_kind = $2
-                  AND module_slug = $3
-                "#,
-                event_type,
-                module_kind,
-                module_slug
-            )
-            .fetch_all(&self.pool)
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, edited.id);
+        assert_eq!(found[0].content.as_deref(), Some("draft"));
+        assert_eq!(found[0].current_content(), Some("final"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_edited_events_keep_valid_signatures() {
+        let pool = pool().await;
+        let repo = PostgresEventsRepository::new(pool);
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[9u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut original = profile_event(&agent);
+        original.content = Some("draft".to_string());
+        original.agent_signature = Some(sign_event(&original, &signing_key));
+        let mut edit = Event::new()
+            .with_event_type("profiles:edit")
+            .with_module_kind("profiles")
+            .with_agent(agent.clone())
+            .with_target(ObjectRef::Event(original.id))
+            .with_content("final")
+            .build();
+        edit.agent_signature = Some(sign_event(&edit, &signing_key));
+        repo.record(original.clone()).await.unwrap();
+        repo.record(edit.clone()).await.unwrap();
+
+        let found = repo
+            .retrieve(EventFilter {
+                event_type: Some("profiles:set_profile".to_string()),
+                agent: Some(agent),
+                ..Default::default()
+            })
             .await
-            .map_err(|err| PersistenceError::Other(err.to_string()))?,
+            .unwrap();
+
+        let read = found.iter().find(|event| event.id == original.id).unwrap();
+        assert!(verify_event_signature(read).is_valid());
+        let latest_edit = read.latest_edit.as_deref().unwrap();
+        assert_eq!(latest_edit.id, edit.id);
+        assert!(verify_event_signature(latest_edit).is_valid());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_count_by_metadata_skips_deleted_events() {
+        let pool = pool().await;
+        let repo = PostgresEventsRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let (busy, quiet) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        let tagged = |value: &str| {
+            let mut event = profile_event(&agent);
+            event.metadata = Some(HashMap::from([("thread".to_string(), value.to_string())]));
+            event
         };
+        let removed = tagged(&quiet);
+        let tombstone = Event::new()
+            .with_event_type("profiles:delete")
+            .with_module_kind("profiles")
+            .with_agent(agent.clone())
+            .with_target(ObjectRef::Event(removed.id))
+            .build();
+        for event in [tagged(&busy), tagged(&busy), removed, tombstone] {
+            repo.record(event).await.unwrap();
+        }
 
-        // Map EventRow -> Event
-        let events = rows
-            .into_iter()
-            .map(|row| {
-                let target: Option<ObjectRef> = row
-                    .target
-                    .map(serde_json::from_value)
-                    .transpose()
-                    .map_err(|err| PersistenceError::Other(err.to_string()))?;
-
-                let metadata = row
-                    .metadata
-                    .map(serde_json::from_value)
-                    .transpose()
-                    .map_err(|err| PersistenceError::Other(err.to_string()))?;
-
-                Ok(Event {
-                    id: row.id,
-                    created_at: row.created_at,
-                    event_type: row.event_type,
-                    module_kind: row.module_kind,
-                    module_slug: row.module_slug,
-                    agent: row.agent,
-                    agent_signature: row.agent_signature,
-                    target,
-                    previous: row.previous,
-                    content: row.content,
-                    artifacts: row.artifacts,
-                    metadata,
-                    links: row.links,
-                    data: row.data,
-                    expiration: row.expiration,
-                })
-            })
-            .collect::<Result<Vec<_>, PersistenceError>>()?;
+        let counts = repo
+            .count_by_metadata("profiles:set_profile", "thread", &[busy.clone(), quiet])
+            .await
+            .unwrap();
 
-        Ok(events)
+        assert_eq!(counts, HashMap::from([(busy, 2)]));
     }
 }
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use sqlx::{Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::domain::events::keys::{ROTATE_KEY, SET_RECOVERY_KEY};
+use synapse_core::domain::events::{Event, PublicKey};
+use synapse_core::ports::keys::key_repository::KeyRepository;
+
+use crate::events_repository::{EventRow, event_from_row};
+
+pub struct PostgresKeyRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresKeyRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl KeyRepository for PostgresKeyRepository {
+    // Read straight from `events`: a history is replayed from the events as
+    // signed, whatever edits or deletions were recorded since
+    async fn key_events(&self, agent: &str) -> Result<Vec<Event>, PersistenceError> {
+        let rows = sqlx::query_as!(
+            EventRow,
+            r#"
+            SELECT
+                id,
+                created_at,
+                event_type,
+                module_kind,
+                module_slug,
+                agent,
+                agent_signature,
+                target      as "target?: JsonValue",
+                previous,
+                content,
+                artifacts   as "artifacts?: Vec<String>",
+                metadata    as "metadata?: JsonValue",
+                links       as "links?: Vec<String>",
+                data        as "data?: Vec<u8>",
+                expiration
+            FROM events
+            WHERE agent = $1 AND event_type IN ($2, $3)
+            ORDER BY created_at, id
+            "#,
+            agent,
+            ROTATE_KEY,
+            SET_RECOVERY_KEY
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        rows.into_iter().map(event_from_row).collect()
+    }
+
+    async fn agents_rotated_to(&self, key: &str) -> Result<Vec<PublicKey>, PersistenceError> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT DISTINCT agent as "agent!"
+            FROM events
+            WHERE event_type = $1 AND target = jsonb_build_object('Agent', $2::text)
+            "#,
+            ROTATE_KEY,
+            key
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events_repository::PostgresEventsRepository;
+    use crate::unit_of_work::tests::pool;
+    use synapse_core::domain::events::keys::{recovery_key_event, rotation_event};
+    use synapse_core::ports::events::event_repository::EventRepository;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_key_events_of_an_agent() {
+        let pool = pool().await;
+        let events = PostgresEventsRepository::new(pool.clone());
+        let keys = PostgresKeyRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let next = Uuid::new_v4().to_string();
+        let recovery = recovery_key_event(&agent, "02recovery", None);
+        let rotation = Event {
+            created_at: recovery.created_at + time::Duration::seconds(1),
+            content: Some("proof".to_string()),
+            ..rotation_event(&agent, &next, Some(recovery.id))
+        };
+        events.record(recovery.clone()).await.unwrap();
+        events.record(rotation.clone()).await.unwrap();
+        events.record(rotation_event("02other", "02elsewhere", None)).await.unwrap();
+
+        let recorded: Vec<_> = keys
+            .key_events(&agent)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(recorded, vec![recovery.id, rotation.id]);
+        assert_eq!(keys.agents_rotated_to(&next).await.unwrap(), vec![agent]);
+    }
+}
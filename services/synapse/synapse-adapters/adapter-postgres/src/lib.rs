@@ -3,9 +3,16 @@
 
 pub mod auth_repository;
 pub mod crypto_repository;
+pub mod delegations_repository;
 pub mod error;
 pub mod events_repository;
+pub mod keys_repository;
+pub mod peers_repository;
 pub mod profiles_repository;
+pub mod reactions_repository;
+pub mod search_repository;
+pub mod subscriptions_repository;
+pub mod unit_of_work;
 
 use crate::error::PostgresAdapterError;
 use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::domain::peers::{
+    MAX_PEER_ADDRESSES, Peer, REPUTATION_CONNECTED, REPUTATION_DIAL_FAILURE, REPUTATION_MAX,
+    REPUTATION_MIN,
+};
+use synapse_core::ports::peers::peers::PeerStore;
+use time::OffsetDateTime;
+
+pub struct PostgresPeerStore {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresPeerStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+struct PeerRow {
+    peer_id: String,
+    public_key: Option<String>,
+    addresses: Vec<String>,
+    first_seen: OffsetDateTime,
+    last_seen: Option<OffsetDateTime>,
+    last_failure: Option<OffsetDateTime>,
+    failures: i32,
+    reputation: i32,
+}
+
+impl From<PeerRow> for Peer {
+    fn from(row: PeerRow) -> Self {
+        Peer {
+            peer_id: row.peer_id,
+            public_key: row.public_key,
+            addresses: row.addresses,
+            first_seen: row.first_seen,
+            last_seen: row.last_seen,
+            last_failure: row.last_failure,
+            failures: row.failures,
+            reputation: row.reputation,
+        }
+    }
+}
+
+#[async_trait]
+impl PeerStore for PostgresPeerStore {
+    async fn get_peer(&self, peer_id: &str) -> Result<Option<Peer>, PersistenceError> {
+        let row = sqlx::query_as!(
+            PeerRow,
+            r#"
+            SELECT peer_id, public_key, addresses, first_seen, last_seen, last_failure,
+                   failures, reputation
+            FROM peers
+            WHERE peer_id = $1
+            "#,
+            peer_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(row.map(Peer::from))
+    }
+
+    async fn get_peer_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<Peer>, PersistenceError> {
+        let row = sqlx::query_as!(
+            PeerRow,
+            r#"
+            SELECT peer_id, public_key, addresses, first_seen, last_seen, last_failure,
+                   failures, reputation
+            FROM peers
+            WHERE public_key = $1
+            "#,
+            public_key
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(row.map(Peer::from))
+    }
+
+    async fn list_peers(&self, limit: u32) -> Result<Vec<Peer>, PersistenceError> {
+        let rows = sqlx::query_as!(
+            PeerRow,
+            r#"
+            SELECT peer_id, public_key, addresses, first_seen, last_seen, last_failure,
+                   failures, reputation
+            FROM peers
+            ORDER BY reputation DESC, last_seen DESC NULLS LAST
+            LIMIT $1
+            "#,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(rows.into_iter().map(Peer::from).collect())
+    }
+
+    async fn record_connected(
+        &self,
+        peer_id: &str,
+        address: Option<&str>,
+    ) -> Result<Peer, PersistenceError> {
+        // The newest address goes first; older ones beyond the cap are dropped
+        let row = sqlx::query_as!(
+            PeerRow,
+            r#"
+            INSERT INTO peers (peer_id, addresses, last_seen, reputation)
+            VALUES (
+              $1,
+              CASE WHEN $2::TEXT IS NULL THEN '{}'::TEXT[] ELSE ARRAY[$2::TEXT] END,
+              now(),
+              $3
+            )
+            ON CONFLICT (peer_id) DO UPDATE SET
+              addresses = CASE
+                WHEN $2::TEXT IS NULL THEN peers.addresses
+                ELSE (ARRAY[$2::TEXT] || array_remove(peers.addresses, $2::TEXT))[1:$4]
+              END,
+              last_seen = now(),
+              failures = 0,
+              reputation = LEAST(GREATEST(peers.reputation + $3, $5), $6)
+            RETURNING peer_id, public_key, addresses, first_seen, last_seen, last_failure,
+                      failures, reputation
+            "#,
+            peer_id,
+            address,
+            REPUTATION_CONNECTED,
+            MAX_PEER_ADDRESSES,
+            REPUTATION_MIN,
+            REPUTATION_MAX,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(row.into())
+    }
+
+    async fn record_public_key(
+        &self,
+        peer_id: &str,
+        public_key: &str,
+    ) -> Result<(), PersistenceError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO peers (peer_id, public_key)
+            VALUES ($1, $2)
+            ON CONFLICT (peer_id) DO UPDATE SET public_key = EXCLUDED.public_key
+            "#,
+            peer_id,
+            public_key
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn record_failure(&self, peer_id: &str) -> Result<Peer, PersistenceError> {
+        let row = sqlx::query_as!(
+            PeerRow,
+            r#"
+            INSERT INTO peers (peer_id, last_failure, failures, reputation)
+            VALUES ($1, now(), 1, $2)
+            ON CONFLICT (peer_id) DO UPDATE SET
+              last_failure = now(),
+              failures = peers.failures + 1,
+              reputation = LEAST(GREATEST(peers.reputation + $2, $3), $4)
+            RETURNING peer_id, public_key, addresses, first_seen, last_seen, last_failure,
+                      failures, reputation
+            "#,
+            peer_id,
+            REPUTATION_DIAL_FAILURE,
+            REPUTATION_MIN,
+            REPUTATION_MAX,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(row.into())
+    }
+
+    async fn remove_peer(&self, peer_id: &str) -> Result<(), PersistenceError> {
+        sqlx::query!(r#"DELETE FROM peers WHERE peer_id = $1"#, peer_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(())
+    }
+}
@@ -3,10 +3,11 @@
 
 use async_trait::async_trait;
 use module_profiles::http::ProfileDoc;
-use sqlx::{Pool, Postgres, query};
+use sqlx::{PgExecutor, Pool, Postgres};
 use synapse_core::PersistenceError;
 use synapse_core::domain::profiles::Profile;
 use synapse_core::ports::profiles::profile_repository::{ProfilesDocStore, ProfilesRepository};
+use time::OffsetDateTime;
 
 pub struct PostgresProfilesRepository {
     pool: Pool<Postgres>,
@@ -29,44 +30,9 @@ impl ProfilesRepository for PostgresProfilesRepository {
         .await
         .map_err(|e| PersistenceError::Other(e.to_string()))?;
 
-        if let Some(row) = row {
-            let mut doc = ProfileDoc::from_bytes(&row.doc_bytes);
-            let display_name = doc.get_display_name();
-            let handle = doc.get_handle();
-            let bio = doc.get_bio();
-            let location = doc.get_location();
-            let avatar_url = doc.get_avatar_url();
-            let profile = Profile {
-                public_key: public_key.to_string(),
-                handle: if handle.is_empty() {
-                    None
-                } else {
-                    Some(handle)
-                },
-                display_name: if display_name.is_empty() {
-                    None
-                } else {
-                    Some(display_name)
-                },
-                bio: if bio.is_empty() { None } else { Some(bio) },
-                location: if location.is_empty() {
-                    None
-                } else {
-                    Some(location)
-                },
-                avatar_url: if avatar_url.is_empty() {
-                    None
-                } else {
-                    Some(avatar_url)
-                },
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-                signature: None,
-            };
-            Ok(Some(profile))
-        } else {
-            Ok(None)
-        }
+        Ok(row.map(|row| {
+            profile_from_doc(public_key, &row.doc_bytes, row.created_at, row.updated_at)
+        }))
     }
 
     async fn upsert_profile(&self, profile: &Profile) -> Result<Profile, PersistenceError> {
@@ -77,17 +43,24 @@ impl ProfilesRepository for PostgresProfilesRepository {
         }
         let mut doc_for_save = doc;
         let bytes = doc_for_save.to_bytes();
+        let fields = SearchFields::of(&bytes);
 
         let row = sqlx::query!(
             r#"
-            INSERT INTO profiles (public_key, doc_bytes, updated_at)
-            VALUES ($1, $2, now())
+            INSERT INTO profiles
+                (public_key, doc_bytes, updated_at, handle, display_name, bio, indexed_at)
+            VALUES ($1, $2, now(), $3, $4, $5, now())
             ON CONFLICT (public_key)
-            DO UPDATE SET doc_bytes = EXCLUDED.doc_bytes, updated_at = now()
+            DO UPDATE SET doc_bytes = EXCLUDED.doc_bytes, updated_at = now(),
+                handle = EXCLUDED.handle, display_name = EXCLUDED.display_name,
+                bio = EXCLUDED.bio, indexed_at = now()
             RETURNING created_at, updated_at
             "#,
             profile.public_key,
-            bytes
+            bytes,
+            fields.handle,
+            fields.display_name,
+            fields.bio
         )
         .fetch_one(&self.pool)
         .await
@@ -116,6 +89,51 @@ impl ProfilesRepository for PostgresProfilesRepository {
     }
 }
 
+/// Reads the profile of `public_key` out of its stored doc.
+pub(crate) fn profile_from_doc(
+    public_key: &str,
+    doc_bytes: &[u8],
+    created_at: OffsetDateTime,
+    updated_at: OffsetDateTime,
+) -> Profile {
+    let doc = ProfileDoc::from_bytes(doc_bytes);
+    Profile {
+        public_key: public_key.to_string(),
+        handle: non_empty(doc.get_handle()),
+        display_name: non_empty(doc.get_display_name()),
+        bio: non_empty(doc.get_bio()),
+        location: non_empty(doc.get_location()),
+        avatar_url: non_empty(doc.get_avatar_url()),
+        created_at,
+        updated_at,
+        signature: None,
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// Profile fields copied out of the doc for full-text search. Docs that
+/// cannot be read have none.
+pub(crate) struct SearchFields {
+    pub(crate) handle: Option<String>,
+    pub(crate) display_name: Option<String>,
+    pub(crate) bio: Option<String>,
+}
+
+impl SearchFields {
+    pub(crate) fn of(doc_bytes: &[u8]) -> Self {
+        let doc = ProfileDoc::try_from_bytes(doc_bytes);
+        let field = |get: fn(&ProfileDoc) -> String| doc.as_ref().map(get).and_then(non_empty);
+        Self {
+            handle: field(ProfileDoc::get_handle),
+            display_name: field(ProfileDoc::get_display_name),
+            bio: field(ProfileDoc::get_bio),
+        }
+    }
+}
+
 pub struct PostgresProfilesDocStore {
     pool: Pool<Postgres>,
 }
@@ -129,38 +147,90 @@ impl PostgresProfilesDocStore {
 #[async_trait]
 impl ProfilesDocStore for PostgresProfilesDocStore {
     async fn get_doc(&self, public_key: &str) -> Result<Option<Vec<u8>>, PersistenceError> {
-        let row = sqlx::query!(
-            r#"SELECT doc_bytes FROM profiles WHERE public_key = $1"#,
-            public_key
-        )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(row.map(|r| r.doc_bytes))
+        select_doc(&self.pool, public_key).await
     }
 
     async fn upsert_doc(&self, public_key: &str, doc: &[u8]) -> Result<(), PersistenceError> {
-        sqlx::query!(
-            r#"
-            INSERT INTO profiles (public_key, doc_bytes, created_at, updated_at)
-            VALUES ($1, $2, now(), now())
-            ON CONFLICT (public_key)
-            DO UPDATE SET doc_bytes = EXCLUDED.doc_bytes, updated_at = now()
-            "#,
-            public_key,
-            doc
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(())
+        upsert_doc(&self.pool, public_key, doc).await
     }
 
     async fn delete_doc(&self, public_key: &str) -> Result<(), PersistenceError> {
-        sqlx::query!(r#"DELETE FROM profiles WHERE public_key = $1"#, public_key)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| PersistenceError::Other(e.to_string()))?;
-        Ok(())
+        delete_doc(&self.pool, public_key).await
+    }
+
+    async fn list_public_keys(&self) -> Result<Vec<String>, PersistenceError> {
+        select_public_keys(&self.pool).await
     }
 }
+
+// Doc store queries, run through a pool or an open transaction
+
+pub(crate) async fn select_doc<'e, E>(
+    executor: E,
+    public_key: &str,
+) -> Result<Option<Vec<u8>>, PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    let row = sqlx::query!(
+        r#"SELECT doc_bytes FROM profiles WHERE public_key = $1"#,
+        public_key
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(row.map(|r| r.doc_bytes))
+}
+
+pub(crate) async fn upsert_doc<'e, E>(
+    executor: E,
+    public_key: &str,
+    doc: &[u8],
+) -> Result<(), PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    let fields = SearchFields::of(doc);
+    sqlx::query!(
+        r#"
+        INSERT INTO profiles
+            (public_key, doc_bytes, created_at, updated_at, handle, display_name, bio, indexed_at)
+        VALUES ($1, $2, now(), now(), $3, $4, $5, now())
+        ON CONFLICT (public_key)
+        DO UPDATE SET doc_bytes = EXCLUDED.doc_bytes, updated_at = now(),
+            handle = EXCLUDED.handle, display_name = EXCLUDED.display_name,
+            bio = EXCLUDED.bio, indexed_at = now()
+        "#,
+        public_key,
+        doc,
+        fields.handle,
+        fields.display_name,
+        fields.bio
+    )
+    .execute(executor)
+    .await
+    .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(())
+}
+
+pub(crate) async fn delete_doc<'e, E>(executor: E, public_key: &str) -> Result<(), PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    sqlx::query!(r#"DELETE FROM profiles WHERE public_key = $1"#, public_key)
+        .execute(executor)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(())
+}
+
+pub(crate) async fn select_public_keys<'e, E>(executor: E) -> Result<Vec<String>, PersistenceError>
+where
+    E: PgExecutor<'e>,
+{
+    let rows = sqlx::query!(r#"SELECT public_key FROM profiles ORDER BY public_key"#)
+        .fetch_all(executor)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(rows.into_iter().map(|r| r.public_key).collect())
+}
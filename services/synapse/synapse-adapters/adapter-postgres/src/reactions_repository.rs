@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use sqlx::{PgConnection, Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::domain::events::reactions::{ReactionChange, ReactionSummary};
+use synapse_core::ports::reactions::reaction_repository::ReactionsRepository;
+use uuid::Uuid;
+
+pub struct PostgresReactionsRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresReactionsRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ReactionsRepository for PostgresReactionsRepository {
+    async fn summaries(
+        &self,
+        targets: &[Uuid],
+        viewer: Option<&str>,
+    ) -> Result<HashMap<Uuid, Vec<ReactionSummary>>, PersistenceError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                target,
+                emoji,
+                COUNT(*) as "count!",
+                COALESCE(bool_or(agent = $2), false) as "reacted!"
+            FROM reactions
+            WHERE target = ANY($1) AND active
+            GROUP BY target, emoji
+            ORDER BY target, COUNT(*) DESC, emoji
+            "#,
+            targets,
+            viewer
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+        let mut summaries: HashMap<Uuid, Vec<ReactionSummary>> = HashMap::new();
+        for row in rows {
+            summaries.entry(row.target).or_default().push(ReactionSummary {
+                emoji: row.emoji,
+                count: u32::try_from(row.count).unwrap_or(u32::MAX),
+                reacted: row.reacted,
+            });
+        }
+        Ok(summaries)
+    }
+}
+
+/// Applies `change` to the materialized reactions over `conn`, unless a later
+/// change by the same agent has already been applied.
+pub(crate) async fn apply_reaction(
+    conn: &mut PgConnection,
+    change: &ReactionChange,
+) -> Result<(), PersistenceError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO reactions (target, emoji, agent, active, event_id, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (target, emoji, agent) DO UPDATE
+        SET active = EXCLUDED.active,
+            event_id = EXCLUDED.event_id,
+            updated_at = EXCLUDED.updated_at
+        WHERE (reactions.updated_at, reactions.event_id)
+            < (EXCLUDED.updated_at, EXCLUDED.event_id)
+        "#,
+        change.target,
+        change.emoji,
+        change.agent,
+        change.active,
+        change.event_id,
+        change.at
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| PersistenceError::Other(e.to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events_repository::PostgresEventsRepository;
+    use crate::unit_of_work::tests::pool;
+    use synapse_core::domain::events::reactions::{ADD_REACTION, REMOVE_REACTION};
+    use synapse_core::domain::events::{Event, ObjectRef};
+    use synapse_core::ports::events::event_repository::EventRepository;
+    use time::{Duration, OffsetDateTime};
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_latest_reaction_change_wins() {
+        let pool = pool().await;
+        let events = PostgresEventsRepository::new(pool.clone());
+        let reactions = PostgresReactionsRepository::new(pool);
+        let target = Uuid::new_v4();
+        let agent = Uuid::new_v4().to_string();
+        let now = OffsetDateTime::now_utc();
+        let reaction = |event_type: &str, agent: &str, at: OffsetDateTime| {
+            let mut event = Event::new()
+                .with_event_type(event_type)
+                .with_module_kind("reactions")
+                .with_agent(agent)
+                .with_target(ObjectRef::Event(target))
+                .with_content("🔥")
+                .build();
+            event.created_at = at;
+            event
+        };
+
+        // The removal arrives before the add it takes back
+        events
+            .record(reaction(REMOVE_REACTION, &agent, now))
+            .await
+            .unwrap();
+        events
+            .record(reaction(ADD_REACTION, &agent, now - Duration::seconds(1)))
+            .await
+            .unwrap();
+        events
+            .record(reaction(ADD_REACTION, "02other", now))
+            .await
+            .unwrap();
+
+        let summaries = reactions.summaries(&[target], Some(&agent)).await.unwrap();
+        assert_eq!(
+            summaries[&target],
+            vec![ReactionSummary {
+                emoji: "🔥".to_string(),
+                count: 1,
+                reacted: false,
+            }]
+        );
+    }
+}
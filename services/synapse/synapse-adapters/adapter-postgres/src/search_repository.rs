@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use sqlx::{Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::domain::search::{EventHit, ProfileHit, SearchQuery, SearchSort};
+use synapse_core::ports::search::search_repository::SearchRepository;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::events_repository::{EventRow, attach_latest_edits, event_from_row};
+use crate::profiles_repository::{SearchFields, profile_from_doc};
+
+pub struct PostgresSearchRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresSearchRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Copies the searchable fields out of the profiles stored before search
+    /// was added. Returns how many profiles were indexed.
+    pub async fn index_profiles(&self) -> Result<u64, PersistenceError> {
+        let rows = sqlx::query!(
+            r#"SELECT public_key, doc_bytes FROM profiles WHERE indexed_at IS NULL"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+        for row in &rows {
+            let fields = SearchFields::of(&row.doc_bytes);
+            sqlx::query!(
+                r#"
+                UPDATE profiles
+                SET handle = $2, display_name = $3, bio = $4, indexed_at = now()
+                WHERE public_key = $1
+                "#,
+                row.public_key,
+                fields.handle,
+                fields.display_name,
+                fields.bio
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        }
+        Ok(rows.len() as u64)
+    }
+}
+
+struct EventHitRow {
+    id: Uuid,
+    created_at: OffsetDateTime,
+    event_type: String,
+    module_kind: Option<String>,
+    module_slug: Option<String>,
+    agent: String,
+    agent_signature: Option<String>,
+    target: Option<JsonValue>,
+    previous: Option<Uuid>,
+    content: Option<String>,
+    artifacts: Option<Vec<String>>,
+    metadata: Option<JsonValue>,
+    links: Option<Vec<String>>,
+    data: Option<Vec<u8>>,
+    expiration: Option<OffsetDateTime>,
+    rank: f32,
+    snippet: String,
+    reactions: i64,
+}
+
+#[async_trait]
+impl SearchRepository for PostgresSearchRepository {
+    async fn search_events(&self, query: &SearchQuery) -> Result<Vec<EventHit>, PersistenceError> {
+        let since = query.time_range.since(OffsetDateTime::now_utc());
+        let sort = match query.sort {
+            SearchSort::Relevance => "Relevance",
+            SearchSort::Recent => "Recent",
+            SearchSort::Popular => "Popular",
+        };
+
+        let rows = sqlx::query_as!(
+            EventHitRow,
+            r#"
+            WITH candidates AS (
+                SELECT e.id
+                FROM events e
+                WHERE e.search @@ websearch_to_tsquery('english', $1)
+                UNION
+                -- Events whose latest content only matches through an edit
+                SELECT (r.target->>'Event')::uuid
+                FROM events r
+                WHERE r.search @@ websearch_to_tsquery('english', $1)
+                  AND r.event_type LIKE '%:edit'
+            )
+            SELECT
+                e.id,
+                e.created_at,
+                e.event_type,
+                e.module_kind,
+                e.module_slug,
+                e.agent,
+                e.agent_signature,
+                e.target      as "target?: JsonValue",
+                e.previous,
+                e.content,
+                e.artifacts   as "artifacts?: Vec<String>",
+                e.metadata    as "metadata?: JsonValue",
+                e.links       as "links?: Vec<String>",
+                e.data        as "data?: Vec<u8>",
+                e.expiration,
+                hit.rank      as "rank!",
+                ts_headline(
+                    'english',
+                    replace(replace(replace(coalesce(hit.content, ''), '&', '&amp;'), '<', '&lt;'), '>', '&gt;'),
+                    q,
+                    'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15'
+                )             as "snippet!",
+                hit.reactions as "reactions!"
+            FROM candidates c
+            JOIN events e ON e.id = c.id
+            CROSS JOIN websearch_to_tsquery('english', $1) q
+            -- The latest edit of the event is what is matched
+            LEFT JOIN LATERAL (
+                SELECT r.id, r.content, r.search
+                FROM events r
+                WHERE r.target @> jsonb_build_object('Event', e.id)
+                  AND r.event_type = e.module_kind || ':edit'
+                ORDER BY r.created_at DESC, r.id DESC
+                LIMIT 1
+            ) latest ON true
+            CROSS JOIN LATERAL (
+                SELECT
+                    CASE WHEN latest.id IS NULL THEN e.content ELSE latest.content END as content,
+                    CASE WHEN latest.id IS NULL THEN e.search ELSE latest.search END as search,
+                    ts_rank(CASE WHEN latest.id IS NULL THEN e.search ELSE latest.search END, q) as rank,
+                    (SELECT COUNT(*) FROM reactions x WHERE x.target = e.id AND x.active) as reactions
+            ) hit
+            WHERE hit.search @@ q
+              AND e.event_type NOT LIKE '%:edit'
+              AND e.event_type NOT LIKE '%:delete'
+              AND ($2::text IS NULL OR e.event_type = $2)
+              AND ($3::text IS NULL OR e.module_kind = $3)
+              AND ($4::timestamptz IS NULL OR e.created_at >= $4)
+              AND (e.expiration IS NULL OR e.expiration > now())
+              -- Deleted events are hidden
+              AND NOT EXISTS (
+                  SELECT 1 FROM events d
+                  WHERE d.target @> jsonb_build_object('Event', e.id)
+                    AND d.event_type = e.module_kind || ':delete'
+              )
+            ORDER BY
+                CASE WHEN $5 = 'Popular' THEN hit.reactions END DESC NULLS LAST,
+                CASE WHEN $5 <> 'Recent' THEN hit.rank END DESC NULLS LAST,
+                e.created_at DESC,
+                e.id DESC
+            OFFSET $6
+            LIMIT $7
+            "#,
+            query.text,
+            query.event_type,
+            query.module_kind,
+            since,
+            sort,
+            i64::from(query.offset),
+            i64::from(query.limit())
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+        let mut hits = rows
+            .into_iter()
+            .map(|row| {
+                let event = event_from_row(EventRow {
+                    id: row.id,
+                    created_at: row.created_at,
+                    event_type: row.event_type,
+                    module_kind: row.module_kind,
+                    module_slug: row.module_slug,
+                    agent: row.agent,
+                    agent_signature: row.agent_signature,
+                    target: row.target,
+                    previous: row.previous,
+                    content: row.content,
+                    artifacts: row.artifacts,
+                    metadata: row.metadata,
+                    links: row.links,
+                    data: row.data,
+                    expiration: row.expiration,
+                })?;
+                Ok(EventHit {
+                    event,
+                    rank: row.rank,
+                    snippet: row.snippet,
+                    reactions: row.reactions as u64,
+                })
+            })
+            .collect::<Result<Vec<_>, PersistenceError>>()?;
+
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        attach_latest_edits(&mut conn, hits.iter_mut().map(|hit| &mut hit.event)).await?;
+        Ok(hits)
+    }
+
+    async fn search_profiles(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<ProfileHit>, PersistenceError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                p.public_key,
+                p.doc_bytes,
+                p.created_at,
+                p.updated_at,
+                ts_rank(p.search, q) as "rank!"
+            FROM profiles p
+            CROSS JOIN websearch_to_tsquery('simple', $1) q
+            WHERE p.search @@ q
+            ORDER BY 5 DESC, p.public_key
+            OFFSET $2
+            LIMIT $3
+            "#,
+            query.text,
+            i64::from(query.offset),
+            i64::from(query.limit())
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ProfileHit {
+                profile: profile_from_doc(
+                    &row.public_key,
+                    &row.doc_bytes,
+                    row.created_at,
+                    row.updated_at,
+                ),
+                rank: row.rank,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events_repository::PostgresEventsRepository;
+    use crate::profiles_repository::PostgresProfilesDocStore;
+    use crate::unit_of_work::tests::pool;
+    use module_profiles::http::ProfileDoc;
+    use synapse_core::domain::events::{Event, ObjectRef};
+    use synapse_core::ports::events::event_repository::EventRepository;
+    use synapse_core::ports::profiles::profile_repository::ProfilesDocStore;
+
+    fn post(agent: &str, content: &str) -> Event {
+        Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_agent(agent)
+            .with_content(content)
+            .build()
+    }
+
+    fn revision(event_type: &str, agent: &str, original: &Event, content: &str) -> Event {
+        Event::new()
+            .with_event_type(event_type)
+            .with_module_kind("posts")
+            .with_agent(agent)
+            .with_target(ObjectRef::Event(original.id))
+            .with_content(content)
+            .build()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_search_matches_latest_content_of_live_events() {
+        let pool = pool().await;
+        let events = PostgresEventsRepository::new(pool.clone());
+        let index = PostgresSearchRepository::new(pool);
+        let agent = Uuid::new_v4().to_string();
+        let word = format!("marker{}", Uuid::new_v4().simple());
+
+        let plain = post(&agent, &format!("<b>{word}</b> sails tonight"));
+        let edited = post(&agent, "nothing to see");
+        let deleted = post(&agent, &format!("{word} is gone"));
+        for event in [
+            plain.clone(),
+            edited.clone(),
+            deleted.clone(),
+            revision("posts:edit", &agent, &edited, &format!("now {word} appears")),
+            revision("posts:delete", &agent, &deleted, ""),
+        ] {
+            events.record(event).await.unwrap();
+        }
+
+        let hits = index.search_events(&SearchQuery::new(&word)).await.unwrap();
+
+        let mut found: Vec<Uuid> = hits.iter().map(|hit| hit.event.id).collect();
+        found.sort();
+        let mut expected = vec![plain.id, edited.id];
+        expected.sort();
+        assert_eq!(found, expected);
+        let edit_hit = hits.iter().find(|hit| hit.event.id == edited.id).unwrap();
+        assert_eq!(
+            edit_hit.event.current_content(),
+            Some(format!("now {word} appears").as_str())
+        );
+        assert_eq!(edit_hit.event.content.as_deref(), Some("nothing to see"));
+        let plain_hit = hits.iter().find(|hit| hit.event.id == plain.id).unwrap();
+        assert!(
+            plain_hit
+                .snippet
+                .contains(&format!("&lt;b&gt;<mark>{word}</mark>&lt;/b&gt;"))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_search_finds_profiles_by_handle() {
+        let pool = pool().await;
+        let docs = PostgresProfilesDocStore::new(pool.clone());
+        let index = PostgresSearchRepository::new(pool);
+        let public_key = Uuid::new_v4().to_string();
+        let handle = format!("marker{}", Uuid::new_v4().simple());
+        let mut doc = ProfileDoc::new();
+        doc.set_handle(&handle);
+        docs.upsert_doc(&public_key, &doc.to_bytes()).await.unwrap();
+
+        let hits = index
+            .search_profiles(&SearchQuery::new(&handle))
+            .await
+            .unwrap();
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].profile.public_key, public_key);
+        assert_eq!(hits[0].profile.handle.as_deref(), Some(handle.as_str()));
+    }
+}
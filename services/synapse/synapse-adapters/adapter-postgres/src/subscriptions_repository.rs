@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use sqlx::{Pool, Postgres};
+use synapse_core::PersistenceError;
+use synapse_core::ports::federation::subscriptions::{FollowedModule, SubscriptionStore};
+use time::OffsetDateTime;
+
+pub struct PostgresSubscriptionStore {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresSubscriptionStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+struct SubscriptionRow {
+    topic: String,
+    synapse_public_key: String,
+    module_kind: String,
+    module_slug: Option<String>,
+    since: Option<JsonValue>,
+    newest_seen: Option<OffsetDateTime>,
+}
+
+impl TryFrom<SubscriptionRow> for FollowedModule {
+    type Error = PersistenceError;
+
+    fn try_from(row: SubscriptionRow) -> Result<Self, Self::Error> {
+        Ok(FollowedModule {
+            topic: row.topic,
+            synapse_public_key: row.synapse_public_key,
+            module_kind: row.module_kind,
+            module_slug: row.module_slug,
+            since: row
+                .since
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| PersistenceError::Other(e.to_string()))?,
+            newest_seen: row.newest_seen,
+        })
+    }
+}
+
+#[async_trait]
+impl SubscriptionStore for PostgresSubscriptionStore {
+    async fn list_subscriptions(&self) -> Result<Vec<FollowedModule>, PersistenceError> {
+        let rows = sqlx::query_as!(
+            SubscriptionRow,
+            r#"
+            SELECT topic, synapse_public_key, module_kind, module_slug,
+                   since as "since?: JsonValue", newest_seen
+            FROM subscriptions
+            ORDER BY created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        rows.into_iter().map(FollowedModule::try_from).collect()
+    }
+
+    async fn save_subscription(&self, subscription: &FollowedModule) -> Result<(), PersistenceError> {
+        let since = subscription
+            .since
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO subscriptions
+              (topic, synapse_public_key, module_kind, module_slug, since, newest_seen)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (topic) DO UPDATE SET
+              since = EXCLUDED.since,
+              newest_seen = EXCLUDED.newest_seen
+            "#,
+            subscription.topic,
+            subscription.synapse_public_key,
+            subscription.module_kind,
+            subscription.module_slug,
+            since,
+            subscription.newest_seen,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn record_seen(
+        &self,
+        topic: &str,
+        created_at: OffsetDateTime,
+    ) -> Result<(), PersistenceError> {
+        sqlx::query!(
+            r#"
+            UPDATE subscriptions
+            SET newest_seen = GREATEST(newest_seen, $2)
+            WHERE topic = $1
+            "#,
+            topic,
+            created_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove_subscription(&self, topic: &str) -> Result<(), PersistenceError> {
+        sqlx::query!(r#"DELETE FROM subscriptions WHERE topic = $1"#, topic)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| PersistenceError::Other(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unit_of_work::tests::pool;
+    use synapse_core::ports::events::event_repository::EventCursor;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_subscription_survives_and_advances() {
+        let pool = pool().await;
+        let store = PostgresSubscriptionStore::new(pool);
+        let topic = Uuid::new_v4().to_string();
+        let since = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+        store
+            .save_subscription(&FollowedModule {
+                topic: topic.clone(),
+                synapse_public_key: "publisher".to_string(),
+                module_kind: "posts".to_string(),
+                module_slug: None,
+                since: Some(EventCursor::Timestamp(since)),
+                newest_seen: None,
+            })
+            .await
+            .unwrap();
+
+        let later = since + time::Duration::minutes(1);
+        store.record_seen(&topic, later).await.unwrap();
+        store.record_seen(&topic, since).await.unwrap();
+
+        let found = store.list_subscriptions().await.unwrap();
+        let followed = found.iter().find(|f| f.topic == topic).unwrap();
+        assert_eq!(followed.since, Some(EventCursor::Timestamp(since)));
+        assert_eq!(followed.newest_seen, Some(later));
+
+        store.remove_subscription(&topic).await.unwrap();
+        let found = store.list_subscriptions().await.unwrap();
+        assert!(found.iter().all(|f| f.topic != topic));
+    }
+}
@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres, Transaction};
+use synapse_core::domain::events::Event;
+use synapse_core::ports::events::event_repository::{EventFilter, EventRepository};
+use synapse_core::ports::persistence::{UnitOfWork, UnitOfWorkFactory};
+use synapse_core::ports::profiles::profile_repository::ProfilesDocStore;
+use synapse_core::{CoreError, PersistenceError};
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::events_repository::{
+    count_by_metadata, insert_event, select_event, select_events, tombstone_expired,
+};
+use crate::profiles_repository::{delete_doc, select_doc, select_public_keys, upsert_doc};
+
+/// Transaction shared by the repositories of one unit of work. `None` once it
+/// has been committed or rolled back.
+type SharedTransaction = Arc<Mutex<Option<Transaction<'static, Postgres>>>>;
+
+pub struct PostgresUnitOfWorkFactory {
+    pool: Pool<Postgres>,
+}
+
+impl PostgresUnitOfWorkFactory {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for PostgresUnitOfWorkFactory {
+    async fn begin(&self) -> Result<Box<dyn UnitOfWork>, CoreError> {
+        let tx = self.pool.begin().await.map_err(CoreError::persistence)?;
+        Ok(Box::new(PostgresUnitOfWork::new(tx)))
+    }
+}
+
+/// A unit of work backed by a single sqlx transaction.
+pub struct PostgresUnitOfWork {
+    tx: SharedTransaction,
+    events: TxEventsRepository,
+    profile_docs: TxProfilesDocStore,
+}
+
+impl PostgresUnitOfWork {
+    fn new(tx: Transaction<'static, Postgres>) -> Self {
+        let tx = Arc::new(Mutex::new(Some(tx)));
+        Self {
+            events: TxEventsRepository { tx: tx.clone() },
+            profile_docs: TxProfilesDocStore { tx: tx.clone() },
+            tx,
+        }
+    }
+
+    async fn take(&self) -> Result<Transaction<'static, Postgres>, CoreError> {
+        self.tx.lock().await.take().ok_or_else(finished)
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PostgresUnitOfWork {
+    fn events(&mut self) -> &mut dyn EventRepository {
+        &mut self.events
+    }
+
+    fn profile_docs(&mut self) -> &mut dyn ProfilesDocStore {
+        &mut self.profile_docs
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), CoreError> {
+        self.take()
+            .await?
+            .commit()
+            .await
+            .map_err(CoreError::persistence)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), CoreError> {
+        self.take()
+            .await?
+            .rollback()
+            .await
+            .map_err(CoreError::persistence)
+    }
+}
+
+fn finished() -> CoreError {
+    CoreError::persistence("unit of work already finished")
+}
+
+struct TxEventsRepository {
+    tx: SharedTransaction,
+}
+
+#[async_trait]
+impl EventRepository for TxEventsRepository {
+    async fn record(&self, event: Event) -> Result<Event, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        insert_event(tx, event).await
+    }
+
+    async fn retrieve(&self, filter: EventFilter) -> Result<Vec<Event>, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        select_events(tx, filter).await
+    }
+
+    async fn count_by_metadata(
+        &self,
+        event_type: &str,
+        key: &str,
+        values: &[String],
+    ) -> Result<HashMap<String, u64>, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        count_by_metadata(&mut **tx, event_type, key, values).await
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        select_event(&mut **tx, id).await
+    }
+
+    async fn prune_expired(&self, now: OffsetDateTime) -> Result<u64, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        tombstone_expired(&mut **tx, now).await
+    }
+}
+
+struct TxProfilesDocStore {
+    tx: SharedTransaction,
+}
+
+#[async_trait]
+impl ProfilesDocStore for TxProfilesDocStore {
+    async fn get_doc(&self, public_key: &str) -> Result<Option<Vec<u8>>, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        select_doc(&mut **tx, public_key).await
+    }
+
+    async fn upsert_doc(&self, public_key: &str, doc: &[u8]) -> Result<(), PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        upsert_doc(&mut **tx, public_key, doc).await
+    }
+
+    async fn delete_doc(&self, public_key: &str) -> Result<(), PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        delete_doc(&mut **tx, public_key).await
+    }
+
+    async fn list_public_keys(&self) -> Result<Vec<String>, PersistenceError> {
+        let mut tx = self.tx.lock().await;
+        let tx = tx.as_mut().ok_or_else(closed)?;
+        select_public_keys(&mut **tx).await
+    }
+}
+
+fn closed() -> PersistenceError {
+    PersistenceError::Other("transaction already finished".to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::events_repository::PostgresEventsRepository;
+    use crate::profiles_repository::PostgresProfilesDocStore;
+    use synapse_core::domain::events::ObjectRef;
+
+    /// Pool on the database at `DATABASE_URL`, migrated. Tests using it are
+    /// ignored by default; run them with `cargo test -- --ignored`.
+    pub(crate) async fn pool() -> Pool<Postgres> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = crate::create_pool(&url).await.unwrap();
+        crate::migrate(&pool).await.unwrap();
+        pool
+    }
+
+    pub(crate) fn profile_event(owner: &str) -> Event {
+        Event::new()
+            .with_event_type("profiles:set_profile")
+            .with_module_kind("profiles")
+            .with_agent(owner)
+            .with_target(ObjectRef::Agent(owner.to_string()))
+            .build()
+    }
+
+    async fn stored(pool: &Pool<Postgres>, event: &Event) -> (usize, Option<Vec<u8>>) {
+        let events = PostgresEventsRepository::new(pool.clone())
+            .retrieve(EventFilter {
+                agent: Some(event.agent.clone()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let doc = PostgresProfilesDocStore::new(pool.clone())
+            .get_doc(&event.agent)
+            .await
+            .unwrap();
+        (events.len(), doc)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_rollback_discards_event_and_doc() {
+        let pool = pool().await;
+        let event = profile_event(&uuid::Uuid::new_v4().to_string());
+
+        let mut uow = PostgresUnitOfWorkFactory::new(pool.clone()).begin().await.unwrap();
+        uow.profile_docs().upsert_doc(&event.agent, b"doc").await.unwrap();
+        uow.events().record(event.clone()).await.unwrap();
+        uow.rollback().await.unwrap();
+
+        assert_eq!(stored(&pool, &event).await, (0, None));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_commit_persists_event_and_doc() {
+        let pool = pool().await;
+        let event = profile_event(&uuid::Uuid::new_v4().to_string());
+
+        let mut uow = PostgresUnitOfWorkFactory::new(pool.clone()).begin().await.unwrap();
+        uow.profile_docs().upsert_doc(&event.agent, b"doc").await.unwrap();
+        uow.events().record(event.clone()).await.unwrap();
+        // Nothing is visible outside the transaction until it commits
+        assert_eq!(stored(&pool, &event).await, (0, None));
+        uow.commit().await.unwrap();
+
+        assert_eq!(stored(&pool, &event).await, (1, Some(b"doc".to_vec())));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL"]
+    async fn test_record_is_idempotent_for_replays() {
+        let pool = pool().await;
+        let repo = PostgresEventsRepository::new(pool.clone());
+        let event = profile_event(&uuid::Uuid::new_v4().to_string());
+
+        repo.record(event.clone()).await.unwrap();
+        let replayed = repo.record(event.clone()).await.unwrap();
+        assert_eq!(replayed.id, event.id);
+        assert_eq!(stored(&pool, &event).await.0, 1);
+
+        let mut conflicting = event.clone();
+        conflicting.content = Some("changed".to_string());
+        assert!(matches!(
+            repo.record(conflicting).await,
+            Err(PersistenceError::Constraint(_))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use synapse_core::ports::auth::SessionRepository;
+use synapse_core::ports::crypto::CryptoRepository;
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+/// How often dead sessions and challenges are deleted. They no longer
+/// authenticate anyone in between.
+pub const AUTH_PRUNE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Deletes expired or revoked sessions and expired login challenges every
+/// `AUTH_PRUNE_INTERVAL`.
+///
+/// Runs forever; spawn it on the runtime.
+pub async fn prune_auth_records(
+    sessions: Arc<dyn SessionRepository>,
+    challenges: Arc<dyn CryptoRepository>,
+) {
+    let mut interval = tokio::time::interval(AUTH_PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = OffsetDateTime::now_utc();
+        match sessions.prune_sessions(now).await {
+            Ok(0) => {}
+            Ok(pruned) => debug!("pruned {pruned} dead sessions"),
+            Err(e) => warn!("failed to prune sessions: {e}"),
+        }
+        match challenges.prune_challenges(now).await {
+            Ok(0) => {}
+            Ok(pruned) => debug!("pruned {pruned} expired challenges"),
+            Err(e) => warn!("failed to prune challenges: {e}"),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use synapse_core::CoreError;
+use synapse_core::domain::events::keys::KeyHistory;
+use synapse_core::domain::events::{Event, PublicKey};
+use synapse_core::ports::keys::key_repository::KeyRepository;
+
+/// The key history of `agent` from the key events recorded here and those in
+/// `carried`, which come along with an event to authenticate.
+pub async fn key_history(
+    keys: &dyn KeyRepository,
+    agent: &str,
+    carried: Vec<Event>,
+) -> Result<KeyHistory, CoreError> {
+    let mut events = keys.key_events(agent).await?;
+    events.extend(carried);
+    Ok(KeyHistory::from_events(agent, events))
+}
+
+/// The agent `key` signs for now: the agent that rotated to it, or the agent
+/// it identifies if it never was rotated to. `None` if `key` was rotated away,
+/// so it no longer signs for anyone.
+pub async fn agent_of(keys: &dyn KeyRepository, key: &str) -> Result<Option<PublicKey>, CoreError> {
+    if key_history(keys, key, Vec::new()).await?.current_key() != key {
+        return Ok(None);
+    }
+    for agent in keys.agents_rotated_to(key).await? {
+        if key_history(keys, &agent, Vec::new()).await?.current_key() == key {
+            return Ok(Some(agent));
+        }
+    }
+    Ok(Some(key.to_string()))
+}
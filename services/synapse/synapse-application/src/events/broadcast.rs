@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use serde::Serialize;
+use synapse_core::domain::events::Event;
+use tokio::sync::broadcast;
+
+/// Number of recorded events buffered per subscriber before it starts lagging.
+pub const EVENT_BROADCAST_CAPACITY: usize = 1024;
+
+/// An event as seen by live subscribers.
+#[derive(Clone, Debug, Serialize)]
+pub struct BroadcastEvent {
+    /// Public key of the followed Synapse the event was published by, or `None`
+    /// for events recorded by this Synapse.
+    pub origin: Option<String>,
+    pub event: Event,
+}
+
+/// Fan-out of newly recorded events to live subscribers.
+///
+/// Cloning yields another handle to the same channel.
+#[derive(Clone)]
+pub struct EventBroadcast {
+    tx: broadcast::Sender<BroadcastEvent>,
+}
+
+impl EventBroadcast {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self { tx }
+    }
+
+    /// Publishes a recorded event. Events published with no subscribers are dropped.
+    pub fn publish(&self, event: Event) {
+        let _ = self.tx.send(BroadcastEvent {
+            origin: None,
+            event,
+        });
+    }
+
+    /// Publishes an event received from the followed Synapse `origin`.
+    pub fn publish_remote(&self, origin: String, event: Event) {
+        let _ = self.tx.send(BroadcastEvent {
+            origin: Some(origin),
+            event,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BroadcastEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for EventBroadcast {
+    fn default() -> Self {
+        Self::new(EVENT_BROADCAST_CAPACITY)
+    }
+}
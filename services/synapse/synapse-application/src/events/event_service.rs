@@ -1,17 +1,28 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+use crate::auth::keys::key_history;
+use crate::events::broadcast::EventBroadcast;
 use crate::events::{
     CreateEventCommand, CreateLocalEventUseCase, CreateRemoteEventCommand, CreateRemoteEventUseCase,
+    MAX_CLOCK_SKEW,
 };
 use async_trait::async_trait;
 use std::sync::Arc;
-use synapse_core::CoreError;
-use synapse_core::domain::events::Event;
+use synapse_core::require_event_signature_with;
+use synapse_core::{CoreError, PersistenceError};
+use synapse_core::domain::events::delegations::{DelegationChange, delegation_grant};
+use synapse_core::domain::events::keys::{KeyChange, KeyHistory, carried_key_changes};
+use synapse_core::domain::events::revisions::{Revision, authorize_revision};
+use synapse_core::domain::events::{Event, PublicKey};
+use synapse_core::domain::modules::ModuleCapability;
+use synapse_core::ports::delegations::delegation_repository::DelegationRepository;
 use synapse_core::ports::events::event_repository::EventRepository;
 use synapse_core::ports::federation::FederationTransport;
 use synapse_core::ports::federation::MessageHandler;
+use synapse_core::ports::keys::key_repository::KeyRepository;
 use synapse_core::ports::modules::ModuleRegistry;
+use synapse_core::ports::persistence::{UnitOfWork, UnitOfWorkFactory};
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -41,9 +52,10 @@ impl<R: EventRepository + Send + Sync, T: ModuleRegistry + Send + Sync> CreateLo
             ));
         }
 
+        let (id, created_at) = event_identity(cmd.id, cmd.created_at, OffsetDateTime::now_utc())?;
         let event = Event {
-            id: Uuid::new_v4(),
-            created_at: OffsetDateTime::now_utc(),
+            id,
+            created_at,
             event_type: cmd.event_type,
             module_kind: cmd.module_kind,
             module_slug: cmd.module_slug,
@@ -57,25 +69,232 @@ impl<R: EventRepository + Send + Sync, T: ModuleRegistry + Send + Sync> CreateLo
             data: cmd.data,
             expiration: cmd.expiration,
             agent_signature: cmd.agent_signature,
+            latest_edit: None,
         };
 
         Ok(self.ingest.ingest(event).await?)
     }
 }
 
+/// Id and creation time of a new event. A client-supplied identity is kept as
+/// is, since the agent signature covers it, once it passes validation; missing
+/// parts are generated.
+fn event_identity(
+    id: Option<Uuid>,
+    created_at: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Result<(Uuid, OffsetDateTime), CoreError> {
+    if let Some(id) = id
+        && !matches!(id.get_version_num(), 4 | 7)
+    {
+        return Err(CoreError::Validation(format!(
+            "event id {id} must be a v4 or v7 UUID"
+        )));
+    }
+    if let Some(created_at) = created_at
+        && (created_at - now).abs() > MAX_CLOCK_SKEW
+    {
+        return Err(CoreError::Validation(format!(
+            "event created_at {created_at} is more than {MAX_CLOCK_SKEW} from the Synapse clock"
+        )));
+    }
+    Ok((id.unwrap_or_else(Uuid::new_v4), created_at.unwrap_or(now)))
+}
+
 pub struct EventIngestService<R: EventRepository, T: ModuleRegistry> {
     registry: Arc<T>,
     repo: Arc<R>,
+    units: Arc<dyn UnitOfWorkFactory>,
+    broadcast: EventBroadcast,
+    moderators: Vec<PublicKey>,
+    delegations: Option<Arc<dyn DelegationRepository>>,
+    keys: Option<Arc<dyn KeyRepository>>,
 }
 
 impl<R: EventRepository, T: ModuleRegistry> EventIngestService<R, T> {
-    pub fn new(repo: Arc<R>, registry: Arc<T>) -> Self {
-        Self { registry, repo }
+    pub fn new(repo: Arc<R>, registry: Arc<T>, units: Arc<dyn UnitOfWorkFactory>) -> Self {
+        Self {
+            registry,
+            repo,
+            units,
+            broadcast: EventBroadcast::default(),
+            moderators: Vec::new(),
+            delegations: None,
+            keys: None,
+        }
+    }
+
+    /// Agents allowed to edit and delete other agents' events.
+    pub fn with_moderators(mut self, moderators: Vec<PublicKey>) -> Self {
+        self.moderators = moderators;
+        self
+    }
+
+    /// Revocations checked for events signed by a delegate of their agent.
+    pub fn with_delegations(mut self, delegations: Arc<dyn DelegationRepository>) -> Self {
+        self.delegations = Some(delegations);
+        self
+    }
+
+    /// Key events recorded here, making up the key histories of agents along
+    /// with those their events carry.
+    pub fn with_keys(mut self, keys: Arc<dyn KeyRepository>) -> Self {
+        self.keys = Some(keys);
+        self
     }
 
+    /// Handle for subscribing to events as they are recorded.
+    pub fn broadcast(&self) -> EventBroadcast {
+        self.broadcast.clone()
+    }
+
+    /// Records `event` and publishes it. Re-delivering a recorded event returns
+    /// the stored copy without publishing it again. Events signed by a delegate,
+    /// and key changes, are authenticated even when a session vouches for
+    /// their agent.
     pub async fn ingest(&self, event: Event) -> Result<Event, CoreError> {
-        let stored = self.repo.record(event).await.map_err(CoreError::from)?;
-        Ok(stored)
+        if let Some(stored) = self.replayed(&event).await? {
+            return Ok(stored);
+        }
+        let key_changes = if delegation_grant(&event)?.is_some() || KeyChange::of(&event)?.is_some() {
+            self.authenticate(&event).await?
+        } else {
+            Vec::new()
+        };
+        reject_expired(&event)?;
+        self.authorize_revision(&event).await?;
+
+        // Key changes carried along commit together with the event
+        let event_type = event.event_type.clone();
+        let mut uow = self.units.begin().await?;
+        let result = async {
+            let mut stored = record_all(uow.events(), key_changes).await?;
+            let event = uow.events().record(event).await.map_err(record_error)?;
+            stored.push(event.clone());
+            Ok::<_, CoreError>((event, stored))
+        }
+        .await;
+        self.finish(uow, result, &event_type).await
+    }
+
+    /// Checks that `event` is signed by the key its agent held when it was
+    /// created, or by a delegate whose delegation was not revoked. Returns the
+    /// key changes of the history it was checked against that are not stored
+    /// here yet, other than `event` itself; recording them along with the event
+    /// spreads key histories with the events signed under them.
+    async fn authenticate(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
+        let carried = carried_key_changes(event)?;
+        let history = match &self.keys {
+            Some(keys) => key_history(keys.as_ref(), &event.agent, carried).await?,
+            None => KeyHistory::from_events(event.agent.clone(), carried),
+        };
+        require_event_signature_with(event, &history)?;
+        if let Some(grant) = delegation_grant(event)? {
+            // Expiry is judged by this Synapse's clock, not the delegate's
+            if let Some(DelegationChange::Grant(delegation)) = DelegationChange::of(&grant)? {
+                delegation.check_in_force(event, OffsetDateTime::now_utc())?;
+            }
+            if let Some(delegations) = &self.delegations
+                && delegations.is_revoked(grant.id, &event.agent).await?
+            {
+                return Err(CoreError::Authentication(format!(
+                    "delegation {} was revoked",
+                    grant.id
+                )));
+            }
+        }
+        let mut unknown = Vec::new();
+        for change in history.changes().filter(|change| change.id != event.id) {
+            if self.repo.find(change.id).await?.is_none() {
+                unknown.push(change.clone());
+            }
+        }
+        Ok(unknown)
+    }
+
+    /// Commits `uow` and publishes the events it stored if `result` is ok,
+    /// otherwise rolls it back.
+    async fn finish<V>(
+        &self,
+        uow: Box<dyn UnitOfWork>,
+        result: Result<(V, Vec<Event>), CoreError>,
+        event_type: &str,
+    ) -> Result<V, CoreError> {
+        match result {
+            Ok((value, stored)) => {
+                uow.commit().await?;
+                for event in stored {
+                    self.broadcast.publish(event);
+                }
+                Ok(value)
+            }
+            Err(err) => {
+                if let Err(e) = uow.rollback().await {
+                    tracing::warn!("failed to roll back {event_type}: {e}");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// The stored copy of `event` if it was already recorded. Fails with
+    /// `CoreError::Conflict` if its id belongs to a different event, and with
+    /// `CoreError::Validation` if it belongs to an expired one, which may have
+    /// been pruned to a tombstone.
+    async fn replayed(&self, event: &Event) -> Result<Option<Event>, CoreError> {
+        match self.repo.find(event.id).await? {
+            Some(stored) if stored.is_expired(OffsetDateTime::now_utc()) => Err(
+                CoreError::Validation(format!("event {} has expired", event.id)),
+            ),
+            Some(stored) if stored.is_replay_of(event) => Ok(Some(stored)),
+            Some(_) => Err(CoreError::Conflict(format!(
+                "event {} already exists with a different payload",
+                event.id
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks an edit or delete against the event it revises, which must be
+    /// stored here.
+    async fn authorize_revision(&self, event: &Event) -> Result<(), CoreError> {
+        let Some(revision) = Revision::of(event)? else {
+            return Ok(());
+        };
+        let original = self
+            .repo
+            .find(revision.original())
+            .await?
+            .ok_or_else(|| CoreError::NotFound(format!("event {}", revision.original())))?;
+        authorize_revision(event, &original, &self.moderators)
+    }
+}
+
+/// Events that are expired on arrival are not worth storing or relaying.
+fn reject_expired(event: &Event) -> Result<(), CoreError> {
+    if event.is_expired(OffsetDateTime::now_utc()) {
+        return Err(CoreError::Validation(format!("event {} has expired", event.id)));
+    }
+    Ok(())
+}
+
+/// Records `events` in order, returning the stored copies.
+async fn record_all(
+    repo: &mut dyn EventRepository,
+    events: Vec<Event>,
+) -> Result<Vec<Event>, CoreError> {
+    let mut stored = Vec::with_capacity(events.len());
+    for event in events {
+        stored.push(repo.record(event).await.map_err(record_error)?);
+    }
+    Ok(stored)
+}
+
+/// An id taken by another event surfaces as a conflict.
+fn record_error(err: PersistenceError) -> CoreError {
+    match err {
+        PersistenceError::Constraint(reason) => CoreError::Conflict(reason),
+        err => err.into(),
     }
 }
 
@@ -85,32 +304,87 @@ impl<R: EventRepository + Send + Sync, T: ModuleRegistry + Send + Sync> MessageH
 {
     async fn handle_message(&self, event: Event) -> Result<Vec<Event>, CoreError> {
         let event_type = event.event_type.clone();
-        
-        let replies = match event.module_kind.as_deref() {
-            Some(kind) => {
-                let module = self.registry.get(kind).ok_or_else(|| {
-                    CoreError::Validation(format!("module '{}' not registered", kind))
-                })?;
-                module.handle_event(&event).await?
-            }
-            None => {
-                return Err(CoreError::Validation(
-                    "unable to validate module_kind".to_string(),
-                ));
-            }
+
+        let Some(kind) = event.module_kind.as_deref() else {
+            return Err(CoreError::Validation(
+                "unable to validate module_kind".to_string(),
+            ));
         };
+        let module = self
+            .registry
+            .get(kind)
+            .ok_or_else(|| CoreError::UnsupportedModule(kind.to_string()))?;
+        let spec = self
+            .registry
+            .event_type(kind, &event_type)
+            .ok_or_else(|| CoreError::UnsupportedAction(event_type.clone()))?;
+
+        // Stored events arriving over federation must be dated by this
+        // Synapse's clock, as local ones are
+        if spec.persisted {
+            event_identity(Some(event.id), Some(event.created_at), OffsetDateTime::now_utc())?;
+        }
+        // Writes arriving over federation must be signed by the agent
+        let key_changes = if spec.requires_auth {
+            self.authenticate(&event).await?
+        } else {
+            Vec::new()
+        };
+        if !spec.persisted {
+            if !key_changes.is_empty() {
+                let mut uow = self.units.begin().await?;
+                let result = record_all(uow.events(), key_changes).await.map(|stored| ((), stored));
+                self.finish(uow, result, &event_type).await?;
+            }
+            return module.handle_event(&event).await;
+        }
+        // Retried and fanned-out deliveries must not repeat the side effects
+        if let Some(stored) = self.replayed(&event).await? {
+            return Ok(vec![stored]);
+        }
+        reject_expired(&event)?;
+        self.authorize_revision(&event).await?;
 
-        // Only ingest write events, not read queries or system events.
-        // Read queries (list_, get_) and system events (synapse:) should not be stored.
-        let is_read_or_system = event_type.contains(":list_")
-            || event_type.contains(":get_")
-            || event_type.starts_with("synapse:");
+        // The module's side effects, the event and the key changes it carries
+        // commit or roll back together
+        let mut uow = self.units.begin().await?;
+        let result = async {
+            let mut stored = record_all(uow.events(), key_changes).await?;
+            let replies = module.handle_event_in(&event, uow.as_mut()).await?;
+            stored.push(uow.events().record(event).await.map_err(record_error)?);
+            Ok::<_, CoreError>((replies, stored))
+        }
+        .await;
+        self.finish(uow, result, &event_type).await
+    }
 
-        if !is_read_or_system {
-            self.ingest(event).await?;
+    async fn handle_publication(
+        &self,
+        synapse_public_key: String,
+        event: Event,
+    ) -> Result<(), CoreError> {
+        // Remote events stay owned by their Synapse; they are only relayed to
+        // local subscribers, and only while they are live
+        if event.is_expired(OffsetDateTime::now_utc()) {
+            return Ok(());
         }
+        self.broadcast.publish_remote(synapse_public_key, event);
+        Ok(())
+    }
 
-        Ok(replies)
+    fn module_capabilities(&self) -> Vec<ModuleCapability> {
+        self.registry
+            .installed_kinds()
+            .into_iter()
+            .filter_map(|kind| {
+                let module = self.registry.get(&kind)?;
+                Some(ModuleCapability {
+                    kind,
+                    version: module.version().ok()?,
+                    event_types: module.event_types(),
+                })
+            })
+            .collect()
     }
 }
 
@@ -143,9 +417,14 @@ impl<T: FederationTransport + Send + Sync> CreateRemoteEventUseCase for RemoteEv
             ));
         }
 
+        let (id, created_at) = event_identity(
+            cmd.event.id,
+            cmd.event.created_at,
+            OffsetDateTime::now_utc(),
+        )?;
         let event = Event {
-            id: Uuid::new_v4(),
-            created_at: OffsetDateTime::now_utc(),
+            id,
+            created_at,
             event_type: cmd.event.event_type,
             module_kind: cmd.event.module_kind,
             module_slug: cmd.event.module_slug,
@@ -159,6 +438,7 @@ impl<T: FederationTransport + Send + Sync> CreateRemoteEventUseCase for RemoteEv
             data: cmd.event.data,
             expiration: cmd.event.expiration,
             agent_signature: cmd.event.agent_signature,
+            latest_edit: None,
         };
 
         Ok(self
@@ -167,3 +447,519 @@ impl<T: FederationTransport + Send + Sync> CreateRemoteEventUseCase for RemoteEv
             .await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::InMemoryModuleRegistry;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use synapse_core::domain::events::ObjectRef;
+    use synapse_core::domain::modules::EventTypeSpec;
+    use synapse_core::ports::events::event_repository::EventFilter;
+    use synapse_core::ports::modules::Module;
+    use synapse_core::ports::profiles::profile_repository::ProfilesDocStore;
+    use synapse_core::sign_event;
+    use synapse_core::domain::events::delegations::{
+        DELEGATION_KEY, Delegation, DelegationScope, delegation_event, delegation_token,
+    };
+    use synapse_core::domain::events::keys::{
+        KEY_HISTORY_KEY, ROTATE_KEY, SET_RECOVERY_KEY, key_proof, rotation_event,
+    };
+
+    /// Events and profile docs written so far, committed or staged.
+    #[derive(Default)]
+    struct Memory {
+        events: Mutex<Vec<Event>>,
+        docs: Mutex<HashMap<String, Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl EventRepository for Memory {
+        async fn record(&self, event: Event) -> Result<Event, PersistenceError> {
+            self.events.lock().unwrap().push(event.clone());
+            Ok(event)
+        }
+        async fn retrieve(&self, _filter: EventFilter) -> Result<Vec<Event>, PersistenceError> {
+            Ok(self.events.lock().unwrap().clone())
+        }
+        async fn count_by_metadata(
+            &self,
+            _event_type: &str,
+            _key: &str,
+            _values: &[String],
+        ) -> Result<HashMap<String, u64>, PersistenceError> {
+            Ok(HashMap::new())
+        }
+        async fn find(&self, id: Uuid) -> Result<Option<Event>, PersistenceError> {
+            Ok(self.events.lock().unwrap().iter().find(|e| e.id == id).cloned())
+        }
+        async fn prune_expired(&self, now: OffsetDateTime) -> Result<u64, PersistenceError> {
+            let mut events = self.events.lock().unwrap();
+            let mut pruned = 0;
+            for event in events.iter_mut().filter(|e| e.is_expired(now)) {
+                event.content = None;
+                event.artifacts = None;
+                event.links = None;
+                event.data = None;
+                pruned += 1;
+            }
+            Ok(pruned)
+        }
+    }
+
+    #[async_trait]
+    impl KeyRepository for Memory {
+        async fn key_events(&self, agent: &str) -> Result<Vec<Event>, PersistenceError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .filter(|e| e.agent == agent)
+                .filter(|e| e.event_type == ROTATE_KEY || e.event_type == SET_RECOVERY_KEY)
+                .cloned()
+                .collect())
+        }
+        async fn agents_rotated_to(&self, key: &str) -> Result<Vec<PublicKey>, PersistenceError> {
+            let events = self.events.lock().unwrap();
+            Ok(events
+                .iter()
+                .filter(|e| e.event_type == ROTATE_KEY)
+                .filter(|e| e.target == Some(ObjectRef::Agent(key.to_string())))
+                .map(|e| e.agent.clone())
+                .collect())
+        }
+    }
+
+    #[async_trait]
+    impl ProfilesDocStore for Memory {
+        async fn get_doc(&self, public_key: &str) -> Result<Option<Vec<u8>>, PersistenceError> {
+            Ok(self.docs.lock().unwrap().get(public_key).cloned())
+        }
+        async fn upsert_doc(&self, public_key: &str, doc: &[u8]) -> Result<(), PersistenceError> {
+            self.docs.lock().unwrap().insert(public_key.to_string(), doc.to_vec());
+            Ok(())
+        }
+        async fn delete_doc(&self, public_key: &str) -> Result<(), PersistenceError> {
+            self.docs.lock().unwrap().remove(public_key);
+            Ok(())
+        }
+        async fn list_public_keys(&self) -> Result<Vec<String>, PersistenceError> {
+            Ok(self.docs.lock().unwrap().keys().cloned().collect())
+        }
+    }
+
+    /// Stages writes and applies them to `committed` on commit.
+    struct MemoryUnit {
+        committed: Arc<Memory>,
+        staged: Memory,
+    }
+
+    #[async_trait]
+    impl UnitOfWork for MemoryUnit {
+        fn events(&mut self) -> &mut dyn EventRepository {
+            &mut self.staged
+        }
+        fn profile_docs(&mut self) -> &mut dyn ProfilesDocStore {
+            &mut self.staged
+        }
+        async fn commit(self: Box<Self>) -> Result<(), CoreError> {
+            let staged = self.staged;
+            self.committed.events.lock().unwrap().extend(staged.events.into_inner().unwrap());
+            self.committed.docs.lock().unwrap().extend(staged.docs.into_inner().unwrap());
+            Ok(())
+        }
+        async fn rollback(self: Box<Self>) -> Result<(), CoreError> {
+            Ok(())
+        }
+    }
+
+    struct MemoryUnits(Arc<Memory>);
+
+    #[async_trait]
+    impl UnitOfWorkFactory for MemoryUnits {
+        async fn begin(&self) -> Result<Box<dyn UnitOfWork>, CoreError> {
+            Ok(Box::new(MemoryUnit {
+                committed: self.0.clone(),
+                staged: Memory::default(),
+            }))
+        }
+    }
+
+    /// Stores a profile doc for the agent, then rejects events without content.
+    struct DocModule;
+
+    #[async_trait]
+    impl Module for DocModule {
+        fn kind(&self) -> Result<String, CoreError> {
+            Ok("docs".to_string())
+        }
+        fn version(&self) -> Result<String, CoreError> {
+            Ok("0.1.0".to_string())
+        }
+        fn event_types(&self) -> Vec<EventTypeSpec> {
+            vec![EventTypeSpec::command("docs:write")]
+        }
+        async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
+            Err(CoreError::UnsupportedAction(event.event_type.clone()))
+        }
+        async fn handle_event_in(
+            &self,
+            event: &Event,
+            uow: &mut dyn UnitOfWork,
+        ) -> Result<Vec<Event>, CoreError> {
+            uow.profile_docs().upsert_doc(&event.agent, b"doc").await?;
+            match event.content {
+                Some(_) => Ok(vec![]),
+                None => Err(CoreError::Validation("content required".to_string())),
+            }
+        }
+    }
+
+    fn service(memory: &Arc<Memory>) -> EventIngestService<Memory, InMemoryModuleRegistry> {
+        let registry = Arc::new(InMemoryModuleRegistry::new());
+        registry.register(Arc::new(DocModule)).unwrap();
+        EventIngestService::new(memory.clone(), registry, Arc::new(MemoryUnits(memory.clone())))
+    }
+
+    fn signed_event(content: Option<&str>) -> Event {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut event = Event::new()
+            .with_event_type("docs:write")
+            .with_module_kind("docs")
+            .with_agent(agent)
+            .build();
+        event.content = content.map(str::to_string);
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+        event
+    }
+
+    #[tokio::test]
+    async fn test_module_failure_leaves_no_partial_state() {
+        let memory = Arc::new(Memory::default());
+
+        let result = service(&memory).handle_message(signed_event(None)).await;
+
+        assert!(matches!(result, Err(CoreError::Validation(_))));
+        assert!(memory.events.lock().unwrap().is_empty());
+        assert!(memory.docs.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backdated_federated_write_is_rejected() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let mut event = signed_event(Some("hello"));
+        event.created_at -= MAX_CLOCK_SKEW * 2;
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+        let memory = Arc::new(Memory::default());
+
+        let result = service(&memory).handle_message(event).await;
+
+        assert!(matches!(result, Err(CoreError::Validation(_))));
+        assert!(memory.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_module_side_effects_commit_with_event() {
+        let memory = Arc::new(Memory::default());
+        let event = signed_event(Some("hello"));
+
+        service(&memory).handle_message(event.clone()).await.unwrap();
+
+        assert_eq!(memory.events.lock().unwrap()[0].id, event.id);
+        assert!(memory.docs.lock().unwrap().contains_key(&event.agent));
+    }
+
+    #[tokio::test]
+    async fn test_undeclared_event_type_is_rejected() {
+        let memory = Arc::new(Memory::default());
+        let mut event = signed_event(Some("hello"));
+        event.event_type = "docs:list_docs".to_string();
+
+        let result = service(&memory).handle_message(event).await;
+
+        assert!(matches!(result, Err(CoreError::UnsupportedAction(_))));
+        assert!(memory.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replayed_event_is_a_noop() {
+        let memory = Arc::new(Memory::default());
+        let service = service(&memory);
+        let event = signed_event(Some("hello"));
+        service.handle_message(event.clone()).await.unwrap();
+        memory.docs.lock().unwrap().clear();
+
+        let replies = service.handle_message(event.clone()).await.unwrap();
+
+        assert_eq!(replies[0].id, event.id);
+        assert_eq!(memory.events.lock().unwrap().len(), 1);
+        assert!(memory.docs.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_event_under_same_id_is_rejected() {
+        let memory = Arc::new(Memory::default());
+        let service = service(&memory);
+        let event = signed_event(Some("hello"));
+        service.ingest(event.clone()).await.unwrap();
+
+        let mut conflicting = event;
+        conflicting.data = Some(vec![1]);
+        let result = service.ingest(conflicting).await;
+
+        assert!(matches!(result, Err(CoreError::Conflict(_))));
+        assert_eq!(memory.events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_local_event_keeps_signed_identity() {
+        let memory = Arc::new(Memory::default());
+        let local = LocalEventService::new(Arc::new(service(&memory)));
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let mut event = signed_event(Some("hello"));
+        event.data = Some(b"doc".to_vec());
+        event.expiration = Some(event.created_at + time::Duration::hours(1));
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+
+        let stored = local
+            .execute(CreateEventCommand {
+                id: Some(event.id),
+                created_at: Some(event.created_at),
+                event_type: event.event_type.clone(),
+                agent: event.agent.clone(),
+                module_kind: event.module_kind.clone(),
+                content: event.content.clone(),
+                data: event.data.clone(),
+                expiration: event.expiration,
+                agent_signature: event.agent_signature.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert!(stored.is_replay_of(&event));
+        assert_eq!(
+            synapse_core::verify_event_signature(&stored),
+            synapse_core::SignatureVerificationResult::Valid
+        );
+    }
+
+    /// Delegations revoked so far.
+    #[derive(Default)]
+    struct Revocations(Mutex<Vec<Uuid>>);
+
+    #[async_trait]
+    impl DelegationRepository for Revocations {
+        async fn list_delegations(
+            &self,
+            _agent: &str,
+            _now: OffsetDateTime,
+        ) -> Result<Vec<Delegation>, PersistenceError> {
+            Ok(Vec::new())
+        }
+        async fn is_revoked(&self, delegation: Uuid, _agent: &str) -> Result<bool, PersistenceError> {
+            Ok(self.0.lock().unwrap().contains(&delegation))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revoked_delegate_is_rejected() {
+        let agent_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let device_key = k256::ecdsa::SigningKey::from_slice(&[4u8; 32]).unwrap();
+        let public = |key: &k256::ecdsa::SigningKey| {
+            hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+        };
+        let scope = DelegationScope {
+            name: "Bot".to_string(),
+            ..Default::default()
+        };
+        let mut grant = delegation_event(public(&agent_key), public(&device_key), &scope).unwrap();
+        grant.agent_signature = Some(sign_event(&grant, &agent_key));
+        let delegated = |content: &str| {
+            let mut metadata = HashMap::new();
+            metadata.insert(DELEGATION_KEY.to_string(), delegation_token(&grant).unwrap());
+            let mut event = Event::new()
+                .with_event_type("docs:write")
+                .with_module_kind("docs")
+                .with_agent(public(&agent_key))
+                .with_content(content)
+                .with_metadata(metadata)
+                .build();
+            event.agent_signature = Some(sign_event(&event, &device_key));
+            event
+        };
+        let memory = Arc::new(Memory::default());
+        let revocations = Arc::new(Revocations::default());
+        let service = service(&memory).with_delegations(revocations.clone());
+
+        service.handle_message(delegated("hello")).await.unwrap();
+
+        revocations.0.lock().unwrap().push(grant.id);
+        let result = service.handle_message(delegated("again")).await;
+        assert!(matches!(result, Err(CoreError::Authentication(_))));
+        assert_eq!(memory.events.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backdated_delegate_event_is_rejected_after_expiry() {
+        let agent_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let device_key = k256::ecdsa::SigningKey::from_slice(&[4u8; 32]).unwrap();
+        let public = |key: &k256::ecdsa::SigningKey| {
+            hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+        };
+        let now = OffsetDateTime::now_utc();
+        let scope = DelegationScope {
+            name: "Phone".to_string(),
+            expires_at: Some(now - time::Duration::minutes(1)),
+            ..Default::default()
+        };
+        let mut grant = delegation_event(public(&agent_key), public(&device_key), &scope).unwrap();
+        grant.created_at = now - time::Duration::hours(1);
+        grant.agent_signature = Some(sign_event(&grant, &agent_key));
+        let mut metadata = HashMap::new();
+        metadata.insert(DELEGATION_KEY.to_string(), delegation_token(&grant).unwrap());
+        // Signed now, but dated before the delegation expired
+        let mut event = Event::new()
+            .with_event_type("docs:write")
+            .with_module_kind("docs")
+            .with_agent(public(&agent_key))
+            .with_content("hello")
+            .with_metadata(metadata)
+            .build();
+        event.created_at = now - time::Duration::minutes(2);
+        event.agent_signature = Some(sign_event(&event, &device_key));
+        let memory = Arc::new(Memory::default());
+
+        let result = service(&memory).handle_message(event).await;
+
+        assert!(matches!(result, Err(CoreError::Authorization(_))));
+        assert!(memory.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rotated_key_history_federates() {
+        let old_key = k256::ecdsa::SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let new_key = k256::ecdsa::SigningKey::from_slice(&[5u8; 32]).unwrap();
+        let public = |key: &k256::ecdsa::SigningKey| {
+            hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+        };
+        let mut rotation = rotation_event(public(&old_key), public(&new_key), None);
+        rotation.content = Some(key_proof(&rotation, &new_key));
+        rotation.agent_signature = Some(sign_event(&rotation, &old_key));
+        let write = |key: &k256::ecdsa::SigningKey, metadata: Option<HashMap<String, String>>| {
+            let mut event = Event::new()
+                .with_event_type("docs:write")
+                .with_module_kind("docs")
+                .with_agent(public(&old_key))
+                .with_content("hello")
+                .build();
+            event.created_at = rotation.created_at + time::Duration::seconds(1);
+            event.metadata = metadata;
+            event.agent_signature = Some(sign_event(&event, key));
+            event
+        };
+        let home = Arc::new(Memory::default());
+        let home_service = service(&home).with_keys(home.clone());
+
+        // A rotation only needs the key it replaces, not a session
+        let mut unsigned = rotation.clone();
+        unsigned.agent_signature = None;
+        assert!(matches!(
+            home_service.ingest(unsigned).await,
+            Err(CoreError::Authentication(_))
+        ));
+        home_service.ingest(rotation.clone()).await.unwrap();
+        home_service.handle_message(write(&new_key, None)).await.unwrap();
+        assert!(matches!(
+            home_service.handle_message(write(&old_key, None)).await,
+            Err(CoreError::Authentication(_))
+        ));
+
+        // A Synapse that never saw the rotation learns it from the event
+        let remote = Arc::new(Memory::default());
+        let remote_service = service(&remote).with_keys(remote.clone());
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            KEY_HISTORY_KEY.to_string(),
+            KeyHistory::from_events(public(&old_key), [rotation.clone()])
+                .token()
+                .unwrap()
+                .unwrap(),
+        );
+        // Carried key changes are only kept along with the event carrying them
+        let mut rejected = write(&new_key, Some(metadata.clone()));
+        rejected.content = None;
+        rejected.agent_signature = Some(sign_event(&rejected, &new_key));
+        assert!(remote_service.handle_message(rejected).await.is_err());
+        assert!(remote.key_events(&public(&old_key)).await.unwrap().is_empty());
+
+        remote_service
+            .handle_message(write(&new_key, Some(metadata)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            remote_service.handle_message(write(&old_key, None)).await,
+            Err(CoreError::Authentication(_))
+        ));
+        assert_eq!(remote.key_events(&public(&old_key)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_event_is_rejected() {
+        let memory = Arc::new(Memory::default());
+        let mut event = signed_event(Some("hello"));
+        event.expiration = Some(OffsetDateTime::now_utc() - time::Duration::seconds(1));
+
+        let result = service(&memory).ingest(event).await;
+
+        assert!(matches!(result, Err(CoreError::Validation(_))));
+        assert!(memory.events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_tombstone_is_expired_not_conflicting() {
+        let memory = Arc::new(Memory::default());
+        let mut event = signed_event(Some("hello"));
+        event.expiration = Some(OffsetDateTime::now_utc() - time::Duration::seconds(1));
+        memory.events.lock().unwrap().push(event.clone());
+        memory.prune_expired(OffsetDateTime::now_utc()).await.unwrap();
+
+        let result = service(&memory).ingest(event).await;
+
+        assert!(matches!(result, Err(CoreError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_only_author_or_moderator_may_delete() {
+        let memory = Arc::new(Memory::default());
+        let original = service(&memory).ingest(signed_event(Some("hello"))).await.unwrap();
+        let tombstone = |agent: &str| {
+            Event::new()
+                .with_event_type("docs:delete")
+                .with_module_kind("docs")
+                .with_agent(agent)
+                .with_target(ObjectRef::Event(original.id))
+                .build()
+        };
+
+        let result = service(&memory).ingest(tombstone("mallory")).await;
+        assert!(matches!(result, Err(CoreError::Authorization(_))));
+
+        let moderated = service(&memory).with_moderators(vec!["moderator".to_string()]);
+        moderated.ingest(tombstone("moderator")).await.unwrap();
+        assert_eq!(memory.events.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_event_identity_is_validated() {
+        let now = OffsetDateTime::now_utc();
+        let skewed = now + MAX_CLOCK_SKEW + time::Duration::seconds(1);
+
+        assert!(event_identity(Some(Uuid::nil()), None, now).is_err());
+        assert!(event_identity(None, Some(skewed), now).is_err());
+        assert!(event_identity(None, Some(now - MAX_CLOCK_SKEW * 2), now).is_err());
+        let id = Uuid::new_v4();
+        assert_eq!(event_identity(Some(id), Some(now), now).unwrap(), (id, now));
+    }
+}
@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use synapse_core::ports::events::event_repository::EventRepository;
+use time::OffsetDateTime;
+use tracing::{debug, warn};
+
+/// How often expired events are pruned. Queries already hide them in between.
+pub const EVENT_PRUNE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Prunes expired events to tombstones every `EVENT_PRUNE_INTERVAL`.
+///
+/// Runs forever; spawn it on the runtime.
+pub async fn prune_expired_events(repo: Arc<dyn EventRepository>) {
+    let mut interval = tokio::time::interval(EVENT_PRUNE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match repo.prune_expired(OffsetDateTime::now_utc()).await {
+            Ok(0) => {}
+            Ok(pruned) => debug!("pruned {pruned} expired events"),
+            Err(e) => warn!("failed to prune expired events: {e}"),
+        }
+    }
+}
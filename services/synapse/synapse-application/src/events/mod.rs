@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod broadcast;
 pub mod event_service;
+pub mod expiration;
+pub mod publication;
 
 use std::collections::HashMap;
 use synapse_core::{
@@ -11,8 +14,17 @@ use synapse_core::{
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+pub use synapse_core::domain::events::MAX_CLOCK_SKEW;
+
 #[derive(Clone, Debug, Default)]
 pub struct CreateEventCommand {
+    /// Id chosen by the client. Re-submitting an event under the same id is
+    /// idempotent; a new id is generated when unset.
+    pub id: Option<Uuid>,
+    /// Creation time chosen by the client, required alongside `id` for events
+    /// signed before submission. Must be within `MAX_CLOCK_SKEW` of the
+    /// Synapse clock; the current time is used when unset.
+    pub created_at: Option<OffsetDateTime>,
     pub event_type: String,
     pub agent: String,
     pub module_kind: Option<String>,
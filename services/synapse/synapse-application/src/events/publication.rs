@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::sync::Arc;
+
+use synapse_core::ports::federation::EventSubscriptions;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use crate::events::broadcast::EventBroadcast;
+
+/// Publishes every event recorded by this Synapse to the Synapses following its
+/// module. Events relayed from followed Synapses are not re-published.
+///
+/// Runs until the broadcast channel closes.
+pub async fn publish_local_events(
+    broadcast: EventBroadcast,
+    subscriptions: Arc<dyn EventSubscriptions + Send + Sync>,
+) {
+    let mut rx = broadcast.subscribe();
+    loop {
+        match rx.recv().await {
+            Ok(broadcast_event) if broadcast_event.origin.is_none() => {
+                if let Err(e) = subscriptions.publish(broadcast_event.event).await {
+                    warn!("failed to publish event: {e}");
+                }
+            }
+            Ok(_) => {}
+            Err(RecvError::Lagged(skipped)) => {
+                // Followers recover these through cursor replay
+                warn!("event publisher lagged, skipped {skipped} events");
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod auth;
 pub mod events;
 pub mod modules;
 pub mod profiles;
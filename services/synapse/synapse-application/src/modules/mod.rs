@@ -5,6 +5,7 @@ use dashmap::DashMap;
 use std::sync::Arc;
 use synapse_core::{
     CoreError,
+    domain::modules::EventTypeSpec,
     ports::modules::{Module, ModuleRegistry},
 };
 
@@ -22,7 +23,20 @@ impl InMemoryModuleRegistry {
 
 impl ModuleRegistry for InMemoryModuleRegistry {
     fn register(&self, module: Arc<dyn Module>) -> Result<(), CoreError> {
-        self.inner.insert(module.kind()?, module);
+        let kind = module.kind()?;
+        // Event types are routed by name, so two modules cannot share one
+        let declared = self.event_types();
+        if let Some(spec) = module
+            .event_types()
+            .into_iter()
+            .find(|spec| declared.iter().any(|d| d.event_type == spec.event_type))
+        {
+            return Err(CoreError::Conflict(format!(
+                "event type '{}' of module '{kind}' is already handled by another module",
+                spec.event_type
+            )));
+        }
+        self.inner.insert(kind, module);
         Ok(())
     }
     fn get(&self, k: &str) -> Option<Arc<dyn Module>> {
@@ -31,4 +45,16 @@ impl ModuleRegistry for InMemoryModuleRegistry {
     fn installed_kinds(&self) -> Vec<String> {
         self.inner.iter().map(|e| e.key().clone()).collect()
     }
+    fn event_type(&self, module_kind: &str, event_type: &str) -> Option<EventTypeSpec> {
+        self.get(module_kind)?
+            .event_types()
+            .into_iter()
+            .find(|spec| spec.event_type == event_type)
+    }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        self.inner
+            .iter()
+            .flat_map(|e| e.value().event_types())
+            .collect()
+    }
 }
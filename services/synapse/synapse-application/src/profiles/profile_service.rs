@@ -2,14 +2,23 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use adapter_libp2p::transport::Libp2pTransport;
 use async_trait::async_trait;
 use synapse_core::{
     PersistenceError, TransportError,
     domain::profiles::Profile,
-    ports::profiles::profile_repository::{ProfileDiscovery, ProfilesRepository},
+    ports::profiles::profile_repository::{ProfileDiscovery, ProfilesDocStore, ProfilesRepository},
 };
+use tracing::{info, warn};
+
+/// Delay before the first announcement after startup, giving the swarm time to
+/// reconnect to its peers.
+pub const PROFILE_REPUBLISH_DELAY: Duration = Duration::from_secs(60);
+/// How often every hosted profile is announced again. Matches the Kademlia
+/// provider publication interval so records never lapse.
+pub const PROFILE_REPUBLISH_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
 
 pub struct ProfileService {
     repo: Arc<dyn ProfilesRepository>,
@@ -51,3 +60,29 @@ impl ProfileDiscovery for ProfileDiscoveryTransport {
         Ok(peers)
     }
 }
+
+/// Periodically announces every profile held in `doc_store` so other Synapses
+/// keep finding this node as their provider, including after restarts.
+pub async fn republish_profiles(
+    doc_store: Arc<dyn ProfilesDocStore>,
+    discovery: Arc<dyn ProfileDiscovery>,
+) {
+    let start = tokio::time::Instant::now() + PROFILE_REPUBLISH_DELAY;
+    let mut interval = tokio::time::interval_at(start, PROFILE_REPUBLISH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let public_keys = match doc_store.list_public_keys().await {
+            Ok(public_keys) => public_keys,
+            Err(e) => {
+                warn!("failed to list hosted profiles: {e}");
+                continue;
+            }
+        };
+        info!("Announcing {} hosted profiles", public_keys.len());
+        for public_key in public_keys {
+            if let Err(e) = discovery.announce(&public_key).await {
+                warn!("failed to announce profile {public_key}: {e}");
+            }
+        }
+    }
+}
@@ -12,6 +12,16 @@
 //! - `PRIVATE_KEY_PATH` - Path to store/load private key
 //! - `AXUM_PORT` - HTTP API port
 //! - `LIBP2P_PORT` - P2P networking port
+//! - `LIBP2P_MAX_CONNECTIONS` - Maximum established peer connections (default 128)
+//! - `LIBP2P_IDLE_TIMEOUT_SECS` - Seconds before an idle peer connection is closed (default 300)
+//! - `LIBP2P_DHT_STORE_PATH` - File to persist Kademlia records in (default: next to the private key)
+//! - `LIBP2P_QUIC` - Also listen for QUIC on UDP `LIBP2P_PORT` (true/false, default false)
+//! - `LIBP2P_IDENTIFY` - Exchange listen addresses with peers via identify (true/false, default true)
+//! - `LIBP2P_AUTONAT` - Ask peers whether we are publicly reachable via AutoNAT (true/false, default true)
+//! - `LIBP2P_RELAY_SERVER` - Relay connections for Synapses behind NAT (true/false, default false)
+//! - `LIBP2P_RELAY_CLIENT` - Dial and accept connections through relays (true/false, default true)
+//! - `LIBP2P_RELAYS` - Comma-separated relay multiaddrs (ending in `/p2p/<peer id>`) to listen through
+//! - `LIBP2P_DCUTR` - Upgrade relayed connections to direct ones by hole punching (true/false, default true)
 //!
 //! ### Identity
 //! - `SYNAPSE_NAME` - Display name
@@ -20,6 +30,8 @@
 //! - `SYNAPSE_AVATAR_URL` - Avatar image URL
 //! - `SYNAPSE_BANNER_URL` - Banner image URL
 //! - `SYNAPSE_TAGS` - Comma-separated tags
+//! - `SYNAPSE_MODERATORS` - Comma-separated public keys of agents allowed to edit and delete any event
+//!   and to follow remote Synapses
 //!
 //! ### Theme
 //! - `SYNAPSE_THEME_PRESET` - Theme preset (default, midnight, etc.)
@@ -78,6 +90,10 @@ pub struct SynapseConfig {
     pub identity: IdentityConfig,
     pub p2p: P2pConfig,
     pub api: ApiConfig,
+    /// Agents allowed to edit and delete other agents' events, and to follow
+    /// and unfollow remote Synapses.
+    #[serde(default)]
+    pub moderators: Vec<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -96,6 +112,53 @@ pub struct P2pConfig {
     pub listen_addrs: Multiaddr,
     pub announce: Vec<String>,
     pub bootstrap: Vec<String>,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default = "default_idle_connection_timeout_secs")]
+    pub idle_connection_timeout_secs: u64,
+    #[serde(default)]
+    pub dht_store_path: Option<PathBuf>,
+    #[serde(default)]
+    pub enable_quic: bool,
+    #[serde(default = "default_enable_identify")]
+    pub enable_identify: bool,
+    #[serde(default = "default_enable_autonat")]
+    pub enable_autonat: bool,
+    #[serde(default)]
+    pub enable_relay_server: bool,
+    #[serde(default = "default_enable_relay_client")]
+    pub enable_relay_client: bool,
+    #[serde(default)]
+    pub relays: Vec<String>,
+    #[serde(default = "default_enable_dcutr")]
+    pub enable_dcutr: bool,
+}
+
+pub const DEFAULT_MAX_CONNECTIONS: u32 = 128;
+pub const DEFAULT_IDLE_CONNECTION_TIMEOUT_SECS: u64 = 300;
+
+fn default_max_connections() -> u32 {
+    DEFAULT_MAX_CONNECTIONS
+}
+
+fn default_idle_connection_timeout_secs() -> u64 {
+    DEFAULT_IDLE_CONNECTION_TIMEOUT_SECS
+}
+
+fn default_enable_identify() -> bool {
+    true
+}
+
+fn default_enable_autonat() -> bool {
+    true
+}
+
+fn default_enable_relay_client() -> bool {
+    true
+}
+
+fn default_enable_dcutr() -> bool {
+    true
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -174,6 +237,10 @@ fn build_synapse_config(keypair: &Keypair, private_key_path: PathBuf) -> Result<
         .parse()
         .expect("Invalid listen Multiaddr");
 
+    let dht_store_path = env_var_opt("LIBP2P_DHT_STORE_PATH")
+        .map(PathBuf::from)
+        .or_else(|| private_key_path.parent().map(|dir| dir.join("dht_records.json")));
+
     let public_url_str = env_var_or("SYNAPSE_PUBLIC_URL", "http://localhost");
     let public_url = Url::parse(&public_url_str)?;
 
@@ -190,8 +257,25 @@ fn build_synapse_config(keypair: &Keypair, private_key_path: PathBuf) -> Result<
             listen_addrs: listen_addr,
             announce: env_var_list("ANNOUNCE"),
             bootstrap: env_var_list("BOOTSTRAP_LIST"),
+            max_connections: env_var_opt("LIBP2P_MAX_CONNECTIONS")
+                .map(|s| s.parse())
+                .transpose()?
+                .unwrap_or(DEFAULT_MAX_CONNECTIONS),
+            idle_connection_timeout_secs: env_var_opt("LIBP2P_IDLE_TIMEOUT_SECS")
+                .map(|s| s.parse())
+                .transpose()?
+                .unwrap_or(DEFAULT_IDLE_CONNECTION_TIMEOUT_SECS),
+            dht_store_path,
+            enable_quic: env_var_bool("LIBP2P_QUIC", false),
+            enable_identify: env_var_bool("LIBP2P_IDENTIFY", true),
+            enable_autonat: env_var_bool("LIBP2P_AUTONAT", true),
+            enable_relay_server: env_var_bool("LIBP2P_RELAY_SERVER", false),
+            enable_relay_client: env_var_bool("LIBP2P_RELAY_CLIENT", true),
+            relays: env_var_list("LIBP2P_RELAYS"),
+            enable_dcutr: env_var_bool("LIBP2P_DCUTR", true),
         },
         api: ApiConfig { port },
+        moderators: env_var_list("SYNAPSE_MODERATORS"),
     })
 }
 
@@ -2,9 +2,12 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 use serde::{Deserialize, Serialize};
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 
+/// How long a session lasts after login or its latest refresh.
+pub const SESSION_TTL: Duration = Duration::hours(4);
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Session {
     pub id: Uuid,
@@ -12,4 +15,29 @@ pub struct Session {
     pub created_at: OffsetDateTime,
     pub expires_at: OffsetDateTime,
     pub revoked: bool,
+    /// `User-Agent` of the client that logged in, telling devices apart.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Time of the login or latest refresh.
+    pub last_seen_at: OffsetDateTime,
+}
+
+impl Session {
+    /// A session of `agent` starting at `now` and lasting `SESSION_TTL`.
+    pub fn new(agent: impl Into<String>, user_agent: Option<String>, now: OffsetDateTime) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            agent: agent.into(),
+            created_at: now,
+            expires_at: now + SESSION_TTL,
+            revoked: false,
+            user_agent,
+            last_seen_at: now,
+        }
+    }
+
+    /// Whether the session still authenticates its agent at `now`.
+    pub fn is_active(&self, now: OffsetDateTime) -> bool {
+        !self.revoked && now < self.expires_at
+    }
 }
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Canonical event signing format.
+//!
+//! Agents sign events in the browser (WASM) and Synapses verify them on the
+//! server, so both sides must produce byte-identical input for the same event.
+//! The encoding is compact JSON with a fixed field order and:
+//! - a `format` tag so the encoding can evolve without breaking old signatures,
+//! - timestamps as integer microseconds since the Unix epoch in UTC (the
+//!   precision Postgres keeps, so stored events still verify),
+//! - `metadata` sorted by key,
+//! - `data` as the hex SHA-256 digest of its bytes, since it can be large.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::domain::events::{Event, ObjectRef};
+
+/// Tag identifying the canonical signing format.
+pub const SIGNING_FORMAT: &str = "menexus.event.v2";
+
+#[derive(Serialize)]
+struct CanonicalEvent<'a> {
+    format: &'static str,
+    id: Uuid,
+    created_at: i64,
+    event_type: &'a str,
+    module_kind: Option<&'a str>,
+    module_slug: Option<&'a str>,
+    agent: &'a str,
+    target: Option<&'a ObjectRef>,
+    previous: Option<Uuid>,
+    content: Option<&'a str>,
+    artifacts: Option<&'a [String]>,
+    metadata: Option<BTreeMap<&'a str, &'a str>>,
+    links: Option<&'a [String]>,
+    data_sha256: Option<String>,
+    expiration: Option<i64>,
+}
+
+/// Returns the canonical bytes an agent signs for `event`.
+pub fn event_signing_bytes(event: &Event) -> Vec<u8> {
+    let payload = CanonicalEvent {
+        format: SIGNING_FORMAT,
+        id: event.id,
+        created_at: micros(event.created_at),
+        event_type: &event.event_type,
+        module_kind: event.module_kind.as_deref(),
+        module_slug: event.module_slug.as_deref(),
+        agent: &event.agent,
+        target: event.target.as_ref(),
+        previous: event.previous,
+        content: event.content.as_deref(),
+        artifacts: event.artifacts.as_deref(),
+        metadata: sorted_metadata(event),
+        links: event.links.as_deref(),
+        data_sha256: event
+            .data
+            .as_ref()
+            .map(|data| hex::encode(Sha256::digest(data))),
+        expiration: event.expiration.map(micros),
+    };
+    serde_json::to_vec(&payload).unwrap_or_default()
+}
+
+fn micros(t: OffsetDateTime) -> i64 {
+    (t.unix_timestamp_nanos() / 1_000) as i64
+}
+
+fn sorted_metadata(event: &Event) -> Option<BTreeMap<&str, &str>> {
+    event.metadata.as_ref().map(|m| {
+        m.iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use time::{OffsetDateTime, UtcOffset};
+
+    fn fixed_event() -> Event {
+        let mut event = Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_module_slug("general")
+            .with_agent("02abcdef")
+            .with_content("hello")
+            .build();
+        event.id = Uuid::nil();
+        event.created_at = OffsetDateTime::from_unix_timestamp_nanos(1_736_380_800_123_456_789).unwrap();
+        event
+    }
+
+    #[test]
+    fn test_canonical_bytes() {
+        let mut event = fixed_event();
+        event.data = Some(b"{}".to_vec());
+        event.expiration = Some(event.created_at + time::Duration::hours(1));
+
+        assert_eq!(
+            String::from_utf8(event_signing_bytes(&event)).unwrap(),
+            r#"{"format":"menexus.event.v2","id":"00000000-0000-0000-0000-000000000000","created_at":1736380800123456,"event_type":"posts:create_post","module_kind":"posts","module_slug":"general","agent":"02abcdef","target":null,"previous":null,"content":"hello","artifacts":null,"metadata":null,"links":null,"data_sha256":"44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a","expiration":1736384400123456}"#
+        );
+    }
+
+    #[test]
+    fn test_all_fields_are_covered() {
+        let base = fixed_event();
+        let mut data = fixed_event();
+        data.data = Some(vec![1]);
+        let mut expiration = fixed_event();
+        expiration.expiration = Some(base.created_at);
+        let mut artifacts = fixed_event();
+        artifacts.artifacts = Some(vec!["cid".to_string()]);
+        let mut links = fixed_event();
+        links.links = Some(vec!["https://example.com".to_string()]);
+
+        for changed in [data, expiration, artifacts, links] {
+            assert_ne!(event_signing_bytes(&base), event_signing_bytes(&changed));
+        }
+    }
+
+    #[test]
+    fn test_metadata_order_is_irrelevant() {
+        let mut a = fixed_event();
+        let mut b = fixed_event();
+        let mut forward = HashMap::new();
+        let mut backward = HashMap::new();
+        for i in 0..32 {
+            forward.insert(format!("k{i}"), i.to_string());
+        }
+        for i in (0..32).rev() {
+            backward.insert(format!("k{i}"), i.to_string());
+        }
+        a.metadata = Some(forward);
+        b.metadata = Some(backward);
+
+        assert_eq!(event_signing_bytes(&a), event_signing_bytes(&b));
+    }
+
+    #[test]
+    fn test_timestamp_offset_and_sub_micros_are_irrelevant() {
+        let a = fixed_event();
+        let mut b = fixed_event();
+        b.created_at = a
+            .created_at
+            .to_offset(UtcOffset::from_hms(2, 0, 0).unwrap())
+            .replace_nanosecond(123_456_000)
+            .unwrap();
+
+        assert_eq!(event_signing_bytes(&a), event_signing_bytes(&b));
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod canonical;
 pub mod signature;
 
 use std::time::Duration;
@@ -39,6 +40,11 @@ impl CryptoChallenge {
     pub fn new() -> CryptoChallengeBuilder {
         Self::builder()
     }
+
+    /// Whether the challenge can no longer be answered at `now`.
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        now >= self.expires_at
+    }
 }
 
 impl CryptoChallengeBuilder {
@@ -8,7 +8,12 @@
 //! federation where remote Synapses can verify user identity without
 //! maintaining session state.
 
+use crate::CoreError;
 use crate::domain::events::Event;
+#[cfg(feature = "crypto")]
+use crate::domain::events::delegations::{Delegation, DelegationChange, delegation_grant};
+#[cfg(feature = "crypto")]
+use crate::domain::events::keys::{KeyChange, KeyHistory, carried_key_changes};
 
 /// Result of signature verification
 #[derive(Debug, Clone, PartialEq)]
@@ -38,6 +43,13 @@ impl SignatureVerificationResult {
 /// Verify that an event's signature is valid for the claimed agent.
 ///
 /// Uses k256/secp256k1 ECDSA with SHA-256 hashing, matching the auth module.
+/// An event carrying a delegation token (see `domain::events::delegations`)
+/// must instead be signed by the delegate, under a grant signed by the agent
+/// that covers the event. Whether the grant was revoked is up to the caller.
+///
+/// The agent's key history is taken from the key changes the event carries
+/// (see `domain::events::keys`); use `verify_event_signature_with` to check
+/// against a history known otherwise.
 ///
 /// # Arguments
 /// * `event` - The event to verify
@@ -46,9 +58,20 @@ impl SignatureVerificationResult {
 /// * `SignatureVerificationResult` indicating whether signature is valid, missing, or invalid
 #[cfg(feature = "crypto")]
 pub fn verify_event_signature(event: &Event) -> SignatureVerificationResult {
-    use k256::ecdsa::{Signature, VerifyingKey, signature::DigestVerifier};
-    use sha2::{Digest, Sha256};
+    match carried_key_changes(event) {
+        Ok(changes) => verify_event_signature_with(event, &KeyHistory::from_events(event.agent.clone(), changes)),
+        Err(e) => SignatureVerificationResult::Invalid(e.to_string()),
+    }
+}
 
+/// Verify that an event's signature is valid for the claimed agent, given the
+/// agent's key `history`.
+///
+/// The event must be signed with the key in force when it was created. A key
+/// change must be part of `history`, and signed by the key that endorsed it
+/// there.
+#[cfg(feature = "crypto")]
+pub fn verify_event_signature_with(event: &Event, history: &KeyHistory) -> SignatureVerificationResult {
     // If no signature, return Unsigned
     let signature_hex = match &event.agent_signature {
         Some(sig) => sig,
@@ -60,8 +83,70 @@ pub fn verify_event_signature(event: &Event) -> SignatureVerificationResult {
         return SignatureVerificationResult::Invalid("agent public key is empty".to_string());
     }
 
-    // Decode the agent's public key (hex-encoded SEC1 compressed point)
-    let pk_bytes = match hex::decode(&event.agent) {
+    if *history.agent() != event.agent {
+        return SignatureVerificationResult::Invalid(format!(
+            "key history of {} does not apply to {}",
+            history.agent(),
+            event.agent
+        ));
+    }
+
+    let signer = match verify_delegation(event, history) {
+        Ok(Some(delegation)) => delegation.delegate,
+        Ok(None) => match KeyChange::of(event) {
+            Ok(None) => history.key_at(event.created_at).clone(),
+            Ok(Some(_)) => match history.endorser_of(event.id) {
+                Some(endorser) => endorser.clone(),
+                None => {
+                    return SignatureVerificationResult::Invalid(
+                        "key change is not endorsed by a key of the agent".to_string(),
+                    );
+                }
+            },
+            Err(e) => return SignatureVerificationResult::Invalid(e.to_string()),
+        },
+        Err(reason) => return SignatureVerificationResult::Invalid(reason),
+    };
+    verify_signature(&signer, &event.signing_payload(), signature_hex)
+}
+
+/// The delegation `event` is signed under, if it carries one, once the grant
+/// is checked to be signed by the agent and to cover the event.
+#[cfg(feature = "crypto")]
+fn verify_delegation(event: &Event, history: &KeyHistory) -> Result<Option<Delegation>, String> {
+    let Some(grant) = delegation_grant(event).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    if delegation_grant(&grant).map_err(|e| e.to_string())?.is_some() {
+        return Err("delegations cannot be delegated".to_string());
+    }
+    match verify_event_signature_with(&grant, history) {
+        SignatureVerificationResult::Valid => {}
+        SignatureVerificationResult::Unsigned => return Err("delegation is unsigned".to_string()),
+        SignatureVerificationResult::Invalid(reason) => {
+            return Err(format!("invalid delegation: {reason}"));
+        }
+    }
+    let Ok(Some(DelegationChange::Grant(delegation))) = DelegationChange::of(&grant) else {
+        return Err("delegation token does not grant a delegation".to_string());
+    };
+    delegation.authorize(event).map_err(|e| e.to_string())?;
+    Ok(Some(delegation))
+}
+
+/// Verify a hex-encoded signature of `payload` by the hex-encoded SEC1 public
+/// key `signer`.
+#[cfg(feature = "crypto")]
+pub(crate) fn verify_signature(
+    signer: &str,
+    payload: &[u8],
+    signature_hex: &str,
+) -> SignatureVerificationResult {
+    use k256::ecdsa::{Signature, VerifyingKey, signature::DigestVerifier};
+    use sha2::{Digest, Sha256};
+
+    // Decode the signer's public key (hex-encoded SEC1 compressed point)
+    let pk_bytes = match hex::decode(signer) {
         Ok(bytes) => bytes,
         Err(e) => return SignatureVerificationResult::Invalid(format!("invalid public key hex: {}", e)),
     };
@@ -82,9 +167,7 @@ pub fn verify_event_signature(event: &Event) -> SignatureVerificationResult {
         Err(e) => return SignatureVerificationResult::Invalid(format!("invalid signature format: {}", e)),
     };
 
-    // Get the canonical signing payload
-    let payload = event.signing_payload();
-    let digest = Sha256::new_with_prefix(&payload);
+    let digest = Sha256::new_with_prefix(payload);
 
     // Verify the signature
     match verifying_key.verify_digest(digest, &signature) {
@@ -93,57 +176,97 @@ pub fn verify_event_signature(event: &Event) -> SignatureVerificationResult {
     }
 }
 
+/// Sign an event as its agent, or as a delegate once it carries the
+/// delegation token, returning the hex-encoded signature.
+///
+/// This is the counterpart of `verify_event_signature`: the same canonical
+/// payload and digest are used on the client (WASM) and on the Synapse.
+#[cfg(feature = "crypto")]
+pub fn sign_event(event: &Event, signing_key: &k256::ecdsa::SigningKey) -> String {
+    sign_payload(&event.signing_payload(), signing_key)
+}
+
+/// Sign the SHA-256 digest of `payload`, returning the hex-encoded signature.
+#[cfg(feature = "crypto")]
+pub(crate) fn sign_payload(payload: &[u8], signing_key: &k256::ecdsa::SigningKey) -> String {
+    use k256::ecdsa::{Signature, signature::DigestSigner};
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::new_with_prefix(payload);
+    let signature: Signature = signing_key.sign_digest(digest);
+    hex::encode(signature.to_bytes())
+}
+
 /// Non-crypto fallback - always returns Unsigned
 #[cfg(not(feature = "crypto"))]
 pub fn verify_event_signature(_event: &Event) -> SignatureVerificationResult {
     SignatureVerificationResult::Unsigned
 }
 
-/// Check if an event requires authentication (signature) based on event type.
+/// Check if an event type requires an agent signature by naming convention.
 ///
-/// Read operations typically don't require signatures, while write operations do.
-/// 
-/// NOTE: For federated (Synapse-to-Synapse) requests, the receiving Synapse trusts
-/// the sending Synapse's assertion of the user's identity. This is the same model
-/// used by ActivityPub. Full end-to-end user signatures can be added later.
-pub fn requires_authentication(_event_type: &str) -> bool {
-    // For now, we rely on Synapse-to-Synapse trust for federation
-    // The sending Synapse has already authenticated the user via session
-    // and includes their public key in the event's agent field.
-    // 
-    // In the future, we can require user signatures for sensitive operations,
-    // but for MVP we accept federation trust.
-    // 
-    // Explicitly mark operations that need signatures:
-    false // Disabled for MVP - using Synapse-to-Synapse trust model
+/// Reads (`<module>:list_*`, `<module>:get_*`) and Synapse system events
+/// (`synapse:*`, already authenticated by the SNP envelope) are exempt; every
+/// other event type is treated as a write. Ingest goes by what modules declare
+/// in `Module::event_types`; this is the fallback for callers without a
+/// module registry.
+pub fn requires_authentication(event_type: &str) -> bool {
+    let Some((namespace, action)) = event_type.split_once(':') else {
+        return true;
+    };
+    if namespace == "synapse" {
+        return false;
+    }
+    !(action.starts_with("list_") || action.starts_with("get_"))
 }
 
-/// Verify an event has proper authentication if required.
+/// Verify an event carries a valid agent signature.
 ///
-/// Returns Ok(()) if event is properly authenticated or doesn't require auth.
-/// Returns Err with message if authentication is required but missing/invalid.
+/// Returns `CoreError::Authentication` with the reason if the signature is
+/// missing or does not verify against `event.agent`.
 #[cfg(feature = "crypto")]
-pub fn verify_event_authentication(event: &Event) -> Result<(), String> {
-    if !requires_authentication(&event.event_type) {
-        return Ok(());
-    }
+pub fn require_event_signature(event: &Event) -> Result<(), CoreError> {
+    signature_required(event, verify_event_signature(event))
+}
+
+/// Verify an event carries a valid agent signature, given the agent's key
+/// `history`.
+#[cfg(feature = "crypto")]
+pub fn require_event_signature_with(event: &Event, history: &KeyHistory) -> Result<(), CoreError> {
+    signature_required(event, verify_event_signature_with(event, history))
+}
 
-    match verify_event_signature(event) {
+#[cfg(feature = "crypto")]
+fn signature_required(event: &Event, result: SignatureVerificationResult) -> Result<(), CoreError> {
+    match result {
         SignatureVerificationResult::Valid => Ok(()),
-        SignatureVerificationResult::Unsigned => {
-            Err(format!("event type '{}' requires authentication but event is unsigned", event.event_type))
-        }
-        SignatureVerificationResult::Invalid(reason) => {
-            Err(format!("event signature verification failed: {}", reason))
-        }
+        SignatureVerificationResult::Unsigned => Err(CoreError::Authentication(format!(
+            "event type '{}' requires authentication but event is unsigned",
+            event.event_type
+        ))),
+        SignatureVerificationResult::Invalid(reason) => Err(CoreError::Authentication(format!(
+            "event signature verification failed: {}",
+            reason
+        ))),
     }
 }
 
 #[cfg(not(feature = "crypto"))]
-pub fn verify_event_authentication(_event: &Event) -> Result<(), String> {
-    // Without crypto feature, we can't verify - accept all for now
-    // In production, the server should have crypto feature enabled
-    Ok(())
+pub fn require_event_signature(_event: &Event) -> Result<(), CoreError> {
+    Err(CoreError::Authentication(
+        "signature verification is unavailable without the crypto feature".to_string(),
+    ))
+}
+
+/// Verify an event has proper authentication if required.
+///
+/// Returns Ok(()) if event is properly authenticated or doesn't require auth.
+/// Returns `CoreError::Authentication` if authentication is required but missing/invalid.
+pub fn verify_event_authentication(event: &Event) -> Result<(), CoreError> {
+    if !requires_authentication(&event.event_type) {
+        return Ok(());
+    }
+    require_event_signature(event)
 }
 
 #[cfg(all(test, feature = "crypto"))]
@@ -167,4 +290,157 @@ mod tests {
         assert!(!requires_authentication("posts:list_posts"));
         assert!(!requires_authentication("posts:get_config"));
     }
+
+    #[test]
+    fn test_rejects_unsigned_write() {
+        let event = Event::new()
+            .with_event_type("posts:create_post")
+            .with_agent("test_agent")
+            .build();
+
+        assert!(matches!(
+            verify_event_authentication(&event),
+            Err(CoreError::Authentication(_))
+        ));
+    }
+
+    #[test]
+    fn test_accepts_signed_write() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut event = Event::new()
+            .with_event_type("posts:create_post")
+            .with_agent(agent)
+            .with_content("hello")
+            .build();
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+
+        assert!(verify_event_authentication(&event).is_ok());
+
+        event.content = Some("tampered".to_string());
+        assert!(verify_event_authentication(&event).is_err());
+    }
+
+    #[test]
+    fn test_rejects_swapped_data_and_expiration() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[8u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut event = Event::new()
+            .with_event_type("profiles:set_profile")
+            .with_agent(agent)
+            .with_data(br#"{"display_name":"alice"}"#.to_vec())
+            .with_expiration(time::OffsetDateTime::now_utc() + time::Duration::hours(1))
+            .build();
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+        assert!(verify_event_signature(&event).is_valid());
+
+        let mut swapped = event.clone();
+        swapped.data = Some(br#"{"display_name":"mallory"}"#.to_vec());
+        assert!(verify_event_signature(&swapped).is_invalid());
+
+        let mut extended = event.clone();
+        extended.expiration = None;
+        assert!(verify_event_signature(&extended).is_invalid());
+    }
+
+    #[test]
+    fn test_signature_survives_wire_round_trip() {
+        let signing_key = k256::ecdsa::SigningKey::from_slice(&[9u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("channel".to_string(), "general".to_string());
+        metadata.insert("lang".to_string(), "en".to_string());
+        let mut event = Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_agent(agent)
+            .with_metadata(metadata)
+            .with_target(crate::domain::events::ObjectRef::Event(uuid::Uuid::new_v4()))
+            .build();
+        event.agent_signature = Some(sign_event(&event, &signing_key));
+
+        // Client -> Synapse: the event is re-encoded and decoded before verification
+        let received: Event = serde_json::from_slice(&serde_json::to_vec(&event).unwrap()).unwrap();
+
+        assert!(verify_event_signature(&received).is_valid());
+    }
+
+    #[test]
+    fn test_accepts_event_signed_by_delegate() {
+        use crate::domain::events::delegations::{
+            DELEGATION_KEY, DelegationScope, delegation_event, delegation_token,
+        };
+
+        let key = |seed: u8| k256::ecdsa::SigningKey::from_slice(&[seed; 32]).unwrap();
+        let public = |key: &k256::ecdsa::SigningKey| {
+            hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+        };
+        let (agent_key, device_key) = (key(11), key(12));
+        let scope = DelegationScope {
+            name: "Bot".to_string(),
+            module_kinds: vec!["posts".to_string()],
+            ..Default::default()
+        };
+        let mut grant = delegation_event(public(&agent_key), public(&device_key), &scope).unwrap();
+        grant.agent_signature = Some(sign_event(&grant, &agent_key));
+        let delegated = |grant: &Event, module_kind: &str| {
+            let mut metadata = std::collections::HashMap::new();
+            metadata.insert(DELEGATION_KEY.to_string(), delegation_token(grant).unwrap());
+            let mut event = Event::new()
+                .with_event_type("posts:create_post")
+                .with_module_kind(module_kind)
+                .with_agent(public(&agent_key))
+                .with_metadata(metadata)
+                .build();
+            event.agent_signature = Some(sign_event(&event, &device_key));
+            event
+        };
+
+        assert!(verify_event_signature(&delegated(&grant, "posts")).is_valid());
+        assert!(verify_event_signature(&delegated(&grant, "reactions")).is_invalid());
+
+        // A grant not signed by the agent does not delegate
+        let mut forged = grant.clone();
+        forged.agent_signature = Some(sign_event(&forged, &device_key));
+        assert!(verify_event_signature(&delegated(&forged, "posts")).is_invalid());
+    }
+
+    #[test]
+    fn test_rotated_key_signs_only_what_follows_the_rotation() {
+        use crate::domain::events::keys::{KEY_HISTORY_KEY, key_proof, rotation_event};
+        use time::Duration;
+
+        let key = |seed: u8| k256::ecdsa::SigningKey::from_slice(&[seed; 32]).unwrap();
+        let public = |key: &k256::ecdsa::SigningKey| {
+            hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+        };
+        let (old_key, new_key) = (key(13), key(14));
+        let mut rotation = rotation_event(public(&old_key), public(&new_key), None);
+        rotation.content = Some(key_proof(&rotation, &new_key));
+        rotation.agent_signature = Some(sign_event(&rotation, &old_key));
+        let history = KeyHistory::from_events(public(&old_key), [rotation.clone()]);
+        let post = |key: &k256::ecdsa::SigningKey, offset: Duration, carried: bool| {
+            let mut event = Event::new()
+                .with_event_type("posts:create_post")
+                .with_module_kind("posts")
+                .with_agent(public(&old_key))
+                .build();
+            event.created_at = rotation.created_at + offset;
+            if carried {
+                let mut metadata = std::collections::HashMap::new();
+                metadata.insert(KEY_HISTORY_KEY.to_string(), history.token().unwrap().unwrap());
+                event.metadata = Some(metadata);
+            }
+            event.agent_signature = Some(sign_event(&event, key));
+            event
+        };
+
+        assert!(verify_event_signature(&rotation).is_valid());
+        assert!(verify_event_signature(&post(&new_key, Duration::seconds(1), true)).is_valid());
+        assert!(verify_event_signature_with(&post(&new_key, Duration::seconds(1), false), &history).is_valid());
+        assert!(verify_event_signature_with(&post(&old_key, -Duration::seconds(1), false), &history).is_valid());
+        assert!(verify_event_signature_with(&post(&old_key, Duration::seconds(1), false), &history).is_invalid());
+        // Without the history, the new key is a stranger
+        assert!(verify_event_signature(&post(&new_key, Duration::seconds(1), false)).is_invalid());
+    }
 }
@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Keys acting for an agent.
+//!
+//! An agent lets a device or app key sign events on its behalf by appending an
+//! `auth:delegate` event, signed with its own key, that targets the delegate
+//! through `ObjectRef::Agent` and carries the `DelegationScope` as its content.
+//! The grant is taken back with an `auth:revoke_delegation` event targeting it
+//! through `ObjectRef::Event`.
+//!
+//! An event signed by a delegate keeps the agent as `Event::agent` and carries
+//! the signed grant under the `DELEGATION_KEY` metadata key. The grant works as
+//! a capability token: any Synapse can check it against the agent's key without
+//! having seen it before. Revocations are checked by the Synapses they reach.
+//!
+//! Delegates never sign `auth:*` events, so they cannot delegate further or
+//! revoke grants.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::CoreError;
+use crate::domain::events::{Event, MAX_CLOCK_SKEW, ObjectRef, PublicKey};
+
+pub const DELEGATE: &str = "auth:delegate";
+pub const REVOKE_DELEGATION: &str = "auth:revoke_delegation";
+
+/// Metadata key of an event signed by a delegate, holding the `auth:delegate`
+/// event that authorizes it as JSON.
+pub const DELEGATION_KEY: &str = "delegation";
+
+/// Longest delegation name accepted, in bytes.
+pub const MAX_DELEGATION_NAME_LEN: usize = 64;
+
+const AUTH_MODULE: &str = "auth";
+
+/// What a delegate may sign. Empty lists leave that dimension unrestricted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DelegationScope {
+    /// Name of the device or app, shown to the agent.
+    pub name: String,
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    #[serde(default)]
+    pub module_kinds: Vec<String>,
+    /// Events created at or after this time are not covered.
+    #[serde(default)]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl DelegationScope {
+    /// The content of the `auth:delegate` event granting this scope.
+    pub fn to_content(&self) -> Result<String, CoreError> {
+        serde_json::to_string(self).map_err(|e| CoreError::Other(e.to_string()))
+    }
+
+    fn validate(&self) -> Result<(), CoreError> {
+        if self.name.trim().is_empty() || self.name.len() > MAX_DELEGATION_NAME_LEN {
+            return Err(CoreError::Validation(format!(
+                "delegation name must be 1 to {MAX_DELEGATION_NAME_LEN} bytes"
+            )));
+        }
+        let manages_auth = self.module_kinds.iter().any(|kind| kind == AUTH_MODULE)
+            || self.event_types.iter().any(|t| is_auth_event_type(t));
+        if manages_auth {
+            return Err(CoreError::Validation(
+                "delegates cannot be granted auth events".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A key allowed to sign events for an agent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Delegation {
+    /// Id of the `auth:delegate` event granting it.
+    pub id: Uuid,
+    pub agent: PublicKey,
+    pub delegate: PublicKey,
+    pub scope: DelegationScope,
+    pub created_at: OffsetDateTime,
+}
+
+impl Delegation {
+    /// Checks that the delegate may sign `event` for the agent.
+    pub fn authorize(&self, event: &Event) -> Result<(), CoreError> {
+        let denied = |reason: String| Err(CoreError::Authorization(reason));
+        if event.agent != self.agent {
+            return denied(format!("delegation {} is not granted by {}", self.id, event.agent));
+        }
+        if is_auth_event_type(&event.event_type) || event.module_kind.as_deref() == Some(AUTH_MODULE)
+        {
+            return denied(format!("delegates may not sign {}", event.event_type));
+        }
+        let scope = &self.scope;
+        if !scope.event_types.is_empty() && !scope.event_types.contains(&event.event_type) {
+            return denied(format!("delegation {} does not cover {}", self.id, event.event_type));
+        }
+        let kind = event.module_kind.as_deref().unwrap_or_default();
+        if !scope.module_kinds.is_empty() && !scope.module_kinds.iter().any(|k| k == kind) {
+            return denied(format!("delegation {} does not cover module {kind}", self.id));
+        }
+        if scope.expires_at.is_some_and(|expires_at| event.created_at >= expires_at) {
+            return denied(format!("delegation {} has expired", self.id));
+        }
+        Ok(())
+    }
+
+    /// Checks that the delegation is still in force when `event` reaches a
+    /// Synapse at `now`. The delegate picks `created_at`, so it must be within
+    /// `MAX_CLOCK_SKEW` of `now` and cannot backdate an event into the scope.
+    pub fn check_in_force(&self, event: &Event, now: OffsetDateTime) -> Result<(), CoreError> {
+        if (event.created_at - now).abs() > MAX_CLOCK_SKEW {
+            return Err(CoreError::Authorization(format!(
+                "event {} signed under delegation {} is more than {MAX_CLOCK_SKEW} from the Synapse clock",
+                event.id, self.id
+            )));
+        }
+        if self.scope.expires_at.is_some_and(|expires_at| now >= expires_at) {
+            return Err(CoreError::Authorization(format!(
+                "delegation {} has expired",
+                self.id
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// An agent taking back a delegation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelegationRevocation {
+    /// The `auth:delegate` event revoked.
+    pub delegation: Uuid,
+    pub agent: PublicKey,
+    /// The `auth:revoke_delegation` event making the change.
+    pub event_id: Uuid,
+    pub at: OffsetDateTime,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DelegationChange {
+    Grant(Delegation),
+    Revoke(DelegationRevocation),
+}
+
+impl DelegationChange {
+    /// The change `event` makes, if it is a delegation event. Fails if it
+    /// does not target the delegate or grant, or carries an invalid scope.
+    pub fn of(event: &Event) -> Result<Option<Self>, CoreError> {
+        match event.event_type.as_str() {
+            DELEGATE => {
+                let Some(ObjectRef::Agent(delegate)) = &event.target else {
+                    return Err(CoreError::Validation(format!(
+                        "{DELEGATE} must target the delegate key"
+                    )));
+                };
+                if delegate.trim().is_empty() || *delegate == event.agent {
+                    return Err(CoreError::Validation(
+                        "an agent cannot delegate to itself".to_string(),
+                    ));
+                }
+                let content = event.content.as_deref().unwrap_or_default();
+                let scope: DelegationScope = serde_json::from_str(content).map_err(|e| {
+                    CoreError::Validation(format!("invalid delegation scope: {e}"))
+                })?;
+                scope.validate()?;
+                Ok(Some(DelegationChange::Grant(Delegation {
+                    id: event.id,
+                    agent: event.agent.clone(),
+                    delegate: delegate.clone(),
+                    scope,
+                    created_at: event.created_at,
+                })))
+            }
+            REVOKE_DELEGATION => {
+                let Some(ObjectRef::Event(delegation)) = event.target else {
+                    return Err(CoreError::Validation(format!(
+                        "{REVOKE_DELEGATION} must target the delegation it revokes"
+                    )));
+                };
+                Ok(Some(DelegationChange::Revoke(DelegationRevocation {
+                    delegation,
+                    agent: event.agent.clone(),
+                    event_id: event.id,
+                    at: event.created_at,
+                })))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The `auth:delegate` event granting `scope` to `delegate`, to be signed by
+/// `agent`.
+pub fn delegation_event(
+    agent: impl Into<PublicKey>,
+    delegate: impl Into<PublicKey>,
+    scope: &DelegationScope,
+) -> Result<Event, CoreError> {
+    Ok(Event::new()
+        .with_event_type(DELEGATE)
+        .with_module_kind(AUTH_MODULE)
+        .with_agent(agent)
+        .with_target(ObjectRef::Agent(delegate.into()))
+        .with_content(scope.to_content()?)
+        .build())
+}
+
+/// The `auth:revoke_delegation` event taking back `delegation`, to be signed
+/// by `agent`.
+pub fn revocation_event(agent: impl Into<PublicKey>, delegation: Uuid) -> Event {
+    Event::new()
+        .with_event_type(REVOKE_DELEGATION)
+        .with_module_kind(AUTH_MODULE)
+        .with_agent(agent)
+        .with_target(ObjectRef::Event(delegation))
+        .build()
+}
+
+/// The value a delegate puts under `DELEGATION_KEY` in the events it signs.
+pub fn delegation_token(grant: &Event) -> Result<String, CoreError> {
+    serde_json::to_string(grant).map_err(|e| CoreError::Other(e.to_string()))
+}
+
+/// The `auth:delegate` event carried by `event`, if it is signed by a
+/// delegate.
+pub fn delegation_grant(event: &Event) -> Result<Option<Event>, CoreError> {
+    let Some(token) = event.metadata.as_ref().and_then(|m| m.get(DELEGATION_KEY)) else {
+        return Ok(None);
+    };
+    let grant: Event = serde_json::from_str(token)
+        .map_err(|e| CoreError::Validation(format!("invalid delegation token: {e}")))?;
+    if grant.event_type != DELEGATE {
+        return Err(CoreError::Validation(format!(
+            "delegation token must be a {DELEGATE} event"
+        )));
+    }
+    Ok(Some(grant))
+}
+
+fn is_auth_event_type(event_type: &str) -> bool {
+    event_type
+        .split_once(':')
+        .is_some_and(|(namespace, _)| namespace == AUTH_MODULE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant(scope: DelegationScope) -> Delegation {
+        let event = delegation_event("02agent", "02device", &scope).unwrap();
+        match DelegationChange::of(&event).unwrap() {
+            Some(DelegationChange::Grant(delegation)) => delegation,
+            other => panic!("expected a grant, got {other:?}"),
+        }
+    }
+
+    fn post(agent: &str) -> Event {
+        Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_agent(agent)
+            .build()
+    }
+
+    #[test]
+    fn test_scope_limits_what_a_delegate_signs() {
+        let delegation = grant(DelegationScope {
+            name: "Bot".to_string(),
+            module_kinds: vec!["posts".to_string()],
+            ..Default::default()
+        });
+
+        assert!(delegation.authorize(&post("02agent")).is_ok());
+        assert!(delegation.authorize(&post("02other")).is_err());
+        let mut reaction = post("02agent");
+        reaction.module_kind = Some("reactions".to_string());
+        assert!(delegation.authorize(&reaction).is_err());
+        assert!(delegation.authorize(&revocation_event("02agent", delegation.id)).is_err());
+    }
+
+    #[test]
+    fn test_delegation_expires() {
+        let event = post("02agent");
+        let delegation = grant(DelegationScope {
+            name: "Phone".to_string(),
+            expires_at: Some(event.created_at),
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            delegation.authorize(&event),
+            Err(CoreError::Authorization(_))
+        ));
+    }
+
+    #[test]
+    fn test_backdated_event_is_rejected_after_expiry() {
+        let mut event = post("02agent");
+        let now = event.created_at;
+        event.created_at = now - time::Duration::minutes(2);
+        let delegation = grant(DelegationScope {
+            name: "Phone".to_string(),
+            expires_at: Some(now - time::Duration::minutes(1)),
+            ..Default::default()
+        });
+
+        assert!(delegation.authorize(&event).is_ok());
+        assert!(matches!(
+            delegation.check_in_force(&event, now),
+            Err(CoreError::Authorization(_))
+        ));
+        event.created_at = now - time::Duration::hours(1);
+        assert!(delegation.check_in_force(&event, event.created_at).is_ok());
+        assert!(delegation.check_in_force(&event, now).is_err());
+    }
+
+    #[test]
+    fn test_auth_events_cannot_be_delegated() {
+        let scope = DelegationScope {
+            name: "Bot".to_string(),
+            event_types: vec![DELEGATE.to_string()],
+            ..Default::default()
+        };
+        let event = delegation_event("02agent", "02device", &scope).unwrap();
+
+        assert!(DelegationChange::of(&event).is_err());
+        let to_self = delegation_event("02agent", "02agent", &DelegationScope {
+            name: "Self".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(DelegationChange::of(&to_self).is_err());
+    }
+}
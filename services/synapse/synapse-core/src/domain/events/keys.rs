@@ -0,0 +1,464 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Keys an agent signs with over time.
+//!
+//! An agent is identified by the key it first signed with, which stays its
+//! `Event::agent` for good. It moves to a new key with an `auth:rotate_key`
+//! event targeting the new key through `ObjectRef::Agent` and carrying, as its
+//! content, the new key's signature of `key_proof_payload` so nobody can claim
+//! a key they do not hold. The rotation is signed by the key in force, or by
+//! the recovery key the agent committed to beforehand with an
+//! `auth:set_recovery_key` event. Once set, the recovery key can only be
+//! replaced with its own signature, so a stolen signing key cannot lock the
+//! agent out of recovering.
+//!
+//! Key changes form a chain: each names the agent's previous key change as its
+//! `previous`, the first naming none. The `KeyHistory` of an agent follows the
+//! chain and skips changes not endorsed by the keys in force. A change naming
+//! a link that already has a known successor conflicts with it and is
+//! rejected, so a key that was rotated away cannot fork the history with a
+//! backdated change. An event is signed by the key in force when it was
+//! created: a key keeps verifying what was created before it was rotated away,
+//! and nothing after. Events signed with a rotated key carry the agent's key
+//! changes under the `KEY_HISTORY_KEY` metadata key, so Synapses that have not
+//! seen them can verify the event.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::CoreError;
+use crate::domain::events::{Event, ObjectRef, PublicKey};
+
+pub const ROTATE_KEY: &str = "auth:rotate_key";
+pub const SET_RECOVERY_KEY: &str = "auth:set_recovery_key";
+
+/// Metadata key of an event signed with a rotated key, holding the key
+/// changes of its agent as a JSON array of events.
+pub const KEY_HISTORY_KEY: &str = "key_history";
+
+const AUTH_MODULE: &str = "auth";
+
+/// Tag of the payload a new key signs to prove it is held by the rotating
+/// agent.
+const KEY_PROOF_FORMAT: &str = "menexus.key_proof.v1";
+
+/// A change to the keys of `Event::agent`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KeyChange {
+    /// The agent signs with `key` from now on. `proof` is the signature of
+    /// `key_proof_payload` by `key`.
+    Rotate { key: PublicKey, proof: String },
+    /// `key` may rotate the agent's key from now on.
+    SetRecovery { key: PublicKey },
+}
+
+impl KeyChange {
+    /// The change `event` makes, if it is a key event. Fails if it does not
+    /// target the new key, or expires, since a history must not lose entries.
+    pub fn of(event: &Event) -> Result<Option<Self>, CoreError> {
+        if event.event_type != ROTATE_KEY && event.event_type != SET_RECOVERY_KEY {
+            return Ok(None);
+        }
+        let Some(ObjectRef::Agent(key)) = &event.target else {
+            return Err(CoreError::Validation(format!(
+                "{} must target the new key",
+                event.event_type
+            )));
+        };
+        if key.trim().is_empty() {
+            return Err(CoreError::Validation("the new key is empty".to_string()));
+        }
+        if event.expiration.is_some() {
+            return Err(CoreError::Validation(format!(
+                "{} events cannot expire",
+                event.event_type
+            )));
+        }
+        if event.event_type == SET_RECOVERY_KEY {
+            return Ok(Some(KeyChange::SetRecovery { key: key.clone() }));
+        }
+        match event.content.as_deref().map(str::trim) {
+            Some(proof) if !proof.is_empty() => Ok(Some(KeyChange::Rotate {
+                key: key.clone(),
+                proof: proof.to_string(),
+            })),
+            _ => Err(CoreError::Validation(format!(
+                "{ROTATE_KEY} must carry the new key's proof"
+            ))),
+        }
+    }
+}
+
+/// A key an agent signed with, from `since` until the next period.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyPeriod {
+    pub key: PublicKey,
+    /// `None` for the agent's own key, in force from the start.
+    pub since: Option<OffsetDateTime>,
+    /// The `auth:rotate_key` event that brought the key in.
+    pub rotation: Option<Uuid>,
+}
+
+/// The keys of an agent over time, as endorsed by its key changes.
+#[derive(Clone, Debug)]
+pub struct KeyHistory {
+    agent: PublicKey,
+    periods: Vec<KeyPeriod>,
+    recovery_key: Option<PublicKey>,
+    /// Key changes applied, in order, with the key that endorsed each.
+    changes: Vec<(Event, PublicKey)>,
+}
+
+impl KeyHistory {
+    /// The history of an agent that never changed keys.
+    pub fn new(agent: impl Into<PublicKey>) -> Self {
+        let agent = agent.into();
+        Self {
+            periods: vec![KeyPeriod {
+                key: agent.clone(),
+                since: None,
+                rotation: None,
+            }],
+            agent,
+            recovery_key: None,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Follows the chain of key changes of `agent` among `events`. Events of
+    /// other agents or types, changes off the chain and changes not endorsed
+    /// by the keys in force are skipped. Of changes naming the same previous
+    /// change, the first given that is endorsed wins, so callers list the
+    /// changes they already know before those they are handed.
+    #[cfg(feature = "crypto")]
+    pub fn from_events(agent: impl Into<PublicKey>, events: impl IntoIterator<Item = Event>) -> Self {
+        let mut history = Self::new(agent);
+        let mut changes: Vec<(Event, KeyChange)> = events
+            .into_iter()
+            .filter(|event| event.agent == history.agent)
+            .filter_map(|event| match KeyChange::of(&event) {
+                Ok(Some(change)) => Some((event, change)),
+                _ => None,
+            })
+            .collect();
+        loop {
+            let latest = history.latest_change();
+            let next = changes.iter().enumerate().find_map(|(position, (event, change))| {
+                if event.previous != latest {
+                    return None;
+                }
+                history.endorser(event, change).map(|endorser| (position, endorser))
+            });
+            let Some((position, endorser)) = next else {
+                break;
+            };
+            let (event, change) = changes.remove(position);
+            history.apply(event, change, endorser);
+        }
+        history
+    }
+
+    /// The key that endorses `change` as the next link of the history, if one
+    /// may make it and the change is valid.
+    #[cfg(feature = "crypto")]
+    fn endorser(&self, event: &Event, change: &KeyChange) -> Option<PublicKey> {
+        use crate::domain::crypto::signature::verify_signature;
+
+        // Periods must stay in order for `key_at`
+        if self.changes.last().is_some_and(|(last, _)| event.created_at < last.created_at) {
+            return None;
+        }
+        let signature = event.agent_signature.as_deref()?;
+        let endorsers = match (change, &self.recovery_key) {
+            (KeyChange::Rotate { .. }, Some(recovery)) => vec![self.current_key(), recovery],
+            (KeyChange::Rotate { .. }, None) => vec![self.current_key()],
+            (KeyChange::SetRecovery { .. }, Some(recovery)) => vec![recovery],
+            (KeyChange::SetRecovery { .. }, None) => vec![self.current_key()],
+        };
+        let payload = event.signing_payload();
+        let endorser = endorsers
+            .into_iter()
+            .find(|key| verify_signature(key, &payload, signature).is_valid())?;
+        if let KeyChange::Rotate { key, proof } = change {
+            let proven = verify_signature(key, &key_proof_payload(&self.agent, event.id, key), proof);
+            if !proven.is_valid() || key == self.current_key() {
+                return None;
+            }
+        }
+        Some(endorser.clone())
+    }
+
+    /// Appends `change`, endorsed by `endorser`, to the history.
+    #[cfg(feature = "crypto")]
+    fn apply(&mut self, event: Event, change: KeyChange, endorser: PublicKey) {
+        match change {
+            KeyChange::Rotate { key, .. } => self.periods.push(KeyPeriod {
+                key,
+                since: Some(event.created_at),
+                rotation: Some(event.id),
+            }),
+            KeyChange::SetRecovery { key } => self.recovery_key = Some(key),
+        }
+        self.changes.push((event, endorser));
+    }
+
+    pub fn agent(&self) -> &PublicKey {
+        &self.agent
+    }
+
+    /// The key the agent signs with now.
+    pub fn current_key(&self) -> &PublicKey {
+        &self.periods[self.periods.len() - 1].key
+    }
+
+    pub fn recovery_key(&self) -> Option<&PublicKey> {
+        self.recovery_key.as_ref()
+    }
+
+    /// The keys of the agent, oldest first.
+    pub fn periods(&self) -> &[KeyPeriod] {
+        &self.periods
+    }
+
+    /// The key events created at `at` must be signed with.
+    pub fn key_at(&self, at: OffsetDateTime) -> &PublicKey {
+        let period = self
+            .periods
+            .iter()
+            .rev()
+            .find(|period| period.since.is_none_or(|since| since <= at))
+            .unwrap_or(&self.periods[0]);
+        &period.key
+    }
+
+    /// The last key change of the history, which the next one must name as
+    /// its `previous`.
+    pub fn latest_change(&self) -> Option<Uuid> {
+        self.changes.last().map(|(event, _)| event.id)
+    }
+
+    /// Whether key change `id` is part of the history.
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.changes.iter().any(|(event, _)| event.id == id)
+    }
+
+    /// The key that endorsed key change `id`, if it is part of the history.
+    pub fn endorser_of(&self, id: Uuid) -> Option<&PublicKey> {
+        self.changes
+            .iter()
+            .find(|(event, _)| event.id == id)
+            .map(|(_, endorser)| endorser)
+    }
+
+    /// The key changes applied, oldest first.
+    pub fn changes(&self) -> impl Iterator<Item = &Event> {
+        self.changes.iter().map(|(event, _)| event)
+    }
+
+    /// The value to put under `KEY_HISTORY_KEY` in events signed with a
+    /// rotated key, or `None` if the agent never changed keys.
+    pub fn token(&self) -> Result<Option<String>, CoreError> {
+        if self.changes.is_empty() {
+            return Ok(None);
+        }
+        let events: Vec<&Event> = self.changes().collect();
+        serde_json::to_string(&events)
+            .map(Some)
+            .map_err(|e| CoreError::Other(e.to_string()))
+    }
+}
+
+/// The `auth:rotate_key` event moving `agent` to `key` after its key change
+/// `previous`, to be completed with the `key_proof` as its content and signed
+/// with the key in force or the recovery key.
+pub fn rotation_event(
+    agent: impl Into<PublicKey>,
+    key: impl Into<PublicKey>,
+    previous: Option<Uuid>,
+) -> Event {
+    key_event(ROTATE_KEY, agent, key, previous)
+}
+
+/// The `auth:set_recovery_key` event committing `agent` to `key` after its
+/// key change `previous`, to be signed with the key in force, or the recovery
+/// key it replaces.
+pub fn recovery_key_event(
+    agent: impl Into<PublicKey>,
+    key: impl Into<PublicKey>,
+    previous: Option<Uuid>,
+) -> Event {
+    key_event(SET_RECOVERY_KEY, agent, key, previous)
+}
+
+fn key_event(
+    event_type: &str,
+    agent: impl Into<PublicKey>,
+    key: impl Into<PublicKey>,
+    previous: Option<Uuid>,
+) -> Event {
+    let mut event = Event::new()
+        .with_event_type(event_type)
+        .with_module_kind(AUTH_MODULE)
+        .with_agent(agent)
+        .with_target(ObjectRef::Agent(key.into()));
+    if let Some(previous) = previous {
+        event = event.with_previous(previous);
+    }
+    event.build()
+}
+
+/// What the new key of rotation `id` signs to prove `agent` holds it.
+pub fn key_proof_payload(agent: &str, id: Uuid, key: &str) -> Vec<u8> {
+    format!("{KEY_PROOF_FORMAT}:{agent}:{id}:{key}").into_bytes()
+}
+
+/// The content of `rotation`: its new key's signature of `key_proof_payload`,
+/// hex-encoded.
+#[cfg(feature = "crypto")]
+pub fn key_proof(rotation: &Event, key: &k256::ecdsa::SigningKey) -> String {
+    use crate::domain::crypto::signature::sign_payload;
+
+    let public = hex::encode(key.verifying_key().to_encoded_point(true).as_bytes());
+    sign_payload(&key_proof_payload(&rotation.agent, rotation.id, &public), key)
+}
+
+/// The key changes `event` brings along: those carried under
+/// `KEY_HISTORY_KEY`, and the event itself if it is one.
+pub fn carried_key_changes(event: &Event) -> Result<Vec<Event>, CoreError> {
+    let mut changes: Vec<Event> = match event.metadata.as_ref().and_then(|m| m.get(KEY_HISTORY_KEY)) {
+        Some(token) => serde_json::from_str(token)
+            .map_err(|e| CoreError::Validation(format!("invalid key history: {e}")))?,
+        None => Vec::new(),
+    };
+    if KeyChange::of(event)?.is_some() {
+        changes.push(event.clone());
+    }
+    Ok(changes)
+}
+
+#[cfg(all(test, feature = "crypto"))]
+mod tests {
+    use super::*;
+    use crate::domain::crypto::signature::sign_event;
+    use k256::ecdsa::SigningKey;
+    use time::Duration;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    fn public(key: &SigningKey) -> PublicKey {
+        hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+    }
+
+    fn rotation(
+        agent: &SigningKey,
+        to: &SigningKey,
+        signer: &SigningKey,
+        previous: Option<&Event>,
+        at: OffsetDateTime,
+    ) -> Event {
+        let mut event = rotation_event(public(agent), public(to), previous.map(|e| e.id));
+        event.created_at = at;
+        event.content = Some(key_proof(&event, to));
+        event.agent_signature = Some(sign_event(&event, signer));
+        event
+    }
+
+    fn recovery(
+        agent: &SigningKey,
+        to: &SigningKey,
+        signer: &SigningKey,
+        previous: Option<&Event>,
+        at: OffsetDateTime,
+    ) -> Event {
+        let mut event = recovery_key_event(public(agent), public(to), previous.map(|e| e.id));
+        event.created_at = at;
+        event.agent_signature = Some(sign_event(&event, signer));
+        event
+    }
+
+    #[test]
+    fn test_rotation_moves_the_key_in_force() {
+        let (genesis, next) = (key(1), key(2));
+        let at = OffsetDateTime::now_utc();
+        let rotate = rotation(&genesis, &next, &genesis, None, at);
+        let history = KeyHistory::from_events(public(&genesis), [rotate.clone(), rotate.clone()]);
+
+        assert_eq!(history.periods().len(), 2);
+        assert_eq!(history.current_key(), &public(&next));
+        assert_eq!(history.key_at(at - Duration::seconds(1)), &public(&genesis));
+        assert_eq!(history.key_at(at), &public(&next));
+        assert_eq!(history.endorser_of(rotate.id), Some(&public(&genesis)));
+        assert_eq!(history.latest_change(), Some(rotate.id));
+
+        // The token replays to the same history
+        let token = history.token().unwrap().unwrap();
+        let replayed: Vec<Event> = serde_json::from_str(&token).unwrap();
+        assert_eq!(KeyHistory::from_events(public(&genesis), replayed).periods(), history.periods());
+    }
+
+    #[test]
+    fn test_rotation_needs_the_key_in_force_and_the_new_key() {
+        let (genesis, next, thief) = (key(1), key(2), key(3));
+        let at = OffsetDateTime::now_utc();
+
+        let forged = rotation(&genesis, &thief, &thief, None, at);
+        assert_eq!(KeyHistory::from_events(public(&genesis), [forged]).periods().len(), 1);
+
+        // Claiming a key without its proof
+        let mut unproven = rotation(&genesis, &next, &genesis, None, at);
+        unproven.content = Some(key_proof(&rotation_event(public(&genesis), public(&next), None), &next));
+        unproven.agent_signature = Some(sign_event(&unproven, &genesis));
+        assert_eq!(KeyHistory::from_events(public(&genesis), [unproven]).periods().len(), 1);
+
+        // The rotated key no longer endorses rotations
+        let first = rotation(&genesis, &next, &genesis, None, at);
+        let stale = rotation(&genesis, &thief, &genesis, Some(&first), at + Duration::seconds(1));
+        let history = KeyHistory::from_events(public(&genesis), [stale, first]);
+        assert_eq!(history.current_key(), &public(&next));
+    }
+
+    #[test]
+    fn test_backdated_change_by_a_rotated_key_cannot_take_over() {
+        let (genesis, next, thief) = (key(1), key(2), key(3));
+        let at = OffsetDateTime::now_utc();
+        let rotate = rotation(&genesis, &next, &genesis, None, at);
+        // The rotated-away key forks the history before the rotation
+        let forged_rotation = rotation(&genesis, &thief, &genesis, None, at - Duration::hours(1));
+        let forged_recovery = recovery(&genesis, &thief, &genesis, None, at - Duration::hours(1));
+
+        let history = KeyHistory::from_events(
+            public(&genesis),
+            [rotate.clone(), forged_rotation.clone(), forged_recovery.clone()],
+        );
+
+        assert_eq!(history.current_key(), &public(&next));
+        assert_eq!(history.recovery_key(), None);
+        assert!(!history.contains(forged_rotation.id));
+        assert!(!history.contains(forged_recovery.id));
+
+        // Nor can it extend the chain with a change dated before the rotation
+        let stale = recovery(&genesis, &thief, &genesis, Some(&rotate), at - Duration::hours(1));
+        let history = KeyHistory::from_events(public(&genesis), [rotate, stale]);
+        assert_eq!(history.recovery_key(), None);
+    }
+
+    #[test]
+    fn test_recovery_key_takes_the_agent_back() {
+        let (genesis, recovery_key, thief, fresh) = (key(1), key(2), key(3), key(4));
+        let at = OffsetDateTime::now_utc();
+        let commit = recovery(&genesis, &recovery_key, &genesis, None, at);
+        let stolen = rotation(&genesis, &thief, &genesis, Some(&commit), at + Duration::seconds(1));
+        let replace = recovery(&genesis, &thief, &thief, Some(&stolen), at + Duration::seconds(2));
+        let recovered = rotation(&genesis, &fresh, &recovery_key, Some(&stolen), at + Duration::seconds(3));
+
+        let history = KeyHistory::from_events(public(&genesis), [recovered, replace, stolen, commit]);
+
+        assert_eq!(history.current_key(), &public(&fresh));
+        assert_eq!(history.recovery_key(), Some(&public(&recovery_key)));
+        assert_eq!(history.key_at(at + Duration::seconds(2)), &public(&thief));
+    }
+}
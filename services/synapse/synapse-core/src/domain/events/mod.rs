@@ -1,11 +1,19 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod delegations;
+pub mod keys;
+pub mod reactions;
+pub mod revisions;
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 
+/// How far a client-supplied `created_at` may be from the Synapse clock.
+pub const MAX_CLOCK_SKEW: Duration = Duration::minutes(5);
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Event {
     pub id: Uuid,
@@ -20,6 +28,8 @@ pub struct Event {
     pub artifacts: Option<Vec<ArtifactUri>>,
     pub metadata: Option<HashMap<String, String>>,
     pub links: Option<Vec<String>>,
+    /// Encoded as a byte string by binary formats; still an array of integers in JSON.
+    #[serde(default, with = "serde_bytes")]
     pub data: Option<Vec<u8>>,
     pub expiration: Option<OffsetDateTime>,
     /// Cryptographic signature of the event by the agent.
@@ -27,6 +37,10 @@ pub struct Event {
     /// that an action was authorized by the agent without needing a session.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent_signature: Option<String>,
+    /// Latest edit of the event, attached by reads (see `revisions`). It is
+    /// not part of the signed payload; the edit carries its own signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_edit: Option<Box<Event>>,
 }
 
 pub struct EventBuilder {
@@ -71,39 +85,39 @@ impl Event {
 
     /// Returns the canonical bytes used for signing/verification.
     /// This excludes the signature field itself to avoid circular dependency.
+    /// See `domain::crypto::canonical` for the encoding.
     pub fn signing_payload(&self) -> Vec<u8> {
-        // Create a deterministic representation for signing
-        // We use a simplified struct that excludes the signature
-        let payload = SigningPayload {
-            id: self.id,
-            created_at: self.created_at,
-            event_type: &self.event_type,
-            module_kind: self.module_kind.as_deref(),
-            module_slug: self.module_slug.as_deref(),
-            agent: &self.agent,
-            target: &self.target,
-            previous: self.previous,
-            content: self.content.as_deref(),
-            metadata: &self.metadata,
-        };
-        // Use JSON for deterministic serialization
-        serde_json::to_vec(&payload).unwrap_or_default()
+        crate::domain::crypto::canonical::event_signing_bytes(self)
     }
-}
 
-/// Internal struct for creating deterministic signing payload
-#[derive(Serialize)]
-struct SigningPayload<'a> {
-    id: Uuid,
-    created_at: OffsetDateTime,
-    event_type: &'a str,
-    module_kind: Option<&'a str>,
-    module_slug: Option<&'a str>,
-    agent: &'a str,
-    target: &'a Option<ObjectRef>,
-    previous: Option<Uuid>,
-    content: Option<&'a str>,
-    metadata: &'a Option<HashMap<String, String>>,
+    /// Whether the event has expired at `now`. Expired events are no longer
+    /// served and are eventually pruned to tombstones.
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expiration.is_some_and(|expiration| expiration <= now)
+    }
+
+    /// Whether `other` is a re-delivery of this event: the same signed payload,
+    /// signature and unsigned attachments. Timestamps are compared at the
+    /// microsecond precision events are stored with.
+    pub fn is_replay_of(&self, other: &Event) -> bool {
+        let micros = |t: Option<OffsetDateTime>| t.map(|t| t.unix_timestamp_nanos() / 1_000);
+        self.signing_payload() == other.signing_payload()
+            && self.agent_signature == other.agent_signature
+            && self.artifacts == other.artifacts
+            && self.links == other.links
+            && self.data == other.data
+            && micros(self.expiration) == micros(other.expiration)
+    }
+
+    /// The content to show: that of the latest edit if reads attached one.
+    pub fn current_content(&self) -> Option<&str> {
+        self.latest_edit.as_deref().unwrap_or(self).content.as_deref()
+    }
+
+    /// The data to show: that of the latest edit if reads attached one.
+    pub fn current_data(&self) -> Option<&[u8]> {
+        self.latest_edit.as_deref().unwrap_or(self).data.as_deref()
+    }
 }
 
 impl EventBuilder {
@@ -189,11 +203,12 @@ impl EventBuilder {
             data: self.data,
             expiration: self.expiration,
             agent_signature: self.agent_signature,
+            latest_edit: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ObjectRef {
     Synapse(Uuid),
     Agent(PublicKey),
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Emoji reactions to events.
+//!
+//! An agent reacts to an event by appending a `reactions:add` event and takes
+//! the reaction back with `reactions:remove`. Both target the event through
+//! `ObjectRef::Event` and carry the emoji as their content. An agent holds at
+//! most one reaction per emoji on an event; its latest add or remove, by
+//! `created_at`, wins, so changes delivered out of order converge.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::CoreError;
+use crate::domain::events::{Event, ObjectRef, PublicKey};
+
+pub const ADD_REACTION: &str = "reactions:add";
+pub const REMOVE_REACTION: &str = "reactions:remove";
+
+/// Longest emoji accepted, in bytes. Leaves room for ZWJ sequences.
+pub const MAX_EMOJI_LEN: usize = 32;
+
+/// An agent adding or removing a reaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReactionChange {
+    /// The event reacted to.
+    pub target: Uuid,
+    pub emoji: String,
+    pub agent: PublicKey,
+    /// Whether the reaction is held after the change.
+    pub active: bool,
+    /// The `reactions:*` event making the change.
+    pub event_id: Uuid,
+    pub at: OffsetDateTime,
+}
+
+impl ReactionChange {
+    /// The change `event` makes, if it is a reaction event. Fails if it does
+    /// not target an event or carry a single emoji.
+    pub fn of(event: &Event) -> Result<Option<Self>, CoreError> {
+        let active = match event.event_type.as_str() {
+            ADD_REACTION => true,
+            REMOVE_REACTION => false,
+            _ => return Ok(None),
+        };
+        let Some(ObjectRef::Event(target)) = event.target else {
+            return Err(CoreError::Validation(format!(
+                "{} must target the event reacted to",
+                event.event_type
+            )));
+        };
+        let emoji = event.content.as_deref().unwrap_or_default();
+        validate_emoji(emoji)?;
+        Ok(Some(Self {
+            target,
+            emoji: emoji.to_string(),
+            agent: event.agent.clone(),
+            active,
+            event_id: event.id,
+            at: event.created_at,
+        }))
+    }
+}
+
+/// Checks that `emoji` is a short run of visible characters.
+pub fn validate_emoji(emoji: &str) -> Result<(), CoreError> {
+    if emoji.is_empty() || emoji.len() > MAX_EMOJI_LEN {
+        return Err(CoreError::Validation(format!(
+            "reaction must be 1 to {MAX_EMOJI_LEN} bytes"
+        )));
+    }
+    if emoji.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(CoreError::Validation(
+            "reaction must not contain whitespace".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The reactions with one emoji on an event.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReactionSummary {
+    pub emoji: String,
+    pub count: u32,
+    /// Whether the agent the summary was made for holds this reaction.
+    pub reacted: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reaction(event_type: &str, target: Option<ObjectRef>, emoji: &str) -> Event {
+        let mut event = Event::new()
+            .with_event_type(event_type)
+            .with_module_kind("reactions")
+            .with_agent("02abcdef")
+            .with_content(emoji)
+            .build();
+        event.target = target;
+        event
+    }
+
+    #[test]
+    fn test_reaction_change_of_event() {
+        let target = Uuid::new_v4();
+        let add = reaction(ADD_REACTION, Some(ObjectRef::Event(target)), "🔥");
+        let change = ReactionChange::of(&add).unwrap().unwrap();
+        assert_eq!((change.target, change.emoji.as_str(), change.active), (target, "🔥", true));
+
+        let remove = reaction(REMOVE_REACTION, Some(ObjectRef::Event(target)), "🔥");
+        assert!(!ReactionChange::of(&remove).unwrap().unwrap().active);
+
+        let post = reaction("posts:create_post", None, "hello");
+        assert_eq!(ReactionChange::of(&post).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_reactions_are_rejected() {
+        let target = Some(ObjectRef::Event(Uuid::new_v4()));
+        for event in [
+            reaction(ADD_REACTION, None, "🔥"),
+            reaction(ADD_REACTION, Some(ObjectRef::Agent("02abcdef".into())), "🔥"),
+            reaction(ADD_REACTION, target.clone(), ""),
+            reaction(ADD_REACTION, target.clone(), "two words"),
+            reaction(ADD_REACTION, target, &"🔥".repeat(9)),
+        ] {
+            assert!(matches!(
+                ReactionChange::of(&event),
+                Err(CoreError::Validation(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Edits and deletions of earlier events.
+//!
+//! The event log is append-only. An event is edited by appending a
+//! `<module>:edit` event and retracted by appending a `<module>:delete`
+//! tombstone, both targeting the original through `ObjectRef::Event`:
+//! - an edit replaces the original's `content` and `data`; its `previous`
+//!   points at the version it supersedes,
+//! - reads return events as signed, with their latest edit attached in the
+//!   unsigned `latest_edit`, and hide deleted ones,
+//! - revisions keep the original's module kind and slug, so they are published
+//!   to the same followers as the original.
+//!
+//! Only the agent of the original or a moderator may revise it.
+
+use uuid::Uuid;
+
+use crate::CoreError;
+use crate::domain::events::{Event, ObjectRef, PublicKey};
+
+/// Action of the event types editing an earlier event.
+pub const EDIT_ACTION: &str = "edit";
+/// Action of the event types retracting an earlier event.
+pub const DELETE_ACTION: &str = "delete";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Revision {
+    Edit(Uuid),
+    Delete(Uuid),
+}
+
+impl Revision {
+    /// The revision `event` makes, if it is an edit or delete. Fails if it
+    /// does not target the event it revises.
+    pub fn of(event: &Event) -> Result<Option<Self>, CoreError> {
+        let revision: fn(Uuid) -> Self = match event.event_type.split_once(':') {
+            Some((_, EDIT_ACTION)) => Revision::Edit,
+            Some((_, DELETE_ACTION)) => Revision::Delete,
+            _ => return Ok(None),
+        };
+        match event.target {
+            Some(ObjectRef::Event(original)) => Ok(Some(revision(original))),
+            _ => Err(CoreError::Validation(format!(
+                "{} must target the event it revises",
+                event.event_type
+            ))),
+        }
+    }
+
+    /// The event being revised.
+    pub fn original(self) -> Uuid {
+        match self {
+            Revision::Edit(id) | Revision::Delete(id) => id,
+        }
+    }
+}
+
+/// The edit event type of `module_kind`.
+pub fn edit_event_type(module_kind: &str) -> String {
+    format!("{module_kind}:{EDIT_ACTION}")
+}
+
+/// The delete event type of `module_kind`.
+pub fn delete_event_type(module_kind: &str) -> String {
+    format!("{module_kind}:{DELETE_ACTION}")
+}
+
+/// Checks that `revision` may revise `original`.
+///
+/// Revisions must stay in the original's module and slug and be made by its
+/// agent or one of `moderators`. Revisions themselves cannot be revised.
+pub fn authorize_revision(
+    revision: &Event,
+    original: &Event,
+    moderators: &[PublicKey],
+) -> Result<(), CoreError> {
+    if Revision::of(original)?.is_some() {
+        return Err(CoreError::Validation(format!(
+            "event {} is a revision and cannot be revised",
+            original.id
+        )));
+    }
+    if revision.module_kind != original.module_kind || revision.module_slug != original.module_slug
+    {
+        return Err(CoreError::Validation(format!(
+            "{} must stay in the module of event {}",
+            revision.event_type, original.id
+        )));
+    }
+    if revision.agent != original.agent && !moderators.contains(&revision.agent) {
+        return Err(CoreError::Authorization(format!(
+            "{} may not revise event {}",
+            revision.agent, original.id
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(agent: &str) -> Event {
+        Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_module_slug("general")
+            .with_agent(agent)
+            .build()
+    }
+
+    fn delete(original: &Event, agent: &str) -> Event {
+        Event::new()
+            .with_event_type(delete_event_type("posts"))
+            .with_module_kind("posts")
+            .with_module_slug("general")
+            .with_agent(agent)
+            .with_target(ObjectRef::Event(original.id))
+            .build()
+    }
+
+    #[test]
+    fn test_only_author_or_moderator_may_revise() {
+        let original = post("alice");
+        let moderators = vec!["mod".to_string()];
+
+        assert!(authorize_revision(&delete(&original, "alice"), &original, &moderators).is_ok());
+        assert!(authorize_revision(&delete(&original, "mod"), &original, &moderators).is_ok());
+        assert!(matches!(
+            authorize_revision(&delete(&original, "mallory"), &original, &moderators),
+            Err(CoreError::Authorization(_))
+        ));
+    }
+
+    #[test]
+    fn test_revision_must_target_an_event() {
+        let mut revision = delete(&post("alice"), "alice");
+        assert!(matches!(Revision::of(&revision), Ok(Some(Revision::Delete(_)))));
+
+        revision.target = None;
+        assert!(Revision::of(&revision).is_err());
+        assert!(Revision::of(&post("alice")).unwrap().is_none());
+    }
+}
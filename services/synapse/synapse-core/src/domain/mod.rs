@@ -12,5 +12,6 @@ pub mod federation;
 pub mod modules;
 pub mod peers;
 pub mod profiles;
+pub mod search;
 pub mod settings;
 pub mod synapses;
@@ -1,2 +1,79 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
-// Copyright © 2025 Malifex LLC and contributors
\ No newline at end of file
+// Copyright © 2025 Malifex LLC and contributors
+
+use serde::{Deserialize, Serialize};
+
+/// A module installed on a Synapse, as advertised to its peers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModuleCapability {
+    pub kind: String,
+    pub version: String,
+    /// Event types the module handles; empty for Synapses that predate the
+    /// declarations.
+    #[serde(default)]
+    pub event_types: Vec<EventTypeSpec>,
+}
+
+/// What handling an event type does.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTypeKind {
+    /// Reads module state and replies with it.
+    Query,
+    /// Changes state owned by the module.
+    Command,
+    /// Synapse-to-Synapse plumbing, authenticated by the SNP envelope.
+    System,
+}
+
+/// Declaration of an event type handled by a module. The ingest pipeline
+/// routes, authenticates and stores events according to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventTypeSpec {
+    pub event_type: String,
+    pub kind: EventTypeKind,
+    /// Whether accepted events are appended to the event log.
+    pub persisted: bool,
+    /// Whether events must carry a valid agent signature.
+    pub requires_auth: bool,
+    /// JSON Schema of the payload carried in the event `data`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
+}
+
+impl EventTypeSpec {
+    /// A read that is neither stored nor signed.
+    pub fn query(event_type: impl Into<String>) -> Self {
+        Self::new(event_type, EventTypeKind::Query, false, false)
+    }
+
+    /// A write that must be signed by its agent and is stored.
+    pub fn command(event_type: impl Into<String>) -> Self {
+        Self::new(event_type, EventTypeKind::Command, true, true)
+    }
+
+    /// A Synapse system event, neither stored nor signed by an agent.
+    pub fn system(event_type: impl Into<String>) -> Self {
+        Self::new(event_type, EventTypeKind::System, false, false)
+    }
+
+    pub fn with_schema(mut self, schema: serde_json::Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    fn new(
+        event_type: impl Into<String>,
+        kind: EventTypeKind,
+        persisted: bool,
+        requires_auth: bool,
+    ) -> Self {
+        Self {
+            event_type: event_type.into(),
+            kind,
+            persisted,
+            requires_auth,
+            schema: None,
+        }
+    }
+}
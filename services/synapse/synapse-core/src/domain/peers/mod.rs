@@ -1,2 +1,102 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
-// Copyright © 2025 Malifex LLC and contributors
\ No newline at end of file
+// Copyright © 2025 Malifex LLC and contributors
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Delay before the first redial of a peer that could not be reached.
+pub const REDIAL_BASE_DELAY: Duration = Duration::from_secs(5);
+/// Upper bound on the delay between redials of an unreachable peer.
+pub const REDIAL_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+/// Consecutive dial failures after which a peer is no longer redialed until it
+/// connects to us again.
+pub const MAX_DIAL_FAILURES: i32 = 16;
+
+/// Most recent addresses remembered per peer.
+pub const MAX_PEER_ADDRESSES: i32 = 8;
+
+pub const REPUTATION_MIN: i32 = -100;
+pub const REPUTATION_MAX: i32 = 100;
+/// Reputation gained by each successful connection.
+pub const REPUTATION_CONNECTED: i32 = 1;
+/// Reputation lost by each failed dial.
+pub const REPUTATION_DIAL_FAILURE: i32 = -5;
+
+/// A Synapse this node has seen on the network.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// libp2p `PeerId` of the Synapse.
+    pub peer_id: String,
+    /// Synapse public key, once the peer has told us.
+    pub public_key: Option<String>,
+    /// Multiaddrs the peer was reached at.
+    pub addresses: Vec<String>,
+    pub first_seen: OffsetDateTime,
+    pub last_seen: Option<OffsetDateTime>,
+    pub last_failure: Option<OffsetDateTime>,
+    /// Dial failures since the last successful connection.
+    pub failures: i32,
+    pub reputation: i32,
+}
+
+impl Peer {
+    pub fn new(peer_id: impl Into<String>) -> Self {
+        Self {
+            peer_id: peer_id.into(),
+            public_key: None,
+            addresses: Vec::new(),
+            first_seen: OffsetDateTime::now_utc(),
+            last_seen: None,
+            last_failure: None,
+            failures: 0,
+            reputation: 0,
+        }
+    }
+
+    /// Whether the peer is still worth redialing.
+    pub fn should_redial(&self) -> bool {
+        !self.addresses.is_empty() && self.failures < MAX_DIAL_FAILURES
+    }
+
+    /// How long to wait before the next dial, doubling with each consecutive failure.
+    pub fn redial_delay(&self) -> Duration {
+        redial_delay(self.failures)
+    }
+}
+
+/// Exponential backoff for the `failures`-th consecutive failed dial.
+pub fn redial_delay(failures: i32) -> Duration {
+    let exponent = failures.clamp(0, 16) as u32;
+    REDIAL_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(exponent))
+        .min(REDIAL_MAX_DELAY)
+}
+
+/// Applies `delta` to `reputation`, keeping it within bounds.
+pub fn adjust_reputation(reputation: i32, delta: i32) -> i32 {
+    reputation
+        .saturating_add(delta)
+        .clamp(REPUTATION_MIN, REPUTATION_MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redial_delay_backs_off_to_cap() {
+        assert_eq!(redial_delay(0), REDIAL_BASE_DELAY);
+        assert_eq!(redial_delay(1), REDIAL_BASE_DELAY * 2);
+        assert_eq!(redial_delay(3), REDIAL_BASE_DELAY * 8);
+        assert_eq!(redial_delay(MAX_DIAL_FAILURES), REDIAL_MAX_DELAY);
+        assert_eq!(redial_delay(i32::MAX), REDIAL_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_reputation_is_bounded() {
+        assert_eq!(adjust_reputation(REPUTATION_MAX, REPUTATION_CONNECTED), REPUTATION_MAX);
+        assert_eq!(adjust_reputation(REPUTATION_MIN + 1, REPUTATION_DIAL_FAILURE), REPUTATION_MIN);
+        assert_eq!(adjust_reputation(0, REPUTATION_DIAL_FAILURE), -5);
+    }
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Full-text search over the events and profiles held by a Synapse.
+//!
+//! A `SearchQuery` travels in the `data` of `search:query` events so remote
+//! Synapses can be searched too; they answer with a `search:results` event
+//! carrying `SearchResults`. Deleted and expired events are never found and
+//! edited events are matched on their latest content.
+
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime, Time};
+
+use crate::CoreError;
+use crate::domain::events::Event;
+use crate::domain::profiles::Profile;
+
+/// Results per page when the query sets no limit.
+pub const DEFAULT_SEARCH_LIMIT: u32 = 20;
+/// Most results per page.
+pub const MAX_SEARCH_LIMIT: u32 = 100;
+/// Longest search text accepted, in bytes.
+pub const MAX_SEARCH_TEXT_LEN: usize = 256;
+
+/// What a query searches.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SearchScope {
+    #[default]
+    All,
+    Events,
+    Profiles,
+}
+
+/// How recent found events must be. Profiles are not filtered by time.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SearchTimeRange {
+    #[default]
+    AnyTime,
+    PastHour,
+    /// Since midnight UTC.
+    Today,
+    ThisWeek,
+    ThisMonth,
+    ThisYear,
+}
+
+impl SearchTimeRange {
+    /// The earliest creation time of events in the range, as of `now`.
+    pub fn since(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        match self {
+            SearchTimeRange::AnyTime => None,
+            SearchTimeRange::PastHour => Some(now - Duration::hours(1)),
+            SearchTimeRange::Today => Some(now.replace_time(Time::MIDNIGHT)),
+            SearchTimeRange::ThisWeek => Some(now - Duration::weeks(1)),
+            SearchTimeRange::ThisMonth => Some(now - Duration::days(30)),
+            SearchTimeRange::ThisYear => Some(now - Duration::days(365)),
+        }
+    }
+}
+
+/// Order of found events. Profiles are always sorted by relevance.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SearchSort {
+    /// Best match first.
+    #[default]
+    Relevance,
+    /// Newest first.
+    Recent,
+    /// Most reactions first.
+    Popular,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SearchQuery {
+    /// Web search syntax: words, `"quoted phrases"`, `or` and `-excluded`.
+    pub text: String,
+    pub scope: SearchScope,
+    /// Only events of this type, e.g. `posts:create_post`.
+    pub event_type: Option<String>,
+    pub module_kind: Option<String>,
+    pub time_range: SearchTimeRange,
+    pub sort: SearchSort,
+    /// Results skipped, for paging.
+    pub offset: u32,
+    pub limit: Option<u32>,
+}
+
+impl SearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the query carried in the `data` of a `search:query` event.
+    pub fn from_event(event: &Event) -> Result<Self, CoreError> {
+        let data = event
+            .data
+            .as_deref()
+            .ok_or_else(|| CoreError::Validation("search:query requires data".to_string()))?;
+        serde_json::from_slice(data)
+            .map_err(|e| CoreError::Validation(format!("invalid search query: {e}")))
+    }
+
+    /// Encodes the query for the `data` field of a `search:query` event.
+    pub fn to_data(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// JSON Schema of the query carried in the `data` of `search:query` events.
+    pub fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["text"],
+            "properties": {
+                "text": { "type": "string", "maxLength": MAX_SEARCH_TEXT_LEN },
+                "scope": { "enum": ["All", "Events", "Profiles"] },
+                "event_type": { "type": ["string", "null"] },
+                "module_kind": { "type": ["string", "null"] },
+                "time_range": {
+                    "enum": ["AnyTime", "PastHour", "Today", "ThisWeek", "ThisMonth", "ThisYear"]
+                },
+                "sort": { "enum": ["Relevance", "Recent", "Popular"] },
+                "offset": { "type": "integer", "minimum": 0 },
+                "limit": { "type": ["integer", "null"], "minimum": 1, "maximum": MAX_SEARCH_LIMIT }
+            }
+        })
+    }
+
+    /// Checks the search text and returns the query with its limit filled in.
+    pub fn validated(mut self) -> Result<Self, CoreError> {
+        if self.text.trim().is_empty() {
+            return Err(CoreError::Validation(
+                "search text must not be empty".to_string(),
+            ));
+        }
+        if self.text.len() > MAX_SEARCH_TEXT_LEN {
+            return Err(CoreError::Validation(format!(
+                "search text must be at most {MAX_SEARCH_TEXT_LEN} bytes"
+            )));
+        }
+        self.limit = Some(self.limit().clamp(1, MAX_SEARCH_LIMIT));
+        Ok(self)
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)
+    }
+}
+
+/// An event matching a query.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventHit {
+    /// The event as recorded, with its latest edit attached.
+    pub event: Event,
+    pub rank: f32,
+    /// Excerpt of the content around the matches, HTML-escaped with the
+    /// matched words wrapped in `<mark>`.
+    pub snippet: String,
+    /// Reactions held on the event.
+    pub reactions: u64,
+}
+
+/// A profile matching a query on its handle, display name or bio.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProfileHit {
+    pub profile: Profile,
+    pub rank: f32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub events: Vec<EventHit>,
+    pub profiles: Vec<ProfileHit>,
+    /// Offset of the next page, if any list may have more results.
+    pub next_offset: Option<u32>,
+}
+
+impl SearchResults {
+    /// Results of `query` from the hits of its page.
+    pub fn page(query: &SearchQuery, events: Vec<EventHit>, profiles: Vec<ProfileHit>) -> Self {
+        let limit = query.limit() as usize;
+        let full = events.len() >= limit || profiles.len() >= limit;
+        Self {
+            events,
+            profiles,
+            next_offset: full.then(|| query.offset.saturating_add(limit as u32)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Date, Month};
+
+    fn at(day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(2025, Month::June, day)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+            .assume_utc()
+    }
+
+    #[test]
+    fn test_time_range_since() {
+        let now = at(15, 13, 45);
+        assert_eq!(SearchTimeRange::AnyTime.since(now), None);
+        assert_eq!(SearchTimeRange::PastHour.since(now), Some(at(15, 12, 45)));
+        assert_eq!(SearchTimeRange::Today.since(now), Some(at(15, 0, 0)));
+        assert_eq!(SearchTimeRange::ThisWeek.since(now), Some(at(8, 13, 45)));
+    }
+
+    #[test]
+    fn test_validated_query() {
+        assert!(matches!(
+            SearchQuery::new("  ").validated(),
+            Err(CoreError::Validation(_))
+        ));
+        assert!(SearchQuery::new("a".repeat(MAX_SEARCH_TEXT_LEN + 1))
+            .validated()
+            .is_err());
+
+        let query = SearchQuery {
+            limit: Some(1000),
+            ..SearchQuery::new("rust")
+        };
+        assert_eq!(query.validated().unwrap().limit, Some(MAX_SEARCH_LIMIT));
+        assert_eq!(
+            SearchQuery::new("rust").validated().unwrap().limit,
+            Some(DEFAULT_SEARCH_LIMIT)
+        );
+    }
+}
@@ -45,6 +45,12 @@ pub enum CoreError {
     #[error("rate limited: {0}")]
     RateLimited(String),
 
+    #[error("module not installed: {0}")]
+    UnsupportedModule(String),
+
+    #[error("unsupported action: {0}")]
+    UnsupportedAction(String),
+
     #[error("other error: {0}")]
     Other(String),
 }
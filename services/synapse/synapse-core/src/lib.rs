@@ -16,5 +16,12 @@ pub use domain::crypto::signature::{
     SignatureVerificationResult,
     verify_event_signature,
     verify_event_authentication,
+    require_event_signature,
     requires_authentication,
 };
+#[cfg(feature = "crypto")]
+pub use domain::crypto::signature::{
+    require_event_signature_with,
+    sign_event,
+    verify_event_signature_with,
+};
@@ -6,6 +6,7 @@ pub mod errors;
 use crate::PersistenceError;
 use crate::domain::auth::Session;
 use async_trait::async_trait;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 #[async_trait]
@@ -14,4 +15,25 @@ pub trait SessionRepository: Send + Sync {
     async fn get_session(&self, id: Uuid) -> Result<Session, PersistenceError>;
     async fn revoke_session(&self, id: Uuid) -> Result<Session, PersistenceError>;
     async fn delete_session(&self, id: Uuid) -> Result<(), PersistenceError>;
+    /// Moves the expiry of an active session to `expires_at`, marking it seen
+    /// at `now`. Fails with `NotFound` if the session is revoked or expired.
+    async fn refresh_session(
+        &self,
+        id: Uuid,
+        expires_at: OffsetDateTime,
+        now: OffsetDateTime,
+    ) -> Result<Session, PersistenceError>;
+    /// The sessions of `agent` active at `now`, most recently seen first.
+    async fn list_sessions(
+        &self,
+        agent: &str,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Session>, PersistenceError>;
+    /// Revokes every session of `agent` but `keep`, returning how many were
+    /// revoked.
+    async fn revoke_other_sessions(&self, agent: &str, keep: Uuid)
+    -> Result<u64, PersistenceError>;
+    /// Deletes the sessions expired at `now` or revoked, returning how many
+    /// were deleted.
+    async fn prune_sessions(&self, now: OffsetDateTime) -> Result<u64, PersistenceError>;
 }
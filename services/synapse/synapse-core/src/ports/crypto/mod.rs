@@ -4,6 +4,7 @@
 pub mod errors;
 
 use async_trait::async_trait;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::{PersistenceError, domain::crypto::CryptoChallenge};
@@ -15,4 +16,15 @@ pub trait CryptoRepository: Send + Sync {
         challenge: CryptoChallenge,
     ) -> Result<CryptoChallenge, PersistenceError>;
     async fn get_challenge(&self, id: Uuid) -> Result<Option<CryptoChallenge>, PersistenceError>;
+    /// Removes challenge `id` if it was issued to `agent` and returns it, so
+    /// each challenge can be answered once. Concurrent calls return the
+    /// challenge to at most one.
+    async fn consume_challenge(
+        &self,
+        id: Uuid,
+        agent: &str,
+    ) -> Result<Option<CryptoChallenge>, PersistenceError>;
+    /// Deletes the challenges expired at `now`, returning how many were
+    /// deleted.
+    async fn prune_challenges(&self, now: OffsetDateTime) -> Result<u64, PersistenceError>;
 }
@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::PersistenceError;
+use crate::domain::events::delegations::Delegation;
+
+/// Read side of the delegations materialized from `auth:*` events as they are
+/// recorded.
+#[async_trait]
+pub trait DelegationRepository: Send + Sync {
+    /// The delegations granted by `agent` that are neither revoked nor expired
+    /// at `now`, most recent first.
+    async fn list_delegations(
+        &self,
+        agent: &str,
+        now: OffsetDateTime,
+    ) -> Result<Vec<Delegation>, PersistenceError>;
+    /// Whether `agent` revoked the delegation granted by event `delegation`.
+    async fn is_revoked(&self, delegation: Uuid, agent: &str) -> Result<bool, PersistenceError>;
+}
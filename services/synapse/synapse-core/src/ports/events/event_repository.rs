@@ -1,18 +1,328 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
-use crate::PersistenceError;
-use crate::domain::events::Event;
+use crate::domain::events::{Event, ObjectRef};
+use crate::{CoreError, PersistenceError};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[async_trait::async_trait]
 pub trait EventRepository: Send + Sync {
+    /// Stores `event` and returns the stored copy. Recording an event whose id
+    /// is already taken returns the stored copy if it is a replay of `event`
+    /// and fails with `PersistenceError::Constraint` otherwise.
     async fn record(&self, event: Event) -> Result<Event, PersistenceError>;
+    /// Looks up a stored event by id as recorded, whether or not it has expired
+    /// or been revised.
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, PersistenceError>;
+    /// Prunes the events whose expiration is at or before `now` to tombstones:
+    /// their content, artifacts, links and data are cleared, but the id and
+    /// signed header stay so events referring to them remain intact. Returns
+    /// how many were pruned.
+    async fn prune_expired(&self, now: OffsetDateTime) -> Result<u64, PersistenceError>;
     async fn retrieve(&self, filter: EventFilter) -> Result<Vec<Event>, PersistenceError>;
+    /// Counts the live events of `event_type` per value of metadata `key`, for
+    /// each of `values`. Values without events are left out.
+    async fn count_by_metadata(
+        &self,
+        event_type: &str,
+        key: &str,
+        values: &[String],
+    ) -> Result<HashMap<String, u64>, PersistenceError>;
 }
 
-#[derive(Clone, Debug, Default)]
+/// Largest page of events a single query returns, whatever its `limit`.
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Query over stored events.
+///
+/// All set fields must match. Expired and deleted events are never returned and
+/// edited events carry their latest edit in `latest_edit` (see
+/// `domain::events::revisions`). Results are ordered by `(created_at, id)` and
+/// can be paged with `before`/`after` cursors plus `limit`, which is capped at
+/// `MAX_PAGE_SIZE`. The filter is serializable so `*:list_*` events can carry
+/// it to remote Synapses in their `data` field.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EventFilter {
     pub event_type: Option<String>,
     pub module_kind: Option<String>,
     pub module_slug: Option<String>,
+    pub agent: Option<String>,
+    pub target: Option<ObjectRef>,
+    /// Only events targeting one of these.
+    pub targets: Option<Vec<ObjectRef>>,
+    pub previous: Option<Uuid>,
+    /// Only events created at or after this instant.
+    pub created_since: Option<OffsetDateTime>,
+    /// Only events created strictly before this instant.
+    pub created_until: Option<OffsetDateTime>,
+    /// Every key/value pair must be present in the event metadata.
+    pub metadata: Option<HashMap<String, String>>,
+    /// Only events that sort before this cursor.
+    pub before: Option<EventCursor>,
+    /// Only events that sort after this cursor.
+    pub after: Option<EventCursor>,
+    pub order: EventOrder,
+    /// Page size; unset or larger values mean `MAX_PAGE_SIZE`.
+    pub limit: Option<u32>,
+}
+
+/// Position in the `(created_at, id)` ordering of events.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum EventCursor {
+    /// The position of an existing event.
+    Id(Uuid),
+    /// A point in time; events at exactly this instant are excluded.
+    Timestamp(OffsetDateTime),
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EventOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+}
+
+impl EventFilter {
+    /// Reads the filter carried in the `data` of a `*:list_*` event.
+    /// Events without data yield the default (unfiltered) query. The limit is
+    /// clamped to `MAX_PAGE_SIZE`.
+    pub fn from_event(event: &Event) -> Result<Self, CoreError> {
+        let mut filter: Self = match &event.data {
+            Some(data) if !data.is_empty() => serde_json::from_slice(data)
+                .map_err(|e| CoreError::Validation(format!("invalid event filter: {e}")))?,
+            _ => Self::default(),
+        };
+        filter.limit = Some(filter.page_limit());
+        Ok(filter)
+    }
+
+    /// The number of events a page holds at most: `limit`, capped at
+    /// `MAX_PAGE_SIZE`.
+    pub fn page_limit(&self) -> u32 {
+        self.limit.map_or(MAX_PAGE_SIZE, |limit| limit.min(MAX_PAGE_SIZE))
+    }
+
+    /// JSON Schema of the filter carried in the `data` of `*:list_*` events.
+    pub fn schema() -> serde_json::Value {
+        let cursor = serde_json::json!({
+            "oneOf": [
+                { "type": "object", "properties": { "Id": { "type": "string", "format": "uuid" } } },
+                { "type": "object", "properties": { "Timestamp": {} } }
+            ]
+        });
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "event_type": { "type": ["string", "null"] },
+                "module_kind": { "type": ["string", "null"] },
+                "module_slug": { "type": ["string", "null"] },
+                "agent": { "type": ["string", "null"] },
+                "target": {},
+                "targets": { "type": ["array", "null"] },
+                "previous": { "type": ["string", "null"], "format": "uuid" },
+                "created_since": {},
+                "created_until": {},
+                "metadata": { "type": ["object", "null"], "additionalProperties": { "type": "string" } },
+                "before": cursor,
+                "after": cursor,
+                "order": { "enum": ["NewestFirst", "OldestFirst"] },
+                "limit": { "type": ["integer", "null"], "minimum": 0, "maximum": MAX_PAGE_SIZE }
+            }
+        })
+    }
+
+    /// Encodes the filter for the `data` field of a `*:list_*` event.
+    pub fn to_data(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Whether `event` satisfies every field except the cursors, ordering and limit.
+    pub fn matches(&self, event: &Event) -> bool {
+        fn eq<T: PartialEq + ?Sized>(want: Option<&T>, have: Option<&T>) -> bool {
+            want.is_none() || want == have
+        }
+
+        eq(self.event_type.as_deref(), Some(event.event_type.as_str()))
+            && eq(self.module_kind.as_deref(), event.module_kind.as_deref())
+            && eq(self.module_slug.as_deref(), event.module_slug.as_deref())
+            && eq(self.agent.as_deref(), Some(event.agent.as_str()))
+            && eq(self.target.as_ref(), event.target.as_ref())
+            && self.targets.as_ref().is_none_or(|targets| {
+                event.target.as_ref().is_some_and(|target| targets.contains(target))
+            })
+            && eq(self.previous.as_ref(), event.previous.as_ref())
+            && self.created_since.is_none_or(|since| event.created_at >= since)
+            && self.created_until.is_none_or(|until| event.created_at < until)
+            && self.metadata.as_ref().is_none_or(|wanted| {
+                let have = event.metadata.as_ref();
+                wanted
+                    .iter()
+                    .all(|(k, v)| have.and_then(|m| m.get(k)) == Some(v))
+            })
+    }
+
+    /// Applies the whole filter to an in-memory set of events.
+    ///
+    /// Reference semantics for repository implementations. An `Id` cursor that
+    /// does not resolve to one of `events` matches nothing.
+    pub fn apply(&self, mut events: Vec<Event>) -> Vec<Event> {
+        let position = |cursor: &EventCursor| match cursor {
+            EventCursor::Id(id) => events
+                .iter()
+                .find(|e| e.id == *id)
+                .map(|e| CursorPosition::Event(e.created_at, e.id)),
+            EventCursor::Timestamp(ts) => Some(CursorPosition::Instant(*ts)),
+        };
+        let before = self.before.as_ref().map(position);
+        let after = self.after.as_ref().map(position);
+
+        events.retain(|e| {
+            self.matches(e)
+                && before.as_ref().is_none_or(|c| {
+                    c.as_ref()
+                        .is_some_and(|c| c.compare(e) == Ordering::Less)
+                })
+                && after.as_ref().is_none_or(|c| {
+                    c.as_ref()
+                        .is_some_and(|c| c.compare(e) == Ordering::Greater)
+                })
+        });
+        events.sort_by_key(|e| (e.created_at, e.id));
+        if self.order == EventOrder::NewestFirst {
+            events.reverse();
+        }
+        events.truncate(self.page_limit() as usize);
+        events
+    }
+}
+
+enum CursorPosition {
+    Event(OffsetDateTime, Uuid),
+    Instant(OffsetDateTime),
+}
+
+impl CursorPosition {
+    /// Orders `event` relative to the cursor.
+    fn compare(&self, event: &Event) -> Ordering {
+        match self {
+            CursorPosition::Event(ts, id) => (event.created_at, event.id).cmp(&(*ts, *id)),
+            CursorPosition::Instant(ts) => event.created_at.cmp(ts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    fn events() -> Vec<Event> {
+        let start = OffsetDateTime::from_unix_timestamp(1_736_380_800).unwrap();
+        (0..5)
+            .map(|i| {
+                let mut event = Event::new()
+                    .with_event_type("posts:create_post")
+                    .with_module_slug(if i % 2 == 0 { "general" } else { "memes" })
+                    .with_agent(format!("agent-{i}"))
+                    .build();
+                event.created_at = start + Duration::minutes(i);
+                event
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pages_newest_first_with_cursor() {
+        let all = events();
+        let first = EventFilter {
+            limit: Some(2),
+            ..Default::default()
+        }
+        .apply(all.clone());
+        assert_eq!(first.iter().map(|e| e.id).collect::<Vec<_>>(), vec![all[4].id, all[3].id]);
+
+        let second = EventFilter {
+            limit: Some(2),
+            before: Some(EventCursor::Id(first[1].id)),
+            ..Default::default()
+        }
+        .apply(all.clone());
+        assert_eq!(second.iter().map(|e| e.id).collect::<Vec<_>>(), vec![all[2].id, all[1].id]);
+    }
+
+    #[test]
+    fn test_filters_by_slug_and_time_range() {
+        let all = events();
+        let filtered = EventFilter {
+            module_slug: Some("general".to_string()),
+            created_since: Some(all[1].created_at),
+            order: EventOrder::OldestFirst,
+            ..Default::default()
+        }
+        .apply(all.clone());
+        assert_eq!(filtered.iter().map(|e| e.id).collect::<Vec<_>>(), vec![all[2].id, all[4].id]);
+    }
+
+    #[test]
+    fn test_filters_by_any_of_targets() {
+        let mut all = events();
+        for (event, target) in all.iter_mut().zip(["a", "b", "c", "a", "d"]) {
+            event.target = Some(ObjectRef::Agent(target.to_string()));
+        }
+        let filtered = EventFilter {
+            targets: Some(vec![
+                ObjectRef::Agent("a".to_string()),
+                ObjectRef::Agent("c".to_string()),
+            ]),
+            order: EventOrder::OldestFirst,
+            ..Default::default()
+        }
+        .apply(all.clone());
+        assert_eq!(
+            filtered.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![all[0].id, all[2].id, all[3].id]
+        );
+    }
+
+    #[test]
+    fn test_round_trips_through_event_data() {
+        let filter = EventFilter {
+            module_slug: Some("general".to_string()),
+            before: Some(EventCursor::Timestamp(OffsetDateTime::UNIX_EPOCH)),
+            limit: Some(20),
+            ..Default::default()
+        };
+        let event = Event::new()
+            .with_event_type("posts:list_posts")
+            .with_data(filter.to_data())
+            .build();
+
+        let decoded = EventFilter::from_event(&event).unwrap();
+        assert_eq!(decoded.module_slug.as_deref(), Some("general"));
+        assert_eq!(decoded.before, filter.before);
+        assert_eq!(decoded.limit, Some(20));
+    }
+
+    #[test]
+    fn test_limit_is_capped() {
+        let unbounded = Event::new().with_event_type("posts:list_posts").build();
+        let huge = Event::new()
+            .with_event_type("posts:list_posts")
+            .with_data(
+                EventFilter {
+                    limit: Some(u32::MAX),
+                    ..Default::default()
+                }
+                .to_data(),
+            )
+            .build();
+
+        assert_eq!(EventFilter::from_event(&unbounded).unwrap().limit, Some(MAX_PAGE_SIZE));
+        assert_eq!(EventFilter::from_event(&huge).unwrap().limit, Some(MAX_PAGE_SIZE));
+    }
 }
@@ -2,10 +2,13 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 pub mod errors;
+pub mod subscriptions;
 
 use async_trait::async_trait;
 
 use crate::CoreError;
+use crate::domain::modules::ModuleCapability;
+use crate::ports::events::event_repository::EventCursor;
 use crate::{TransportError, domain::events::Event};
 
 // Outbound port
@@ -18,8 +21,48 @@ pub trait FederationTransport: Send + Sync {
     ) -> Result<Vec<Event>, TransportError>;
 }
 
+// Outbound port for live event subscriptions between Synapses
+#[async_trait]
+pub trait EventSubscriptions: Send + Sync {
+    /// Follows the events a remote Synapse records for a module, or one slug of it.
+    /// Events after `since` are replayed first, and missed events are replayed
+    /// from the last one seen whenever the connection is re-established. Follows
+    /// are kept in a `SubscriptionStore` and resumed after a restart.
+    async fn subscribe(
+        &self,
+        synapse_public_key: String,
+        module_kind: String,
+        module_slug: Option<String>,
+        since: Option<EventCursor>,
+    ) -> Result<(), TransportError>;
+
+    async fn unsubscribe(
+        &self,
+        synapse_public_key: String,
+        module_kind: String,
+        module_slug: Option<String>,
+    ) -> Result<(), TransportError>;
+
+    /// Publishes a locally recorded event to the Synapses following its module.
+    async fn publish(&self, event: Event) -> Result<(), TransportError>;
+}
+
 // Inbound port
 #[async_trait]
 pub trait MessageHandler: Send + Sync {
     async fn handle_message(&self, event: Event) -> Result<Vec<Event>, CoreError>;
+
+    /// Receives an event published by a followed Synapse.
+    async fn handle_publication(
+        &self,
+        _synapse_public_key: String,
+        _event: Event,
+    ) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    /// Modules installed on this Synapse, advertised to peers when they connect.
+    fn module_capabilities(&self) -> Vec<ModuleCapability> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::PersistenceError;
+use crate::ports::events::event_repository::EventCursor;
+
+/// A module of a remote Synapse this node follows, kept across restarts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FollowedModule {
+    /// Topic the module's events are published on.
+    pub topic: String,
+    pub synapse_public_key: String,
+    pub module_kind: String,
+    pub module_slug: Option<String>,
+    /// Cursor replay starts after when no event has been seen yet.
+    pub since: Option<EventCursor>,
+    /// Creation time of the newest event delivered so far.
+    pub newest_seen: Option<OffsetDateTime>,
+}
+
+/// Durable record of the modules followed through `EventSubscriptions`, so
+/// follows and their replay position survive a restart.
+#[async_trait]
+pub trait SubscriptionStore: Send + Sync {
+    /// Every followed module, to subscribe to again on startup.
+    async fn list_subscriptions(&self) -> Result<Vec<FollowedModule>, PersistenceError>;
+
+    /// Stores `subscription`, replacing the one on the same topic.
+    async fn save_subscription(&self, subscription: &FollowedModule) -> Result<(), PersistenceError>;
+
+    /// Moves the replay position of the subscription on `topic` up to
+    /// `created_at`, unless it is already past it.
+    async fn record_seen(
+        &self,
+        topic: &str,
+        created_at: OffsetDateTime,
+    ) -> Result<(), PersistenceError>;
+
+    async fn remove_subscription(&self, topic: &str) -> Result<(), PersistenceError>;
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+
+use crate::PersistenceError;
+use crate::domain::events::{Event, PublicKey};
+
+/// Read side of the `auth:*` key events recorded. Nothing is verified here;
+/// the events are replayed into a `KeyHistory` by the caller.
+#[async_trait]
+pub trait KeyRepository: Send + Sync {
+    /// The `auth:rotate_key` and `auth:set_recovery_key` events of `agent`,
+    /// oldest first.
+    async fn key_events(&self, agent: &str) -> Result<Vec<Event>, PersistenceError>;
+    /// The agents with an `auth:rotate_key` event to `key`.
+    async fn agents_rotated_to(&self, key: &str) -> Result<Vec<PublicKey>, PersistenceError>;
+}
@@ -7,11 +7,15 @@ pub mod auth;
 pub mod channels;
 pub mod config;
 pub mod crypto;
+pub mod delegations;
 pub mod events;
 pub mod federation;
+pub mod keys;
 pub mod modules;
 pub mod peers;
 pub mod persistence;
 pub mod profiles;
+pub mod reactions;
+pub mod search;
 pub mod settings;
 pub mod synapses;
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+use crate::domain::modules::EventTypeSpec;
+use crate::ports::persistence::UnitOfWork;
 use crate::{CoreError, domain::events::Event};
 use async_trait::async_trait;
 use std::sync::Arc;
@@ -9,7 +11,20 @@ use std::sync::Arc;
 pub trait Module: Send + Sync {
     fn kind(&self) -> Result<String, CoreError>;
     fn version(&self) -> Result<String, CoreError>;
+    /// Every event type this module handles. Events of other types are
+    /// rejected before reaching `handle_event`.
+    fn event_types(&self) -> Vec<EventTypeSpec>;
     async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError>;
+    /// Handles a write event as part of `uow`. Side effects written through it
+    /// commit or roll back together with the event itself; modules that only
+    /// append to the event log can keep the default.
+    async fn handle_event_in(
+        &self,
+        event: &Event,
+        _uow: &mut dyn UnitOfWork,
+    ) -> Result<Vec<Event>, CoreError> {
+        self.handle_event(event).await
+    }
 }
 
 #[async_trait]
@@ -17,4 +32,8 @@ pub trait ModuleRegistry: Send + Sync {
     fn register(&self, module: Arc<dyn Module>) -> Result<(), CoreError>;
     fn get(&self, kind: &str) -> Option<Arc<dyn Module>>;
     fn installed_kinds(&self) -> Vec<String>;
+    /// Declaration of `event_type` by the module installed as `module_kind`.
+    fn event_type(&self, module_kind: &str, event_type: &str) -> Option<EventTypeSpec>;
+    /// Every event type handled by an installed module.
+    fn event_types(&self) -> Vec<EventTypeSpec>;
 }
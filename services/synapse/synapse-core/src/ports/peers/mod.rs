@@ -1,2 +1,5 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
+
+#[allow(clippy::module_inception)]
+pub mod peers;
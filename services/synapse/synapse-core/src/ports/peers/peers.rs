@@ -1,2 +1,41 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
-// Copyright © 2025 Malifex LLC and contributors
\ No newline at end of file
+// Copyright © 2025 Malifex LLC and contributors
+
+use crate::PersistenceError;
+use crate::domain::peers::Peer;
+use async_trait::async_trait;
+
+/// Durable record of the Synapses this node has seen, so it can find them
+/// again after a restart.
+#[async_trait]
+pub trait PeerStore: Send + Sync {
+    async fn get_peer(&self, peer_id: &str) -> Result<Option<Peer>, PersistenceError>;
+
+    async fn get_peer_by_public_key(
+        &self,
+        public_key: &str,
+    ) -> Result<Option<Peer>, PersistenceError>;
+
+    /// Peers to redial on startup, best reputation first.
+    async fn list_peers(&self, limit: u32) -> Result<Vec<Peer>, PersistenceError>;
+
+    /// Records a successful connection to `peer_id` reached at `address`, resetting
+    /// its failure count.
+    async fn record_connected(
+        &self,
+        peer_id: &str,
+        address: Option<&str>,
+    ) -> Result<Peer, PersistenceError>;
+
+    /// Associates `peer_id` with the Synapse public key it reported.
+    async fn record_public_key(
+        &self,
+        peer_id: &str,
+        public_key: &str,
+    ) -> Result<(), PersistenceError>;
+
+    /// Records a failed dial to `peer_id` and returns the updated peer.
+    async fn record_failure(&self, peer_id: &str) -> Result<Peer, PersistenceError>;
+
+    async fn remove_peer(&self, peer_id: &str) -> Result<(), PersistenceError>;
+}
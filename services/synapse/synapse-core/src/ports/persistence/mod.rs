@@ -5,10 +5,14 @@ pub mod errors;
 
 use crate::errors::CoreError;
 use crate::ports::events::event_repository::EventRepository;
+use crate::ports::profiles::profile_repository::ProfilesDocStore;
 
+/// Writes that commit or roll back together. Dropping a unit of work without
+/// committing it discards its writes.
 #[async_trait::async_trait]
 pub trait UnitOfWork: Send {
     fn events(&mut self) -> &mut dyn EventRepository;
+    fn profile_docs(&mut self) -> &mut dyn ProfilesDocStore;
 
     async fn commit(self: Box<Self>) -> Result<(), CoreError>;
     async fn rollback(self: Box<Self>) -> Result<(), CoreError>;
@@ -24,6 +24,8 @@ pub trait ProfilesDocStore: Send + Sync {
     async fn get_doc(&self, public_key: &str) -> Result<Option<Vec<u8>>, PersistenceError>;
     async fn upsert_doc(&self, public_key: &str, doc_bytes: &[u8]) -> Result<(), PersistenceError>;
     async fn delete_doc(&self, public_key: &str) -> Result<(), PersistenceError>;
+    /// Public keys of every profile hosted on this Synapse.
+    async fn list_public_keys(&self) -> Result<Vec<String>, PersistenceError>;
 }
 
 #[async_trait]
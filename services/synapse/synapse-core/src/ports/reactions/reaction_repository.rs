@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::PersistenceError;
+use crate::domain::events::reactions::ReactionSummary;
+
+/// Read side of the reactions materialized from `reactions:*` events as they
+/// are recorded.
+#[async_trait]
+pub trait ReactionsRepository: Send + Sync {
+    /// The reactions held on each of `targets`, one summary per emoji, most
+    /// used first. `reacted` is set for the emoji `viewer` reacted with.
+    /// Targets without reactions are left out.
+    async fn summaries(
+        &self,
+        targets: &[Uuid],
+        viewer: Option<&str>,
+    ) -> Result<HashMap<Uuid, Vec<ReactionSummary>>, PersistenceError>;
+}
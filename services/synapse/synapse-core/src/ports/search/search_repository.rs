@@ -0,0 +1,24 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use async_trait::async_trait;
+
+use crate::PersistenceError;
+use crate::domain::search::{EventHit, ProfileHit, SearchQuery};
+
+/// Full-text index over the events and profiles held by this Synapse.
+///
+/// Both searches take one page of `query`, `offset` and `limit` included,
+/// and ignore its `scope`.
+#[async_trait]
+pub trait SearchRepository: Send + Sync {
+    /// Live events whose content matches, sorted by `query.sort`. Revisions
+    /// are not returned themselves; an edited event matches on its latest
+    /// content.
+    async fn search_events(&self, query: &SearchQuery) -> Result<Vec<EventHit>, PersistenceError>;
+    /// Profiles whose handle, display name or bio matches, best match first.
+    async fn search_profiles(
+        &self,
+        query: &SearchQuery,
+    ) -> Result<Vec<ProfileHit>, PersistenceError>;
+}
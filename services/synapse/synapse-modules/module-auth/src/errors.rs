@@ -9,6 +9,8 @@ use thiserror::Error;
 pub enum ModuleAuthError {
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
     #[error("Not found: {0}")]
@@ -42,6 +44,12 @@ impl From<synapse_core::errors::CoreError> for ModuleAuthError {
             CoreError::RateLimited(_) => {
                 ModuleAuthError::BadRequest("RateLimited error".to_string())
             }
+            CoreError::UnsupportedModule(_) => {
+                ModuleAuthError::BadRequest("UnsupportedModule error".to_string())
+            }
+            CoreError::UnsupportedAction(_) => {
+                ModuleAuthError::BadRequest("UnsupportedAction error".to_string())
+            }
             CoreError::Other(_) => ModuleAuthError::Other("Other error".to_string()),
         }
     }
@@ -51,6 +59,7 @@ impl IntoResponse for ModuleAuthError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
             ModuleAuthError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ModuleAuthError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             ModuleAuthError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             ModuleAuthError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             ModuleAuthError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
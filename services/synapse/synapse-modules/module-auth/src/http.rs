@@ -4,29 +4,46 @@
 #[cfg(feature = "ssr")]
 mod server {
     use crate::errors::ModuleAuthError;
-    use crate::service::{create_challenge, verify_challenge};
+    use crate::service::{
+        create_challenge, delegate, get_key_history, list_delegations, list_sessions, logout,
+        parse_public_key, refresh_session, revoke_delegation, revoke_other_sessions,
+        revoke_session, rotate_key, set_recovery_key, verify_challenge,
+    };
+    use crate::session::{
+        AuthenticatedAgent, cleared_session_cookie, session_cookie, user_agent,
+    };
     use crate::types::{
-        AuthDeps, ChallengeRequest, ChallengeResponse, LogoutRequest, VerifyChallengeRequest,
+        AuthDeps, ChallengeRequest, ChallengeResponse, DelegateRequest, DelegateResponse,
+        KeyHistoryResponse, ListDelegationsResponse, ListSessionsResponse,
+        RefreshSessionResponse, RevokeDelegationRequest, RevokeSessionsResponse,
+        RotateKeyRequest, SetRecoveryKeyRequest, VerifyChallengeRequest,
         VerifyChallengeResponse,
     };
     use async_trait::async_trait;
     use axum::{
         Json,
-        extract::{FromRef, State},
+        extract::{FromRef, Path, State},
         http::{HeaderMap, StatusCode, header},
-        routing::post,
+        routing::{delete, get, post},
     };
     use std::sync::Arc;
     use synapse_core::ports::modules::Module;
     use synapse_core::{
         CoreError,
-        domain::events::Event,
+        domain::{
+            events::Event,
+            events::delegations::{DELEGATE, DelegationChange, REVOKE_DELEGATION},
+            events::keys::{KeyChange, ROTATE_KEY, SET_RECOVERY_KEY},
+            modules::EventTypeSpec,
+        },
         ports::{
             auth::SessionRepository,
             crypto::{CryptoRepository, errors::CryptoError},
         },
     };
+    use time::OffsetDateTime;
     use tracing::debug;
+    use uuid::Uuid;
 
     pub struct AuthModule {
         kind: String,
@@ -57,9 +74,32 @@ mod server {
         fn version(&self) -> Result<String, CoreError> {
             Ok(self.version.clone())
         }
+        // Logins and sessions are only served over HTTP to local clients;
+        // delegations and key changes federate so signatures are checked the
+        // same everywhere
+        fn event_types(&self) -> Vec<EventTypeSpec> {
+            vec![
+                // `target` is the delegate key, `content` the scope
+                EventTypeSpec::command(DELEGATE),
+                EventTypeSpec::command(REVOKE_DELEGATION),
+                // `target` is the new key, `content` its proof for rotations
+                EventTypeSpec::command(ROTATE_KEY),
+                EventTypeSpec::command(SET_RECOVERY_KEY),
+            ]
+        }
         async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
             match event.event_type.as_str() {
-                _ => Ok(vec![]),
+                // Recording the event updates the delegations of its agent
+                DELEGATE | REVOKE_DELEGATION => {
+                    DelegationChange::of(event)?;
+                    Ok(vec![event.clone()])
+                }
+                // Recording the event extends the key history of its agent
+                ROTATE_KEY | SET_RECOVERY_KEY => {
+                    KeyChange::of(event)?;
+                    Ok(vec![event.clone()])
+                }
+                _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
             }
         }
     }
@@ -72,7 +112,25 @@ mod server {
         axum::Router::new()
             .route("/auth/challenges", post(request_challenge_http))
             .route("/auth/login", post(verify_challenge_http))
+            .route("/auth/refresh", post(refresh_session_http))
             .route("/auth/logout", post(handle_logout_http))
+            .route("/auth/sessions", get(list_sessions_http))
+            .route("/auth/sessions/{session_id}", delete(revoke_session_http))
+            .route(
+                "/auth/sessions/revoke_others",
+                post(revoke_other_sessions_http),
+            )
+            .route(
+                "/auth/delegations",
+                get(list_delegations_http).post(delegate_http),
+            )
+            .route(
+                "/auth/delegations/{delegation_id}/revoke",
+                post(revoke_delegation_http),
+            )
+            .route("/auth/keys/rotate", post(rotate_key_http))
+            .route("/auth/keys/recovery", post(set_recovery_key_http))
+            .route("/auth/keys/{agent}", get(get_key_history_http))
     }
 
     async fn request_challenge_http(
@@ -80,30 +138,33 @@ mod server {
         Json(body): Json<ChallengeRequest>,
     ) -> Result<(StatusCode, Json<ChallengeResponse>), ModuleAuthError> {
         debug!("request_challenge_http called!");
-        let challenge = create_challenge(body.public_key).await.unwrap();
+        parse_public_key(&body.public_key)?;
+        let challenge = create_challenge(body.public_key).await?;
         deps.crypto_repo
             .store_challenge(challenge.clone())
             .await
-            .unwrap();
+            .map_err(CoreError::from)?;
         Ok((StatusCode::CREATED, Json(ChallengeResponse { challenge })))
     }
 
+    /// Headers setting the session cookie to `value`.
+    fn set_cookie(value: &str) -> Result<HeaderMap, ModuleAuthError> {
+        let value = header::HeaderValue::from_str(value)
+            .map_err(|e| ModuleAuthError::Internal(e.to_string()))?;
+        Ok(HeaderMap::from_iter([(header::SET_COOKIE, value)]))
+    }
+
     async fn verify_challenge_http(
         State(deps): State<AuthDeps>,
+        request_headers: HeaderMap,
         Json(body): Json<VerifyChallengeRequest>,
     ) -> Result<(StatusCode, HeaderMap, Json<VerifyChallengeResponse>), ModuleAuthError> {
-        let challenge_response = verify_challenge(deps, body).await.unwrap();
-        let token = challenge_response.session.id.clone();
-        let ttl_secs = 14_400;
-        // TODO add Secure; to cookie for prod
-        let cookie_value =
-            format!("menexus_session={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={ttl_secs}",);
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            header::SET_COOKIE,
-            header::HeaderValue::from_str(&cookie_value).unwrap(),
-        );
+        let challenge_response =
+            verify_challenge(deps, body, user_agent(&request_headers)).await?;
+        let headers = set_cookie(&session_cookie(
+            &challenge_response.session,
+            OffsetDateTime::now_utc(),
+        ))?;
 
         Ok((
             StatusCode::CREATED,
@@ -114,12 +175,102 @@ mod server {
         ))
     }
 
+    async fn refresh_session_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+    ) -> Result<(StatusCode, HeaderMap, Json<RefreshSessionResponse>), ModuleAuthError> {
+        let session = refresh_session(deps, &agent).await?;
+        let headers = set_cookie(&session_cookie(&session, OffsetDateTime::now_utc()))?;
+        Ok((StatusCode::OK, headers, Json(RefreshSessionResponse { session })))
+    }
+
     async fn handle_logout_http(
         State(deps): State<AuthDeps>,
-        Json(body): Json<LogoutRequest>,
-    ) -> Result<(), ModuleAuthError> {
+        agent: Option<AuthenticatedAgent>,
+    ) -> Result<(StatusCode, HeaderMap), ModuleAuthError> {
         debug!("handle_logout_http called!");
-        Ok(())
+        if let Some(agent) = agent {
+            logout(deps, &agent).await?;
+        }
+        Ok((StatusCode::NO_CONTENT, set_cookie(&cleared_session_cookie())?))
+    }
+
+    async fn list_sessions_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+    ) -> Result<(StatusCode, Json<ListSessionsResponse>), ModuleAuthError> {
+        let sessions = list_sessions(deps, &agent).await?;
+        Ok((StatusCode::OK, Json(sessions)))
+    }
+
+    async fn revoke_session_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+        Path(session_id): Path<Uuid>,
+    ) -> Result<StatusCode, ModuleAuthError> {
+        revoke_session(deps, &agent, session_id).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn revoke_other_sessions_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+    ) -> Result<(StatusCode, Json<RevokeSessionsResponse>), ModuleAuthError> {
+        let revoked = revoke_other_sessions(deps, &agent).await?;
+        Ok((StatusCode::OK, Json(revoked)))
+    }
+
+    async fn list_delegations_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+    ) -> Result<(StatusCode, Json<ListDelegationsResponse>), ModuleAuthError> {
+        let delegations = list_delegations(deps, &agent).await?;
+        Ok((StatusCode::OK, Json(delegations)))
+    }
+
+    async fn delegate_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+        Json(body): Json<DelegateRequest>,
+    ) -> Result<(StatusCode, Json<DelegateResponse>), ModuleAuthError> {
+        let delegation = delegate(deps, &agent, body).await?;
+        Ok((StatusCode::CREATED, Json(delegation)))
+    }
+
+    async fn revoke_delegation_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+        Path(delegation_id): Path<Uuid>,
+        Json(body): Json<RevokeDelegationRequest>,
+    ) -> Result<StatusCode, ModuleAuthError> {
+        revoke_delegation(deps, &agent, delegation_id, body).await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    async fn get_key_history_http(
+        State(deps): State<AuthDeps>,
+        Path(agent): Path<String>,
+    ) -> Result<(StatusCode, Json<KeyHistoryResponse>), ModuleAuthError> {
+        let history = get_key_history(deps, &agent).await?;
+        Ok((StatusCode::OK, Json(history)))
+    }
+
+    async fn rotate_key_http(
+        State(deps): State<AuthDeps>,
+        agent: Option<AuthenticatedAgent>,
+        Json(body): Json<RotateKeyRequest>,
+    ) -> Result<(StatusCode, Json<KeyHistoryResponse>), ModuleAuthError> {
+        let history = rotate_key(deps, agent.as_ref(), body).await?;
+        Ok((StatusCode::OK, Json(history)))
+    }
+
+    async fn set_recovery_key_http(
+        State(deps): State<AuthDeps>,
+        agent: AuthenticatedAgent,
+        Json(body): Json<SetRecoveryKeyRequest>,
+    ) -> Result<(StatusCode, Json<KeyHistoryResponse>), ModuleAuthError> {
+        let history = set_recovery_key(deps, &agent, body).await?;
+        Ok((StatusCode::OK, Json(history)))
     }
 }
 
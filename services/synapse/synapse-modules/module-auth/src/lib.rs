@@ -10,13 +10,15 @@ pub mod http;
 #[cfg(feature = "ssr")]
 pub mod service;
 
+#[cfg(feature = "ssr")]
+pub mod session;
+
 #[cfg(any(feature = "ssr", feature = "hydrate"))]
 pub mod server_fns;
 
 #[cfg(any(feature = "ssr", feature = "hydrate"))]
 pub mod types;
 
-#[cfg(feature = "hydrate")]
 pub mod signing;
 
 #[cfg(feature = "hydrate")]
@@ -1,18 +1,26 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
-use crate::types::{ChallengeRequest, ChallengeResponse, VerifyChallengeRequest, VerifyChallengeResponse};
+use crate::types::{
+    ChallengeRequest, ChallengeResponse, DelegateRequest, DelegateResponse, KeyHistoryResponse,
+    ListDelegationsResponse, ListSessionsResponse, RefreshSessionResponse,
+    RevokeDelegationRequest, RevokeSessionsResponse, RotateKeyRequest, SetRecoveryKeyRequest,
+    VerifyChallengeRequest, VerifyChallengeResponse,
+};
 use leptos::prelude::*;
+use uuid::Uuid;
 
 #[server(RequestChallengeServer, "/api")]
 pub async fn request_challenge_server(
     challenge_request: ChallengeRequest,
 ) -> Result<ChallengeResponse, ServerFnError> {
-    use crate::service::create_challenge;
+    use crate::service::{create_challenge, parse_public_key};
     use crate::types::AuthDeps;
 
     let deps: AuthDeps = expect_context();
 
+    parse_public_key(&challenge_request.public_key)
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
     let challenge = create_challenge(challenge_request.public_key)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
@@ -27,25 +35,208 @@ pub async fn request_challenge_server(
 pub async fn verify_challenge_server(
     challenge_request: VerifyChallengeRequest,
 ) -> Result<VerifyChallengeResponse, ServerFnError> {
-    use axum::http::header;
     use crate::service::verify_challenge;
+    use crate::session::{session_cookie, user_agent};
     use crate::types::AuthDeps;
-    use leptos_axum::ResponseOptions;
+    use leptos_axum::extract;
+    use time::OffsetDateTime;
 
     let deps: AuthDeps = expect_context();
-    let response = expect_context::<ResponseOptions>();
-    let challenge_response = verify_challenge(deps, challenge_request)
+    let headers: axum::http::HeaderMap = extract().await?;
+    let challenge_response = verify_challenge(deps, challenge_request, user_agent(&headers))
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    set_cookie(&session_cookie(
+        &challenge_response.session,
+        OffsetDateTime::now_utc(),
+    ))?;
+
+    Ok(challenge_response)
+}
+
+/// Extend the current session by its lifetime from now
+#[server(RefreshSessionServer, "/api")]
+pub async fn refresh_session_server() -> Result<RefreshSessionResponse, ServerFnError> {
+    use crate::service::refresh_session;
+    use crate::session::{authenticated_agent, session_cookie};
+    use crate::types::AuthDeps;
+    use time::OffsetDateTime;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    let session = refresh_session(deps, &agent)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
-    let token = challenge_response.session.id.clone();
-    let ttl_secs = 14_400;
-    // TODO add Secure; to cookie for prod
-    let cookie_value =
-        format!("menexus_session={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={ttl_secs}",);
+    set_cookie(&session_cookie(&session, OffsetDateTime::now_utc()))?;
+    Ok(RefreshSessionResponse { session })
+}
+
+/// Revoke the current session and clear its cookie
+#[server(LogoutServer, "/api")]
+pub async fn logout_server() -> Result<(), ServerFnError> {
+    use crate::service::logout;
+    use crate::session::{AuthenticatedAgent, cleared_session_cookie};
+    use crate::types::AuthDeps;
+    use leptos_axum::extract;
+
+    let agent: Option<AuthenticatedAgent> = extract().await?;
+    if let Some(agent) = agent {
+        let deps: AuthDeps = expect_context();
+        logout(deps, &agent)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
+    }
+    set_cookie(&cleared_session_cookie())
+}
+
+/// List the active sessions of the logged in agent
+#[server(ListSessionsServer, "/api")]
+pub async fn list_sessions_server() -> Result<ListSessionsResponse, ServerFnError> {
+    use crate::service::list_sessions;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    list_sessions(deps, &agent)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Revoke one of the sessions of the logged in agent
+#[server(RevokeSessionServer, "/api")]
+pub async fn revoke_session_server(session_id: Uuid) -> Result<(), ServerFnError> {
+    use crate::service::revoke_session;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    revoke_session(deps, &agent, session_id)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Revoke every session of the logged in agent but the current one
+#[server(RevokeOtherSessionsServer, "/api")]
+pub async fn revoke_other_sessions_server() -> Result<RevokeSessionsResponse, ServerFnError> {
+    use crate::service::revoke_other_sessions;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    revoke_other_sessions(deps, &agent)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// List the delegations of the logged in agent still in force
+#[server(ListDelegationsServer, "/api")]
+pub async fn list_delegations_server() -> Result<ListDelegationsResponse, ServerFnError> {
+    use crate::service::list_delegations;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    list_delegations(deps, &agent)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Let a device or app key sign events for the logged in agent
+#[server(DelegateServer, "/api")]
+pub async fn delegate_server(request: DelegateRequest) -> Result<DelegateResponse, ServerFnError> {
+    use crate::service::delegate;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    delegate(deps, &agent, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Revoke one of the delegations of the logged in agent
+#[server(RevokeDelegationServer, "/api")]
+pub async fn revoke_delegation_server(
+    delegation_id: Uuid,
+    request: RevokeDelegationRequest,
+) -> Result<(), ServerFnError> {
+    use crate::service::revoke_delegation;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    revoke_delegation(deps, &agent, delegation_id, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// The key history of `agent`, or of the logged in agent, as recorded on this
+/// Synapse
+#[server(KeyHistoryServer, "/api")]
+pub async fn key_history_server(
+    agent: Option<String>,
+) -> Result<KeyHistoryResponse, ServerFnError> {
+    use crate::service::get_key_history;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = match agent {
+        Some(agent) => agent,
+        None => authenticated_agent().await?.agent,
+    };
+    let deps: AuthDeps = expect_context();
+    get_key_history(deps, &agent)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Move an agent to a new key, signed with its key in force or its recovery key
+#[server(RotateKeyServer, "/api")]
+pub async fn rotate_key_server(request: RotateKeyRequest) -> Result<KeyHistoryResponse, ServerFnError> {
+    use crate::service::rotate_key;
+    use crate::session::AuthenticatedAgent;
+    use crate::types::AuthDeps;
+    use leptos_axum::extract;
+
+    let agent: Option<AuthenticatedAgent> = extract().await?;
+    let deps: AuthDeps = expect_context();
+    rotate_key(deps, agent.as_ref(), request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Commit the logged in agent to a recovery key
+#[server(SetRecoveryKeyServer, "/api")]
+pub async fn set_recovery_key_server(
+    request: SetRecoveryKeyRequest,
+) -> Result<KeyHistoryResponse, ServerFnError> {
+    use crate::service::set_recovery_key;
+    use crate::session::authenticated_agent;
+    use crate::types::AuthDeps;
+
+    let agent = authenticated_agent().await?;
+    let deps: AuthDeps = expect_context();
+    set_recovery_key(deps, &agent, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Set the session cookie of the server function response to `value`.
+#[cfg(feature = "ssr")]
+fn set_cookie(value: &str) -> Result<(), ServerFnError> {
+    use axum::http::header;
+    use leptos_axum::ResponseOptions;
+
+    let response = expect_context::<ResponseOptions>();
     response.insert_header(
         header::SET_COOKIE,
-        header::HeaderValue::from_str(&cookie_value).unwrap(),
+        header::HeaderValue::from_str(value).map_err(|e| ServerFnError::new(e.to_string()))?,
     );
-
-    Ok(challenge_response)
+    Ok(())
 }
@@ -2,15 +2,32 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 use crate::errors::ModuleAuthError;
-use crate::types::{AuthDeps, VerifyChallengeRequest, VerifyChallengeResponse};
+use crate::session::AuthenticatedAgent;
+use crate::types::{
+    AuthDeps, DelegateRequest, DelegateResponse, KeyHistoryResponse, ListDelegationsResponse,
+    ListSessionsResponse, RevokeDelegationRequest, RevokeSessionsResponse, RotateKeyRequest,
+    SessionInfo, SetRecoveryKeyRequest, VerifyChallengeRequest, VerifyChallengeResponse,
+};
 
 use base64::Engine;
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use hex;
 use k256::ecdsa::{Signature, VerifyingKey, signature::DigestVerifier};
 use sha2::{Digest, Sha256};
-use std::time::Duration;
-use synapse_core::domain::{auth::Session, crypto::CryptoChallenge};
+use synapse_application::auth::keys::{agent_of, key_history};
+use synapse_application::events::CreateEventCommand;
+use synapse_core::domain::{
+    auth::{SESSION_TTL, Session},
+    crypto::CryptoChallenge,
+    events::Event,
+    events::delegations::{
+        DelegationChange, delegation_event, delegation_token, revocation_event,
+    },
+    events::keys::{KeyChange, carried_key_changes, recovery_key_event, rotation_event},
+};
+use synapse_core::{
+    CoreError, PersistenceError, require_event_signature_with,
+};
 use time::OffsetDateTime;
 use tracing::debug;
 use uuid::Uuid;
@@ -31,37 +48,418 @@ pub async fn create_challenge(public_key: String) -> Result<CryptoChallenge, Mod
     Ok(challenge)
 }
 
+/// Verifies the signed challenge and opens a session for its agent on the
+/// device described by `user_agent`. A key the agent rotated to logs in as
+/// the agent; a key rotated away no longer logs in.
+///
+/// The challenge is consumed once its signature checks out, so a captured
+/// signature cannot be replayed, while a wrong agent or signature leaves it
+/// for its rightful answer.
 pub async fn verify_challenge(
     deps: AuthDeps,
     challenge_request: VerifyChallengeRequest,
+    user_agent: Option<String>,
 ) -> Result<VerifyChallengeResponse, ModuleAuthError> {
     debug!("verify_challenge called from service!");
+    let verifying_key = parse_public_key(&challenge_request.public_key)?;
+    let signature = parse_signature(&challenge_request.signature)?;
     let challenge = deps
         .crypto_repo
         .get_challenge(challenge_request.challenge.id)
         .await
-        .unwrap()
-        .unwrap();
-    let challenge_nonce = challenge.nonce;
-    let pk_bytes = hex::decode(challenge_request.public_key.clone()).unwrap();
-    let verifying_key = VerifyingKey::from_sec1_bytes(&pk_bytes).unwrap();
-    let sig_bytes = hex::decode(challenge_request.signature.clone()).unwrap();
-    let signature = Signature::from_slice(&sig_bytes).unwrap();
-    let nonce_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
-        .decode(challenge_nonce)
-        .unwrap();
+        .map_err(CoreError::from)?
+        .ok_or_else(|| ModuleAuthError::Unauthorized("unknown or used challenge".to_string()))?;
+    let now = OffsetDateTime::now_utc();
+    check_challenge(
+        &challenge,
+        &challenge_request.public_key,
+        &verifying_key,
+        &signature,
+        now,
+    )?;
+    // Of concurrent answers, only the one that removes the challenge logs in
+    deps.crypto_repo
+        .consume_challenge(challenge.id, &challenge_request.public_key)
+        .await
+        .map_err(CoreError::from)?
+        .ok_or_else(|| ModuleAuthError::Unauthorized("unknown or used challenge".to_string()))?;
+
+    let agent = agent_of(deps.keys.as_ref(), &challenge_request.public_key)
+        .await?
+        .ok_or_else(|| ModuleAuthError::Unauthorized("key was rotated away".to_string()))?;
+
+    let session = Session::new(agent, user_agent, now);
+    let session = deps
+        .session_repo
+        .store_session(session)
+        .await
+        .map_err(CoreError::from)?;
+    Ok(VerifyChallengeResponse { session })
+}
+
+pub(crate) fn parse_public_key(public_key: &str) -> Result<VerifyingKey, ModuleAuthError> {
+    let bytes = hex::decode(public_key)
+        .map_err(|_| ModuleAuthError::BadRequest("public key must be hex".to_string()))?;
+    VerifyingKey::from_sec1_bytes(&bytes)
+        .map_err(|_| ModuleAuthError::BadRequest("invalid public key".to_string()))
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, ModuleAuthError> {
+    let bytes = hex::decode(signature)
+        .map_err(|_| ModuleAuthError::BadRequest("signature must be hex".to_string()))?;
+    Signature::from_slice(&bytes)
+        .map_err(|_| ModuleAuthError::BadRequest("invalid signature".to_string()))
+}
+
+/// Checks that `challenge` was issued to `public_key`, is still open at `now`
+/// and that `signature` signs its nonce.
+fn check_challenge(
+    challenge: &CryptoChallenge,
+    public_key: &str,
+    verifying_key: &VerifyingKey,
+    signature: &Signature,
+    now: OffsetDateTime,
+) -> Result<(), ModuleAuthError> {
+    if challenge.agent != public_key {
+        return Err(ModuleAuthError::Unauthorized(
+            "challenge was issued to another agent".to_string(),
+        ));
+    }
+    if challenge.is_expired(now) {
+        return Err(ModuleAuthError::Unauthorized(
+            "challenge expired".to_string(),
+        ));
+    }
+    let nonce_bytes = URL_SAFE_NO_PAD
+        .decode(&challenge.nonce)
+        .map_err(|e| ModuleAuthError::Internal(format!("stored nonce is invalid: {e}")))?;
     let digest = Sha256::new_with_prefix(&nonce_bytes);
     verifying_key
-        .verify_digest(digest, &signature)
-        .map_err(|_| ModuleAuthError::BadRequest("signature verification failed".into()))?;
-    let expires_at = OffsetDateTime::now_utc() + Duration::from_hours(4);
-    let session = Session {
-        id: Uuid::new_v4(),
-        agent: challenge_request.public_key.clone(),
-        created_at: OffsetDateTime::now_utc(),
-        expires_at,
-        revoked: false,
+        .verify_digest(digest, signature)
+        .map_err(|_| ModuleAuthError::Unauthorized("signature verification failed".to_string()))
+}
+
+/// Extends the session of `agent` by `SESSION_TTL` from now.
+pub async fn refresh_session(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+) -> Result<Session, ModuleAuthError> {
+    let now = OffsetDateTime::now_utc();
+    match deps
+        .session_repo
+        .refresh_session(agent.session, now + SESSION_TTL, now)
+        .await
+    {
+        Ok(session) => Ok(session),
+        Err(PersistenceError::NotFound) => Err(ModuleAuthError::Unauthorized(
+            "session expired".to_string(),
+        )),
+        Err(e) => Err(CoreError::from(e).into()),
+    }
+}
+
+/// Revokes the session a logout is requested with. Revoking it again is
+/// harmless.
+pub async fn logout(deps: AuthDeps, agent: &AuthenticatedAgent) -> Result<(), ModuleAuthError> {
+    match deps.session_repo.revoke_session(agent.session).await {
+        Ok(_) | Err(PersistenceError::NotFound) => Ok(()),
+        Err(e) => Err(CoreError::from(e).into()),
+    }
+}
+
+/// The active sessions of `agent`, flagging the one it is using.
+pub async fn list_sessions(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+) -> Result<ListSessionsResponse, ModuleAuthError> {
+    let sessions = deps
+        .session_repo
+        .list_sessions(&agent.agent, OffsetDateTime::now_utc())
+        .await
+        .map_err(CoreError::from)?;
+    Ok(ListSessionsResponse {
+        sessions: sessions
+            .into_iter()
+            .map(|session| SessionInfo::of(session, agent.session))
+            .collect(),
+    })
+}
+
+/// Revokes session `id` of `agent`. Sessions of other agents are not found.
+pub async fn revoke_session(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+    id: Uuid,
+) -> Result<(), ModuleAuthError> {
+    let not_found = || ModuleAuthError::NotFound(format!("session {id}"));
+    let session = match deps.session_repo.get_session(id).await {
+        Ok(session) => session,
+        Err(PersistenceError::NotFound) => return Err(not_found()),
+        Err(e) => return Err(CoreError::from(e).into()),
     };
-    deps.session_repo.store_session(session.clone()).await;
-    Ok(VerifyChallengeResponse { session })
+    if session.agent != agent.agent {
+        return Err(not_found());
+    }
+    deps.session_repo
+        .revoke_session(id)
+        .await
+        .map_err(CoreError::from)?;
+    Ok(())
+}
+
+/// Revokes every session of `agent` but the one it is using.
+pub async fn revoke_other_sessions(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+) -> Result<RevokeSessionsResponse, ModuleAuthError> {
+    let revoked = deps
+        .session_repo
+        .revoke_other_sessions(&agent.agent, agent.session)
+        .await
+        .map_err(CoreError::from)?;
+    Ok(RevokeSessionsResponse { revoked })
+}
+
+/// The delegations `agent` granted that are still in force.
+pub async fn list_delegations(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+) -> Result<ListDelegationsResponse, ModuleAuthError> {
+    let delegations = deps
+        .delegations
+        .list_delegations(&agent.agent, OffsetDateTime::now_utc())
+        .await
+        .map_err(CoreError::from)?;
+    Ok(ListDelegationsResponse { delegations })
+}
+
+/// Records the delegation of `agent` in `request`. The grant must be signed
+/// by the agent itself, since delegates present it to other Synapses as their
+/// token.
+pub async fn delegate(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+    request: DelegateRequest,
+) -> Result<DelegateResponse, ModuleAuthError> {
+    parse_public_key(&request.delegate)?;
+    let grant = Event {
+        id: request.id,
+        created_at: request.created_at,
+        agent_signature: Some(request.agent_signature),
+        latest_edit: None,
+        ..delegation_event(agent.agent.clone(), request.delegate, &request.scope)?
+    };
+    let Some(DelegationChange::Grant(delegation)) = DelegationChange::of(&grant)? else {
+        return Err(ModuleAuthError::Internal("expected a delegation grant".to_string()));
+    };
+    require_signed(&deps, &grant).await?;
+    let stored = deps.create_local_event.execute(auth_command(grant)).await?;
+    Ok(DelegateResponse {
+        delegation,
+        token: delegation_token(&stored)?,
+    })
+}
+
+/// Revokes delegation `id` of `agent`. Delegations of other agents, and
+/// those no longer in force, are not found.
+pub async fn revoke_delegation(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+    id: Uuid,
+    request: RevokeDelegationRequest,
+) -> Result<(), ModuleAuthError> {
+    let delegations = list_delegations(deps.clone(), agent).await?.delegations;
+    if !delegations.iter().any(|delegation| delegation.id == id) {
+        return Err(ModuleAuthError::NotFound(format!("delegation {id}")));
+    }
+    let revocation = Event {
+        id: request.id,
+        created_at: request.created_at,
+        agent_signature: Some(request.agent_signature),
+        latest_edit: None,
+        ..revocation_event(agent.agent.clone(), id)
+    };
+    require_signed(&deps, &revocation).await?;
+    deps.create_local_event
+        .execute(auth_command(revocation))
+        .await?;
+    Ok(())
+}
+
+/// The key history of `agent` as recorded here.
+pub async fn get_key_history(
+    deps: AuthDeps,
+    agent: &str,
+) -> Result<KeyHistoryResponse, ModuleAuthError> {
+    let history = key_history(deps.keys.as_ref(), agent, Vec::new()).await?;
+    Ok(KeyHistoryResponse {
+        agent: history.agent().clone(),
+        current_key: history.current_key().clone(),
+        recovery_key: history.recovery_key().cloned(),
+        periods: history.periods().to_vec(),
+        latest_change: history.latest_change(),
+        token: history.token()?,
+    })
+}
+
+/// Records the key rotation in `request`. It needs no session, so an agent
+/// that lost its key can recover with its recovery key. The other sessions of
+/// the agent are revoked, keeping `session` if it is one of the agent's.
+pub async fn rotate_key(
+    deps: AuthDeps,
+    session: Option<&AuthenticatedAgent>,
+    request: RotateKeyRequest,
+) -> Result<KeyHistoryResponse, ModuleAuthError> {
+    parse_public_key(&request.key)?;
+    let agent = request.agent.clone();
+    let rotation = Event {
+        id: request.id,
+        created_at: request.created_at,
+        content: Some(request.proof),
+        agent_signature: Some(request.agent_signature),
+        latest_edit: None,
+        ..rotation_event(request.agent, request.key, request.previous)
+    };
+    record_key_change(&deps, rotation).await?;
+    let keep = session
+        .filter(|session| session.agent == agent)
+        .map_or(Uuid::nil(), |session| session.session);
+    deps.session_repo
+        .revoke_other_sessions(&agent, keep)
+        .await
+        .map_err(CoreError::from)?;
+    get_key_history(deps, &agent).await
+}
+
+/// Records the recovery key of `agent` in `request`.
+pub async fn set_recovery_key(
+    deps: AuthDeps,
+    agent: &AuthenticatedAgent,
+    request: SetRecoveryKeyRequest,
+) -> Result<KeyHistoryResponse, ModuleAuthError> {
+    parse_public_key(&request.key)?;
+    let change = Event {
+        id: request.id,
+        created_at: request.created_at,
+        agent_signature: Some(request.agent_signature),
+        latest_edit: None,
+        ..recovery_key_event(agent.agent.clone(), request.key, request.previous)
+    };
+    record_key_change(&deps, change).await?;
+    get_key_history(deps, &agent.agent).await
+}
+
+/// Records key change `event` once it is endorsed by the agent's keys.
+async fn record_key_change(deps: &AuthDeps, event: Event) -> Result<(), ModuleAuthError> {
+    KeyChange::of(&event)?;
+    require_signed(deps, &event).await?;
+    deps.create_local_event.execute(auth_command(event)).await?;
+    Ok(())
+}
+
+/// Delegation and key events federate, so they must carry the agent's
+/// signature, made with the key in force, even though the session vouches for
+/// the agent here.
+async fn require_signed(deps: &AuthDeps, event: &Event) -> Result<(), ModuleAuthError> {
+    let carried = carried_key_changes(event)?;
+    let history = key_history(deps.keys.as_ref(), &event.agent, carried).await?;
+    require_event_signature_with(event, &history)
+        .map_err(|e| ModuleAuthError::Unauthorized(e.to_string()))
+}
+
+fn auth_command(event: Event) -> CreateEventCommand {
+    CreateEventCommand {
+        id: Some(event.id),
+        created_at: Some(event.created_at),
+        event_type: event.event_type,
+        agent: event.agent,
+        module_kind: event.module_kind,
+        module_slug: event.module_slug,
+        target: event.target,
+        previous: event.previous,
+        content: event.content,
+        artifacts: event.artifacts,
+        metadata: event.metadata,
+        links: event.links,
+        data: event.data,
+        expiration: event.expiration,
+        agent_signature: event.agent_signature,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::{SigningKey, signature::DigestSigner};
+    use time::Duration;
+
+    fn signed(challenge: &CryptoChallenge, key: &SigningKey) -> Signature {
+        let nonce_bytes = URL_SAFE_NO_PAD.decode(&challenge.nonce).unwrap();
+        key.sign_digest(Sha256::new_with_prefix(&nonce_bytes))
+    }
+
+    fn public_key(key: &SigningKey) -> String {
+        hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+    }
+
+    #[test]
+    fn test_check_challenge() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let other = SigningKey::from_slice(&[9u8; 32]).unwrap();
+        let challenge = CryptoChallenge::builder()
+            .with_agent(public_key(&key))
+            .with_nonce(URL_SAFE_NO_PAD.encode([1u8; 32]))
+            .build();
+        let now = OffsetDateTime::now_utc();
+        let verifying_key = *key.verifying_key();
+        let signature = signed(&challenge, &key);
+
+        assert!(check_challenge(&challenge, &public_key(&key), &verifying_key, &signature, now).is_ok());
+        assert!(matches!(
+            check_challenge(
+                &challenge,
+                &public_key(&key),
+                &verifying_key,
+                &signature,
+                challenge.expires_at + Duration::seconds(1)
+            ),
+            Err(ModuleAuthError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            check_challenge(
+                &challenge,
+                &public_key(&other),
+                other.verifying_key(),
+                &signed(&challenge, &other),
+                now
+            ),
+            Err(ModuleAuthError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            check_challenge(
+                &challenge,
+                &public_key(&key),
+                &verifying_key,
+                &signed(&challenge, &other),
+                now
+            ),
+            Err(ModuleAuthError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_input_is_bad_request() {
+        assert!(matches!(
+            parse_public_key("not hex"),
+            Err(ModuleAuthError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_public_key("02abcd"),
+            Err(ModuleAuthError::BadRequest(_))
+        ));
+        assert!(matches!(
+            parse_signature("zz"),
+            Err(ModuleAuthError::BadRequest(_))
+        ));
+    }
 }
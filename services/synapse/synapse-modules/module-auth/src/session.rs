@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Session authentication of local clients.
+//!
+//! Logging in sets the `menexus_session` cookie. The server resolves it once
+//! per request (see `authenticate_session`) and stores the resulting
+//! `AuthenticatedAgent` in the request extensions, where handlers and server
+//! functions take it from instead of trusting the agent named in their input.
+
+use std::convert::Infallible;
+
+use axum::extract::{FromRequestParts, OptionalFromRequestParts};
+use axum::http::{HeaderMap, header, request::Parts};
+use synapse_core::CoreError;
+use synapse_core::domain::auth::Session;
+use synapse_core::ports::auth::SessionRepository;
+use time::OffsetDateTime;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::errors::ModuleAuthError;
+
+pub const SESSION_COOKIE: &str = "menexus_session";
+
+/// Longest `User-Agent` kept with a session, in bytes.
+const MAX_USER_AGENT_LEN: usize = 256;
+
+/// The agent a request is authenticated as, through an active session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthenticatedAgent {
+    pub agent: String,
+    pub session: Uuid,
+}
+
+impl AuthenticatedAgent {
+    /// Fails unless `public_key` is the authenticated agent.
+    pub fn require(&self, public_key: &str) -> Result<(), CoreError> {
+        if self.agent == public_key {
+            Ok(())
+        } else {
+            Err(CoreError::Authorization(
+                "cannot act for another agent".to_string(),
+            ))
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for AuthenticatedAgent {
+    type Rejection = ModuleAuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Self>()
+            .cloned()
+            .ok_or_else(|| ModuleAuthError::Unauthorized("login required".to_string()))
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for AuthenticatedAgent {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<Self>().cloned())
+    }
+}
+
+/// The session id in the cookies of `headers`, if any.
+pub fn session_id(headers: &HeaderMap) -> Option<Uuid> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == SESSION_COOKIE).then(|| value.parse().ok())?
+        })
+}
+
+/// `Set-Cookie` value handing `session` to the client until it expires.
+pub fn session_cookie(session: &Session, now: OffsetDateTime) -> String {
+    let max_age = (session.expires_at - now).whole_seconds().max(0);
+    let token = session.id;
+    // TODO add Secure; to cookie for prod
+    format!("{SESSION_COOKIE}={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={max_age}")
+}
+
+/// `Set-Cookie` value removing the session cookie from the client.
+pub fn cleared_session_cookie() -> String {
+    format!("{SESSION_COOKIE}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0")
+}
+
+/// The `User-Agent` of `headers`, cut to a length worth storing.
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::USER_AGENT)?.to_str().ok()?.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let mut end = value.len().min(MAX_USER_AGENT_LEN);
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(value[..end].to_string())
+}
+
+/// Resolves the session cookie of `headers`. Unknown, expired and revoked
+/// sessions authenticate no one.
+pub async fn authenticate_session(
+    sessions: &dyn SessionRepository,
+    headers: &HeaderMap,
+) -> Option<AuthenticatedAgent> {
+    let id = session_id(headers)?;
+    let session = match sessions.get_session(id).await {
+        Ok(session) => session,
+        Err(e) => {
+            debug!("session {id} not found: {e}");
+            return None;
+        }
+    };
+    if !session.is_active(OffsetDateTime::now_utc()) {
+        debug!("session {id} is expired or revoked");
+        return None;
+    }
+    Some(AuthenticatedAgent {
+        agent: session.agent,
+        session: session.id,
+    })
+}
+
+/// The agent the current server function call is authenticated as.
+pub async fn authenticated_agent() -> Result<AuthenticatedAgent, leptos::prelude::ServerFnError> {
+    Ok(leptos_axum::extract::<AuthenticatedAgent>().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use synapse_core::domain::auth::SESSION_TTL;
+
+    #[test]
+    fn test_session_id_from_cookies() {
+        let id = Uuid::new_v4();
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_id(&headers), None);
+
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_str(&format!("theme=dark; {SESSION_COOKIE}={id}; lang=en")).unwrap(),
+        );
+        assert_eq!(session_id(&headers), Some(id));
+
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("menexus_session=not-a-uuid"),
+        );
+        assert_eq!(session_id(&headers), None);
+    }
+
+    #[test]
+    fn test_session_cookie_lasts_until_expiry() {
+        let now = OffsetDateTime::now_utc();
+        let session = Session::new("agent", None, now);
+        let cookie = session_cookie(&session, now);
+        assert!(cookie.starts_with(&format!("{SESSION_COOKIE}={}; ", session.id)));
+        assert!(cookie.ends_with(&format!("Max-Age={}", SESSION_TTL.whole_seconds())));
+        assert!(cleared_session_cookie().ends_with("Max-Age=0"));
+    }
+}
@@ -6,10 +6,18 @@
 //! This module provides functions for signing events with the user's private key,
 //! enabling federated authentication across Synapses.
 
+use k256::ecdsa::SigningKey;
 #[cfg(feature = "hydrate")]
-use k256::ecdsa::{Signature, SigningKey, signature::DigestSigner};
+use k256::ecdsa::{Signature, signature::DigestSigner};
 #[cfg(feature = "hydrate")]
 use sha2::{Digest, Sha256};
+use synapse_core::domain::events::Event;
+#[cfg(feature = "hydrate")]
+use synapse_core::domain::events::keys::{KEY_HISTORY_KEY, key_proof, rotation_event};
+#[cfg(feature = "hydrate")]
+use crate::types::RotateKeyRequest;
+#[cfg(feature = "hydrate")]
+use uuid::Uuid;
 
 /// Get the signing key from session storage.
 /// Returns None if not logged in or key not available.
@@ -43,39 +51,19 @@ pub fn sign_bytes(payload: &[u8]) -> Option<String> {
     Some(hex::encode(signature.to_bytes()))
 }
 
-/// Sign an event payload and return the hex-encoded signature.
-/// 
-/// This creates a canonical representation of the event for signing.
-/// The signature can be verified by any Synapse that has the agent's public key.
+/// Sign an event as the logged-in agent and return the hex-encoded signature.
 #[cfg(feature = "hydrate")]
-pub fn sign_event_payload(
-    event_type: &str,
-    agent: &str,
-    content: Option<&str>,
-    module_kind: Option<&str>,
-    module_slug: Option<&str>,
-) -> Option<String> {
-    use serde::Serialize;
-    
-    #[derive(Serialize)]
-    struct SigningPayload<'a> {
-        event_type: &'a str,
-        agent: &'a str,
-        content: Option<&'a str>,
-        module_kind: Option<&'a str>,
-        module_slug: Option<&'a str>,
-    }
-    
-    let payload = SigningPayload {
-        event_type,
-        agent,
-        content,
-        module_kind,
-        module_slug,
-    };
-    
-    let payload_bytes = serde_json::to_vec(&payload).ok()?;
-    sign_bytes(&payload_bytes)
+pub fn sign_event(event: &Event) -> Option<String> {
+    let signing_key = get_signing_key()?;
+    Some(sign_event_with(event, &signing_key))
+}
+
+/// Sign an event with `signing_key` and return the hex-encoded signature.
+///
+/// Uses the canonical signing format from synapse-core, so the signature
+/// verifies on any Synapse that receives the same event.
+pub fn sign_event_with(event: &Event, signing_key: &SigningKey) -> String {
+    synapse_core::sign_event(event, signing_key)
 }
 
 /// Clear the signing key from session storage (logout).
@@ -84,10 +72,102 @@ pub fn clear_signing_key() {
     if let Some(window) = web_sys::window() {
         if let Ok(Some(storage)) = window.session_storage() {
             let _ = storage.remove_item("menexus_signing_key");
+            let _ = storage.remove_item("menexus_agent");
+            let _ = storage.remove_item("menexus_key_history");
         }
     }
 }
 
+/// Store the signing key in session storage, replacing the previous one.
+#[cfg(feature = "hydrate")]
+pub fn set_signing_key(signing_key: &SigningKey) {
+    if let Some(window) = web_sys::window()
+        && let Ok(Some(storage)) = window.session_storage()
+    {
+        let _ = storage.set_item("menexus_signing_key", &encode_signing_key(signing_key));
+    }
+}
+
+/// The hex-encoded private key of `signing_key`, as entered to log in.
+#[cfg(feature = "hydrate")]
+pub fn encode_signing_key(signing_key: &SigningKey) -> String {
+    hex::encode(signing_key.to_bytes())
+}
+
+/// Generate a new random signing key.
+#[cfg(feature = "hydrate")]
+pub fn generate_signing_key() -> Option<SigningKey> {
+    let mut key_bytes = [0u8; 32];
+    getrandom::fill(&mut key_bytes).ok()?;
+    SigningKey::from_slice(&key_bytes).ok()
+}
+
+/// Store the agent the signing key signs for and, once it rotated keys, the
+/// key history token its events carry.
+#[cfg(feature = "hydrate")]
+pub fn set_agent(agent: &str, key_history: Option<&str>) {
+    if let Some(window) = web_sys::window()
+        && let Ok(Some(storage)) = window.session_storage()
+    {
+        let _ = storage.set_item("menexus_agent", agent);
+        let _ = match key_history {
+            Some(token) => storage.set_item("menexus_key_history", token),
+            None => storage.remove_item("menexus_key_history"),
+        };
+    }
+}
+
+/// Get the agent the stored signing key signs for. It is the public key of
+/// the signing key until the agent rotates to a new key.
+#[cfg(feature = "hydrate")]
+pub fn get_agent() -> Option<String> {
+    let stored = web_sys::window()
+        .and_then(|window| window.session_storage().ok().flatten())
+        .and_then(|storage| storage.get_item("menexus_agent").ok().flatten());
+    stored.or_else(get_public_key)
+}
+
+/// Put the stored key history under the `key_history` metadata key of
+/// `event`, so Synapses that have not seen the agent rotate keys can verify
+/// it. Must be done before signing.
+#[cfg(feature = "hydrate")]
+pub fn with_key_history(mut event: Event) -> Event {
+    let token = web_sys::window()
+        .and_then(|window| window.session_storage().ok().flatten())
+        .and_then(|storage| storage.get_item("menexus_key_history").ok().flatten());
+    if let Some(token) = token {
+        event
+            .metadata
+            .get_or_insert_with(Default::default)
+            .insert(KEY_HISTORY_KEY.to_string(), token);
+    }
+    event
+}
+
+/// Sign the rotation of `agent` to `new_key`, following its key change
+/// `previous`, with the stored signing key.
+#[cfg(feature = "hydrate")]
+pub fn rotation_request(
+    agent: &str,
+    new_key: &SigningKey,
+    previous: Option<Uuid>,
+) -> Option<RotateKeyRequest> {
+    let key = hex::encode(new_key.verifying_key().to_encoded_point(true).as_bytes());
+    let mut rotation = rotation_event(agent, key.clone(), previous);
+    let proof = key_proof(&rotation, new_key);
+    rotation.content = Some(proof.clone());
+    let agent_signature = sign_event(&rotation)?;
+    Some(RotateKeyRequest {
+        agent: agent.to_string(),
+        id: rotation.id,
+        created_at: rotation.created_at,
+        previous,
+        key,
+        proof,
+        agent_signature,
+    })
+}
+
 /// Get the public key (hex-encoded) derived from the stored signing key.
 #[cfg(feature = "hydrate")]
 pub fn get_public_key() -> Option<String> {
@@ -96,3 +176,73 @@ pub fn get_public_key() -> Option<String> {
     let encoded = verifying_key.to_encoded_point(true);
     Some(hex::encode(encoded.as_bytes()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synapse_core::domain::crypto::canonical::event_signing_bytes;
+    use synapse_core::{SignatureVerificationResult, verify_event_signature};
+    use time::{Duration, OffsetDateTime};
+
+    #[test]
+    fn test_client_signature_verifies_on_the_synapse() {
+        let signing_key = SigningKey::from_slice(&[21u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        // Built the way the compose bar builds it, with nanosecond timestamps
+        let mut event = Event::new()
+            .with_event_type("profiles:set_profile")
+            .with_module_kind("profiles")
+            .with_agent(agent)
+            .with_data(br#"{"display_name":"alice"}"#.to_vec())
+            .with_expiration(OffsetDateTime::now_utc() + Duration::hours(1))
+            .build();
+        event.agent_signature = Some(sign_event_with(&event, &signing_key));
+
+        // The event crosses the server function boundary as JSON
+        let received: Event = serde_json::from_slice(&serde_json::to_vec(&event).unwrap()).unwrap();
+        assert_eq!(verify_event_signature(&received), SignatureVerificationResult::Valid);
+
+        let mut swapped = received.clone();
+        swapped.data = Some(br#"{"display_name":"mallory"}"#.to_vec());
+        assert!(verify_event_signature(&swapped).is_invalid());
+    }
+
+    /// Canonical bytes and signature of a fixed event under a fixed key, as
+    /// any client must produce them for Synapses to accept its events.
+    #[test]
+    fn test_signing_test_vector() {
+        let signing_key = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let agent = hex::encode(signing_key.verifying_key().to_encoded_point(true).as_bytes());
+        let created_at = OffsetDateTime::from_unix_timestamp(1_736_380_800).unwrap();
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("lang".to_string(), "en".to_string());
+        metadata.insert("channel".to_string(), "general".to_string());
+        let mut event = Event::new()
+            .with_event_type("posts:create_post")
+            .with_module_kind("posts")
+            .with_module_slug("general")
+            .with_agent(agent.clone())
+            .with_content("hello")
+            .with_metadata(metadata)
+            .with_data(b"{}".to_vec())
+            .with_expiration(created_at + Duration::hours(1))
+            .build();
+        event.id = uuid::Uuid::from_u128(1);
+        event.created_at = created_at;
+
+        assert_eq!(
+            agent,
+            "031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f"
+        );
+        assert_eq!(
+            String::from_utf8(event_signing_bytes(&event)).unwrap(),
+            r#"{"format":"menexus.event.v2","id":"00000000-0000-0000-0000-000000000001","created_at":1736380800000000,"event_type":"posts:create_post","module_kind":"posts","module_slug":"general","agent":"031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f","target":null,"previous":null,"content":"hello","artifacts":null,"metadata":{"channel":"general","lang":"en"},"links":null,"data_sha256":"44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a","expiration":1736384400000000}"#
+        );
+        // ECDSA over SHA-256 with RFC 6979 nonces, as r || s in hex
+        let signature = "e097481bd4e767ae369331fb1cf2a25c40b3f6d8e7e591e81b58d35691a0be367de19c615cff4278332fc520b5de81ca54ee25b1ab59ffe02dad2df97e15f2bd";
+        assert_eq!(sign_event_with(&event, &signing_key), signature);
+
+        event.agent_signature = Some(signature.to_string());
+        assert_eq!(verify_event_signature(&event), SignatureVerificationResult::Valid);
+    }
+}
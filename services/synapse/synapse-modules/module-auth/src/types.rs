@@ -4,19 +4,33 @@
 use serde::{Deserialize, Serialize};
 use synapse_core::domain::auth::Session;
 use synapse_core::domain::crypto::CryptoChallenge;
+use synapse_core::domain::events::PublicKey;
+use synapse_core::domain::events::delegations::{Delegation, DelegationScope};
+use synapse_core::domain::events::keys::KeyPeriod;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
 #[cfg(feature = "ssr")]
 use std::sync::Arc;
 #[cfg(feature = "ssr")]
 use synapse_core::ports::auth::SessionRepository;
 #[cfg(feature = "ssr")]
+use synapse_application::events::CreateLocalEventUseCase;
+#[cfg(feature = "ssr")]
 use synapse_core::ports::crypto::CryptoRepository;
+#[cfg(feature = "ssr")]
+use synapse_core::ports::delegations::delegation_repository::DelegationRepository;
+#[cfg(feature = "ssr")]
+use synapse_core::ports::keys::key_repository::KeyRepository;
 
 #[cfg(feature = "ssr")]
 #[derive(Clone)]
 pub struct AuthDeps {
     pub crypto_repo: Arc<dyn CryptoRepository>,
     pub session_repo: Arc<dyn SessionRepository>,
+    pub delegations: Arc<dyn DelegationRepository>,
+    pub keys: Arc<dyn KeyRepository>,
+    pub create_local_event: Arc<dyn CreateLocalEventUseCase + Send + Sync>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -42,7 +56,117 @@ pub struct VerifyChallengeResponse {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
-pub struct LogoutRequest {
-    pub public_key: String,
-    pub signature: String,
+pub struct RefreshSessionResponse {
+    pub session: Session,
+}
+
+/// An active session of the authenticated agent, as listed to it.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub last_seen_at: OffsetDateTime,
+    pub expires_at: OffsetDateTime,
+    /// Whether this is the session the listing was requested with.
+    pub current: bool,
+}
+
+impl SessionInfo {
+    pub fn of(session: Session, current: Uuid) -> Self {
+        Self {
+            id: session.id,
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_seen_at: session.last_seen_at,
+            expires_at: session.expires_at,
+            current: session.id == current,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ListSessionsResponse {
+    pub sessions: Vec<SessionInfo>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+}
+
+/// An `auth:delegate` event signed in the browser by the agent of the session,
+/// letting the `delegate` key sign events within `scope`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DelegateRequest {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub delegate: PublicKey,
+    pub scope: DelegationScope,
+    pub agent_signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DelegateResponse {
+    pub delegation: Delegation,
+    /// Goes under the `delegation` metadata key of the events the delegate
+    /// signs.
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ListDelegationsResponse {
+    pub delegations: Vec<Delegation>,
+}
+
+/// An `auth:revoke_delegation` event signed in the browser by the agent of the
+/// session.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RevokeDelegationRequest {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    pub agent_signature: String,
+}
+
+/// The keys of an agent over time, as endorsed by its key changes.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct KeyHistoryResponse {
+    pub agent: PublicKey,
+    pub current_key: PublicKey,
+    pub recovery_key: Option<PublicKey>,
+    pub periods: Vec<KeyPeriod>,
+    /// The last key change, which the next one must name as its `previous`.
+    #[serde(default)]
+    pub latest_change: Option<Uuid>,
+    /// Goes under the `key_history` metadata key of the events signed with a
+    /// rotated key. `None` while the agent never changed keys.
+    pub token: Option<String>,
+}
+
+/// An `auth:rotate_key` event moving `agent` to `key`, signed with its key in
+/// force or its recovery key. `proof` is the `key_proof` signed by `key`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RotateKeyRequest {
+    pub agent: PublicKey,
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    /// The agent's last key change, if any.
+    #[serde(default)]
+    pub previous: Option<Uuid>,
+    pub key: PublicKey,
+    pub proof: String,
+    pub agent_signature: String,
+}
+
+/// An `auth:set_recovery_key` event signed in the browser by the agent of the
+/// session.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SetRecoveryKeyRequest {
+    pub id: Uuid,
+    pub created_at: OffsetDateTime,
+    /// The agent's last key change, if any.
+    #[serde(default)]
+    pub previous: Option<Uuid>,
+    pub key: PublicKey,
+    pub agent_signature: String,
 }
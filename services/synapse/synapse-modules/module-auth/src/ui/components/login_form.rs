@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
-use crate::server_fns::{request_challenge_server, verify_challenge_server};
+use crate::server_fns::{key_history_server, request_challenge_server, verify_challenge_server};
+use crate::signing::set_agent;
 use crate::types::{ChallengeRequest, ChallengeResponse, VerifyChallengeRequest};
 use base64::Engine;
 use base64::prelude::BASE64_URL_SAFE_NO_PAD;
@@ -70,6 +71,18 @@ pub fn LoginForm() -> impl IntoView {
                     if let Ok(Some(storage)) = window.session_storage() {
                         let _ = storage.set_item("menexus_signing_key", private_key_hex);
                     }
+
+                    // A rotated key signs for the agent it was rotated into
+                    let agent = resp.session.agent;
+                    let key_history = if agent != public_key_hex {
+                        key_history_server(Some(agent.clone()))
+                            .await
+                            .ok()
+                            .and_then(|history| history.token)
+                    } else {
+                        None
+                    };
+                    set_agent(&agent, key_history.as_deref());
                     
                     // Full page reload to ensure SSR picks up the new session cookie
                     window.location().set_href("/").expect("failed to redirect");
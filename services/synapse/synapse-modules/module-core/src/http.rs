@@ -17,7 +17,7 @@ use std::sync::Arc;
 use synapse_config::{get_synapse_config, get_synapse_manifest};
 use synapse_core::{
     CoreError,
-    domain::events::Event,
+    domain::{events::Event, modules::EventTypeSpec},
     ports::{
         events::event_repository::{EventFilter, EventRepository},
         modules::Module,
@@ -112,6 +112,14 @@ impl Module for CoreModule {
     fn version(&self) -> Result<String, CoreError> {
         Ok(self.version.clone())
     }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        vec![
+            EventTypeSpec::system("synapse:get_public_key"),
+            EventTypeSpec::system("synapse:get_manifest"),
+            EventTypeSpec::system("synapse:create_event"),
+            EventTypeSpec::system("synapse:list_all_events").with_schema(EventFilter::schema()),
+        ]
+    }
     async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
         match event.event_type.as_str() {
             // Return this Synapse's public key
@@ -163,17 +171,13 @@ impl Module for CoreModule {
             "synapse:list_all_events" => {
                 let events = self
                     .repo
-                    .retrieve(EventFilter {
-                        event_type: None,
-                        module_kind: None,
-                        module_slug: None,
-                    })
+                    .retrieve(EventFilter::from_event(event)?)
                     .await
                     .unwrap();
                 Ok(events)
             }
 
-            _ => Ok(vec![])
+            _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
         }
     }
 }
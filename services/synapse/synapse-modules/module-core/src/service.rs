@@ -37,6 +37,8 @@ pub async fn get_remote_manifest(
 
     // Create the event command
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "synapse:get_manifest".to_string(),
         module_kind: Some("core".to_string()),
         agent: "local-agent".to_string(),
@@ -51,6 +51,12 @@ impl From<synapse_core::errors::CoreError> for ModulePostsError {
             CoreError::RateLimited(_) => {
                 ModulePostsError::BadRequest("RateLimited error".to_string())
             }
+            CoreError::UnsupportedModule(_) => {
+                ModulePostsError::BadRequest("UnsupportedModule error".to_string())
+            }
+            CoreError::UnsupportedAction(_) => {
+                ModulePostsError::BadRequest("UnsupportedAction error".to_string())
+            }
             CoreError::Other(_) => ModulePostsError::Other("Other error".to_string()),
         }
     }
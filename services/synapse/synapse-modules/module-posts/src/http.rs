@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use axum::{Json, extract::Path, http::StatusCode};
+use module_auth::session::AuthenticatedAgent;
 use synapse_application::events::{
     CreateEventCommand, CreateLocalEventUseCase, CreateRemoteEventCommand, CreateRemoteEventUseCase,
 };
@@ -12,20 +13,25 @@ use synapse_config::get_synapse_config;
 use synapse_core::{
     CoreError,
     domain::events::Event,
+    domain::modules::EventTypeSpec,
     ports::events::event_repository::{EventFilter, EventRepository},
     ports::modules::Module,
-    verify_event_authentication,
 };
 use tracing::debug;
 
 use crate::{
     errors::ModulePostsError,
-    service::{create_post, get_posts_config, list_posts},
+    service::{create_post, get_posts_config, list_posts, page_size, posts_page_filter},
 };
 use crate::{
-    service::list_posts_for_channel,
+    service::{
+        MAX_THREAD_COMMENTS, comment_counts, comment_events, create_comment,
+        create_remote_comment, list_comments, list_posts_for_channel, list_remote_comments,
+        list_remote_posts_for_channel, validate_comment,
+    },
     types::{
-        CreatePostRequest, GetPostsConfigResult, ListPostsForChannelRequest,
+        CountCommentsRequest, CreateCommentRequest, CreatePostRequest, GetPostsConfigResult,
+        ListCommentsRequest, ListCommentsResult, ListPostsForChannelRequest,
         ListPostsForChannelResult, ListPostsRequest, ListPostsResult, ListRemotePostsRequest,
         ListRemotePostsResult, Post, PostsDeps, PostsModuleConfig,
     },
@@ -55,21 +61,30 @@ impl Module for PostsModule {
     fn version(&self) -> Result<String, CoreError> {
         Ok(self.version.clone())
     }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        vec![
+            EventTypeSpec::command("posts:create_post"),
+            EventTypeSpec::command("posts:edit"),
+            EventTypeSpec::command("posts:delete"),
+            EventTypeSpec::query("posts:list_posts").with_schema(EventFilter::schema()),
+            EventTypeSpec::query("posts:list_posts_for_channel")
+                .with_schema(EventFilter::schema()),
+            EventTypeSpec::query("posts:get_config"),
+            EventTypeSpec::command("posts:create_comment"),
+            EventTypeSpec::query("posts:list_comments").with_schema(list_comments_schema()),
+            EventTypeSpec::query("posts:count_comments").with_schema(count_comments_schema()),
+        ]
+    }
     async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
-        // Verify authentication for events that require it (federated requests)
-        verify_event_authentication(event)
-            .map_err(|e| CoreError::Authentication(e))?;
-            
         match event.event_type.as_str() {
             "posts:create_post" => create_post_handler(event).await,
+            // Authorized by the ingest pipeline; the revision is the whole effect
+            "posts:edit" | "posts:delete" => Ok(vec![]),
             "posts:list_posts" => {
+                let filter = requested_posts_filter(event, None)?;
                 let posts = self
                     .repo
-                    .retrieve(EventFilter {
-                        event_type: Some("posts:create_post".to_string()),
-                        module_kind: None,
-                        module_slug: None,
-                    })
+                    .retrieve(filter)
                     .await
                     .map_err(|e| CoreError::Other(format!("Failed to retrieve posts: {}", e)))?;
                 Ok(posts)
@@ -80,16 +95,12 @@ impl Module for PostsModule {
                     .metadata
                     .as_ref()
                     .and_then(|m| m.get("channel"))
-                    .cloned()
-                    .unwrap_or_default();
+                    .cloned();
 
+                let filter = requested_posts_filter(event, channel)?;
                 let posts = self
                     .repo
-                    .retrieve(EventFilter {
-                        event_type: Some("posts:create_post".to_string()),
-                        module_kind: None,
-                        module_slug: Some(channel),
-                    })
+                    .retrieve(filter)
                     .await
                     .map_err(|e| CoreError::Other(format!("Failed to retrieve posts: {}", e)))?;
                 Ok(posts)
@@ -108,7 +119,36 @@ impl Module for PostsModule {
                     .build();
                 Ok(vec![res_event])
             }
-            _ => Ok(vec![]),
+            "posts:create_comment" => {
+                validate_comment(self.repo.as_ref(), event).await?;
+                // Echo the comment so the requesting Synapse can show it
+                Ok(vec![event.clone()])
+            }
+            "posts:list_comments" => {
+                let request: ListCommentsRequest = requested(event)?;
+                comment_events(self.repo.as_ref(), &request).await
+            }
+            "posts:count_comments" => {
+                let request: CountCommentsRequest = requested(event)?;
+                if request.threads.len() + request.parents.len() > MAX_THREAD_COMMENTS as usize {
+                    return Err(CoreError::Validation(
+                        "too many posts to count comments for".to_string(),
+                    ));
+                }
+                let counts = comment_counts(self.repo.as_ref(), &request).await?;
+                let counts_bytes =
+                    serde_json::to_vec(&counts).map_err(|e| CoreError::Other(e.to_string()))?;
+                let synapse_config = get_synapse_config()
+                    .map_err(|e| CoreError::Other(format!("Failed to get synapse config: {}", e)))?;
+                let res_event = Event::new()
+                    .with_event_type("posts:comment_counts")
+                    .with_module_kind("posts")
+                    .with_agent(synapse_config.identity.public_key.clone())
+                    .with_data(counts_bytes)
+                    .build();
+                Ok(vec![res_event])
+            }
+            _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
         }
     }
 }
@@ -123,6 +163,8 @@ where
         .route("/posts", get(list_posts_http))
         .route("/posts", post(create_post_http))
         .route("/posts/config", get(get_posts_config_http))
+        .route("/posts/comments", get(list_comments_http))
+        .route("/posts/comments", post(create_comment_http))
         .route("/posts/{channel}", get(list_posts_for_channel_http))
         .route(
             "/synapses/{synapse_public_key}/posts",
@@ -136,6 +178,14 @@ where
             "/synapses/{synapse_public_key}/posts/config",
             get(get_posts_config_remote_http),
         )
+        .route(
+            "/synapses/{synapse_public_key}/posts/comments",
+            get(list_comments_remote_http),
+        )
+        .route(
+            "/synapses/{synapse_public_key}/posts/comments",
+            post(create_comment_remote_http),
+        )
         .route(
             "/synapses/{synapse_public_key}/posts/{channel}",
             get(list_posts_for_channel_remote_http),
@@ -146,7 +196,7 @@ async fn list_posts_http(
     axum::extract::State(deps): axum::extract::State<PostsDeps>,
     Json(body): Json<ListPostsRequest>,
 ) -> Result<(StatusCode, Json<ListPostsResult>), ModulePostsError> {
-    let posts = list_posts(deps).await.unwrap();
+    let posts = list_posts(deps, body.viewer).await.unwrap();
     Ok((StatusCode::CREATED, Json(ListPostsResult { posts })))
 }
 
@@ -166,7 +216,10 @@ async fn list_remote_events(
     Path(synapse_public_key): Path<String>,
     Json(body): Json<ListRemotePostsRequest>,
 ) -> Result<(StatusCode, Json<ListRemotePostsResult>), ModulePostsError> {
+    let filter = posts_page_filter(None, body.before, body.limit);
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "posts:list_posts".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: None,
@@ -177,7 +230,7 @@ async fn list_remote_events(
         artifacts: None,
         metadata: None,
         links: None,
-        data: None,
+        data: Some(filter.to_data()),
         expiration: None,
         agent_signature: None, // Read operations don't require signature
     };
@@ -197,8 +250,10 @@ async fn list_remote_events(
 
 async fn create_post_http(
     axum::extract::State(deps): axum::extract::State<PostsDeps>,
-    axum::Json(body): axum::Json<CreatePostRequest>,
+    agent: AuthenticatedAgent,
+    axum::Json(mut body): axum::Json<CreatePostRequest>,
 ) -> Result<(axum::http::StatusCode, axum::Json<Post>), ModulePostsError> {
+    body.agent = agent.agent;
     let post = create_post(deps, body).await.unwrap();
     Ok((axum::http::StatusCode::CREATED, axum::Json(post)))
 }
@@ -206,15 +261,18 @@ async fn create_post_http(
 async fn create_post_remote(
     axum::extract::State(deps): axum::extract::State<PostsDeps>,
     axum::extract::Path(synapse_public_key): axum::extract::Path<String>,
+    agent: AuthenticatedAgent,
     axum::Json(body): axum::Json<CreatePostRequest>,
 ) -> Result<(axum::http::StatusCode, axum::Json<Vec<Event>>), axum::http::StatusCode> {
     use synapse_application::events::{CreateEventCommand, CreateRemoteEventCommand};
 
     let inner = CreateEventCommand {
+        id: body.id,
+        created_at: body.created_at,
         event_type: "posts:create_post".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: body.module_slug,
-        agent: body.agent, // Use the actual agent public key from the request
+        agent: agent.agent,
         target: body.target,
         previous: body.previous,
         content: body.content,
@@ -250,6 +308,8 @@ async fn get_posts_config_remote_http(
     Path(synapse_public_key): Path<String>,
 ) -> Result<(StatusCode, Json<GetPostsConfigResult>), ModulePostsError> {
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "posts:get_config".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: None,
@@ -295,68 +355,50 @@ async fn list_posts_for_channel_remote_http(
     axum::extract::State(deps): axum::extract::State<PostsDeps>,
     Path((synapse_public_key, channel)): Path<(String, String)>,
 ) -> Result<(StatusCode, Json<Vec<Post>>), ModulePostsError> {
-    let mut metadata = std::collections::HashMap::new();
-    metadata.insert("channel".to_string(), channel);
+    let posts =
+        list_remote_posts_for_channel(deps, synapse_public_key, channel, None, None, None).await?;
+    Ok((StatusCode::OK, Json(posts)))
+}
 
-    let inner = CreateEventCommand {
-        event_type: "posts:list_posts_for_channel".to_string(),
-        module_kind: Some("posts".to_string()),
-        module_slug: None,
-        agent: "guest".to_string(), // Read operations don't require specific identity
-        target: None,
-        previous: None,
-        content: None,
-        artifacts: None,
-        metadata: Some(metadata),
-        links: None,
-        data: None,
-        expiration: None,
-        agent_signature: None, // Read operations don't require signature
-    };
+/// List the reply tree under a post or comment (local)
+async fn list_comments_http(
+    axum::extract::State(deps): axum::extract::State<PostsDeps>,
+    Json(body): Json<ListCommentsRequest>,
+) -> Result<(StatusCode, Json<ListCommentsResult>), ModulePostsError> {
+    let comments = list_comments(deps, body).await?;
+    Ok((StatusCode::OK, Json(ListCommentsResult { comments })))
+}
 
-    let cmd = CreateRemoteEventCommand {
-        synapse_public_key: synapse_public_key.clone(),
-        event: inner,
-    };
+async fn create_comment_http(
+    axum::extract::State(deps): axum::extract::State<PostsDeps>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<CreateCommentRequest>,
+) -> Result<(StatusCode, Json<Post>), ModulePostsError> {
+    body.agent = agent.agent;
+    let comment = create_comment(deps, body).await?;
+    Ok((StatusCode::CREATED, Json(comment)))
+}
 
-    let events = deps.create_remote_event.execute(cmd).await?;
-
-    // Convert events to Posts
-    let mut posts = Vec::new();
-    for event in events {
-        // Try to get profile info for each post author
-        let (author_name, author_handle) =
-            if let Ok(Some(profile)) = deps.profile_repo.get_profile(&event.agent).await {
-                (
-                    profile.display_name.unwrap_or_else(|| "Unknown".to_string()),
-                    profile.handle.unwrap_or_else(|| "unknown".to_string()),
-                )
-            } else {
-                // Fallback: use truncated public key
-                let short_pk = if event.agent.len() > 8 {
-                    format!("{}...", &event.agent[..8])
-                } else {
-                    event.agent.clone()
-                };
-                (short_pk.clone(), short_pk)
-            };
-
-        posts.push(Post {
-            id: event.id.to_string(),
-            author_public_key: event.agent.clone(),
-            author_name,
-            author_handle,
-            author_avatar: "AvatarPath".to_string(),
-            timestamp: "TimeStamp".to_string(),
-            content: event.content.unwrap_or_default(),
-            posted_in: event.module_slug.unwrap_or_default(),
-            likes: 0,
-            comments: 0,
-            liked: false,
-        });
-    }
+/// List the reply tree under a post or comment of a remote synapse
+async fn list_comments_remote_http(
+    axum::extract::State(deps): axum::extract::State<PostsDeps>,
+    Path(synapse_public_key): Path<String>,
+    Json(body): Json<ListCommentsRequest>,
+) -> Result<(StatusCode, Json<ListCommentsResult>), ModulePostsError> {
+    let comments = list_remote_comments(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::OK, Json(ListCommentsResult { comments })))
+}
 
-    Ok((StatusCode::OK, Json(posts)))
+/// Comment on a post of a remote synapse
+async fn create_comment_remote_http(
+    axum::extract::State(deps): axum::extract::State<PostsDeps>,
+    Path(synapse_public_key): Path<String>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<CreateCommentRequest>,
+) -> Result<(StatusCode, Json<Post>), ModulePostsError> {
+    body.agent = agent.agent;
+    let comment = create_remote_comment(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::CREATED, Json(comment)))
 }
 
 async fn create_post_handler(event: &Event) -> Result<Vec<Event>, CoreError> {
@@ -373,3 +415,51 @@ async fn create_post_handler(event: &Event) -> Result<Vec<Event>, CoreError> {
     let events = vec![res_event];
     Ok(events)
 }
+
+/// Builds the posts query for a (possibly remote) `posts:list_*` event.
+///
+/// Paging and filters come from the event data; the event type is always
+/// posts and the page size is clamped.
+fn requested_posts_filter(event: &Event, channel: Option<String>) -> Result<EventFilter, CoreError> {
+    let mut filter = EventFilter::from_event(event)?;
+    filter.event_type = Some("posts:create_post".to_string());
+    if channel.is_some() {
+        filter.module_slug = channel;
+    }
+    filter.limit = Some(page_size(filter.limit));
+    Ok(filter)
+}
+
+/// Decodes the query carried in the data of a (possibly remote) event.
+fn requested<T: serde::de::DeserializeOwned>(event: &Event) -> Result<T, CoreError> {
+    let data = event
+        .data
+        .as_deref()
+        .ok_or_else(|| CoreError::Validation(format!("{} requires data", event.event_type)))?;
+    serde_json::from_slice(data)
+        .map_err(|e| CoreError::Validation(format!("invalid {} data: {e}", event.event_type)))
+}
+
+fn list_comments_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "required": ["parent"],
+        "properties": {
+            "parent": { "type": "string", "format": "uuid" },
+            "depth": { "type": ["integer", "null"], "minimum": 1 },
+            "after": { "type": ["string", "null"], "format": "uuid" },
+            "limit": { "type": ["integer", "null"], "minimum": 0 }
+        }
+    })
+}
+
+fn count_comments_schema() -> serde_json::Value {
+    let ids = serde_json::json!({ "type": "array", "items": { "type": "string", "format": "uuid" } });
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "threads": ids,
+            "parents": ids
+        }
+    })
+}
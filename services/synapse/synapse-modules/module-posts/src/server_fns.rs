@@ -2,18 +2,22 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 use leptos::prelude::*;
+use uuid::Uuid;
 
-use crate::types::{CreatePostRequest, ListPostsForChannelRequest, Post, PostsModuleConfig};
+use crate::types::{
+    CommentNode, CreateCommentRequest, CreatePostRequest, ListCommentsRequest,
+    ListPostsForChannelRequest, Post, PostsModuleConfig,
+};
 
 #[cfg(feature = "ssr")]
 use crate::types::PostsDeps;
 
 #[cfg(feature = "ssr")]
 #[server(ListPosts, "/api/posts")]
-pub async fn list_posts_server() -> Result<Vec<Post>, ServerFnError> {
+pub async fn list_posts_server(viewer: Option<String>) -> Result<Vec<Post>, ServerFnError> {
     use crate::service::list_posts;
     let deps: PostsDeps = expect_context();
-    let posts = list_posts(deps).await.unwrap();
+    let posts = list_posts(deps, viewer).await.unwrap();
     Ok(posts)
 }
 
@@ -30,11 +34,46 @@ pub async fn list_posts_for_channel_server(
 #[server(CreatePostServer, "/api/posts")]
 pub async fn create_post_server(request: CreatePostRequest) -> Result<Post, ServerFnError> {
     use crate::service::create_post;
+    use module_auth::session::authenticated_agent;
+    let request = CreatePostRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
     let deps: PostsDeps = expect_context();
     let post = create_post(deps, request).await.unwrap();
     Ok(post)
 }
 
+/// List the reply tree under a post or comment
+#[server(ListComments, "/api/posts")]
+pub async fn list_comments_server(
+    request: ListCommentsRequest,
+) -> Result<Vec<CommentNode>, ServerFnError> {
+    use crate::service::list_comments;
+    let deps: PostsDeps = expect_context();
+    let comments = list_comments(deps, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(comments)
+}
+
+#[server(CreateCommentServer, "/api/posts")]
+pub async fn create_comment_server(
+    request: CreateCommentRequest,
+) -> Result<Post, ServerFnError> {
+    use crate::service::create_comment;
+    use module_auth::session::authenticated_agent;
+    let request = CreateCommentRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: PostsDeps = expect_context();
+    let comment = create_comment(deps, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(comment)
+}
+
 /// Get the posts module configuration (channels, default channel, max post length)
 #[server(GetPostsConfig, "/api/posts")]
 pub async fn get_posts_config_server() -> Result<PostsModuleConfig, ServerFnError> {
@@ -65,12 +104,16 @@ pub async fn get_remote_posts_config_server(
 pub async fn list_remote_posts_for_channel_server(
     synapse_public_key: String,
     channel: String,
+    before: Option<Uuid>,
+    limit: Option<u32>,
+    viewer: Option<String>,
 ) -> Result<Vec<Post>, ServerFnError> {
     use crate::service::list_remote_posts_for_channel;
     let deps: PostsDeps = expect_context();
-    let posts = list_remote_posts_for_channel(deps, synapse_public_key, channel)
-        .await
-        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    let posts =
+        list_remote_posts_for_channel(deps, synapse_public_key, channel, before, limit, viewer)
+            .await
+            .map_err(|e| ServerFnError::new(e.to_string()))?;
     Ok(posts)
 }
 
@@ -81,9 +124,47 @@ pub async fn create_remote_post_server(
     request: CreatePostRequest,
 ) -> Result<Post, ServerFnError> {
     use crate::service::create_remote_post;
+    use module_auth::session::authenticated_agent;
+    let request = CreatePostRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
     let deps: PostsDeps = expect_context();
     let post = create_remote_post(deps, synapse_public_key, request)
         .await
         .map_err(|e| ServerFnError::new(e.to_string()))?;
     Ok(post)
 }
+
+/// List the reply tree under a post or comment of a remote synapse
+#[server(ListRemoteComments, "/api/posts")]
+pub async fn list_remote_comments_server(
+    synapse_public_key: String,
+    request: ListCommentsRequest,
+) -> Result<Vec<CommentNode>, ServerFnError> {
+    use crate::service::list_remote_comments;
+    let deps: PostsDeps = expect_context();
+    let comments = list_remote_comments(deps, synapse_public_key, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(comments)
+}
+
+/// Comment on a post of a remote synapse, which stores the comment
+#[server(CreateRemoteCommentServer, "/api/posts")]
+pub async fn create_remote_comment_server(
+    synapse_public_key: String,
+    request: CreateCommentRequest,
+) -> Result<Post, ServerFnError> {
+    use crate::service::create_remote_comment;
+    use module_auth::session::authenticated_agent;
+    let request = CreateCommentRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: PostsDeps = expect_context();
+    let comment = create_remote_comment(deps, synapse_public_key, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))?;
+    Ok(comment)
+}
@@ -8,20 +8,57 @@ use crate::types::ListPostsForChannelRequest;
 use crate::types::Post;
 use crate::types::PostsDeps;
 use crate::types::PostsModuleConfig;
+use crate::types::{
+    CommentCounts, CommentNode, CountCommentsRequest, CreateCommentRequest, ListCommentsRequest,
+    comment_metadata,
+};
+use crate::types::{DEFAULT_COMMENT_DEPTH, MAX_COMMENT_DEPTH, PARENT_METADATA_KEY, THREAD_METADATA_KEY};
+use crate::types::{DEFAULT_PAGE_SIZE, LIKE_EMOJI, MAX_PAGE_SIZE};
+use module_reactions::service::remote_reaction_summaries;
+use module_reactions::types::SummarizeReactionsRequest;
+use std::collections::HashMap;
 use synapse_application::events::{CreateEventCommand, CreateRemoteEventCommand};
-use synapse_core::ports::events::event_repository::EventFilter;
+use synapse_core::CoreError;
+use synapse_core::domain::events::reactions::ReactionSummary;
+use synapse_core::domain::events::{Event, ObjectRef};
+use synapse_core::ports::events::event_repository::{
+    EventCursor, EventFilter, EventOrder, EventRepository,
+};
+use time::OffsetDateTime;
+use uuid::Uuid;
 
-pub async fn list_posts(deps: PostsDeps) -> Result<Vec<Post>, ModulePostsError> {
+/// Clamps a requested page size to `1..=MAX_PAGE_SIZE`.
+pub fn page_size(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Query for one page of posts, newest first.
+pub fn posts_page_filter(
+    channel: Option<String>,
+    before: Option<Uuid>,
+    limit: Option<u32>,
+) -> EventFilter {
+    EventFilter {
+        event_type: Some("posts:create_post".to_string()),
+        module_slug: channel,
+        before: before.map(EventCursor::Id),
+        limit: Some(page_size(limit)),
+        ..Default::default()
+    }
+}
+
+pub async fn list_posts(
+    deps: PostsDeps,
+    viewer: Option<String>,
+) -> Result<Vec<Post>, ModulePostsError> {
     let events = deps
         .repo
-        .retrieve(EventFilter {
-            event_type: Some("posts:create_post".to_string()),
-            module_kind: None,
-            module_slug: None,
-        })
+        .retrieve(posts_page_filter(None, None, None))
         .await
         .unwrap();
 
+    let counts = thread_counts(deps.repo.as_ref(), &events).await?;
+    let reactions = reaction_summaries(&deps, &events, viewer.as_deref()).await?;
     let mut posts: Vec<Post> = Vec::new();
 
     for event in events {
@@ -38,13 +75,15 @@ pub async fn list_posts(deps: PostsDeps) -> Result<Vec<Post>, ModulePostsError>
             author_handle: profile.handle.unwrap().clone(),
             author_avatar: "AvatarPath".to_string(),
             timestamp: "TimeStamp".to_string(),
-            content: event.content.unwrap_or_default(),
+            content: event.current_content().unwrap_or_default().to_string(),
             posted_in: event.module_slug.unwrap(),
             likes: 0,
-            comments: 0,
+            comments: comment_count(&counts, event.id),
             liked: false,
+            reactions: Vec::new(),
         });
     }
+    apply_reactions(&mut posts, reactions);
     Ok(posts)
 }
 
@@ -54,14 +93,16 @@ pub async fn list_posts_for_channel(
 ) -> Result<Vec<Post>, ModulePostsError> {
     let events = deps
         .repo
-        .retrieve(EventFilter {
-            event_type: Some("posts:create_post".to_string()),
-            module_kind: None,
-            module_slug: Some(request.channel),
-        })
+        .retrieve(posts_page_filter(
+            Some(request.channel),
+            request.before,
+            request.limit,
+        ))
         .await
         .unwrap();
 
+    let counts = thread_counts(deps.repo.as_ref(), &events).await?;
+    let reactions = reaction_summaries(&deps, &events, request.viewer.as_deref()).await?;
     let mut posts: Vec<Post> = Vec::new();
 
     for event in events {
@@ -78,13 +119,15 @@ pub async fn list_posts_for_channel(
             author_handle: profile.handle.unwrap().clone(),
             author_avatar: "AvatarPath".to_string(),
             timestamp: "TimeStamp".to_string(),
-            content: event.content.unwrap_or_default(),
+            content: event.current_content().unwrap_or_default().to_string(),
             posted_in: event.module_slug.unwrap(),
             likes: 0,
-            comments: 0,
+            comments: comment_count(&counts, event.id),
             liked: false,
+            reactions: Vec::new(),
         });
     }
+    apply_reactions(&mut posts, reactions);
 
     Ok(posts)
 }
@@ -94,6 +137,8 @@ pub async fn create_post(
     request: CreatePostRequest,
 ) -> Result<Post, ModulePostsError> {
     let cmd = CreateEventCommand {
+        id: request.id,
+        created_at: request.created_at,
         event_type: "posts:create_post".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: request.module_slug,
@@ -128,6 +173,7 @@ pub async fn create_post(
         likes: 0,
         comments: 0,
         liked: false,
+        reactions: Vec::new(),
     };
 
     Ok(post)
@@ -153,6 +199,8 @@ pub async fn get_remote_posts_config(
     synapse_public_key: String,
 ) -> Result<PostsModuleConfig, ModulePostsError> {
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "posts:get_config".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: None,
@@ -195,6 +243,8 @@ pub async fn create_remote_post(
     request: CreatePostRequest,
 ) -> Result<Post, ModulePostsError> {
     let inner = CreateEventCommand {
+        id: request.id,
+        created_at: request.created_at,
         event_type: "posts:create_post".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: request.module_slug,
@@ -248,6 +298,7 @@ pub async fn create_remote_post(
             likes: 0,
             comments: 0,
             liked: false,
+            reactions: Vec::new(),
         })
     } else {
         Err(ModulePostsError::Internal(
@@ -261,11 +312,17 @@ pub async fn list_remote_posts_for_channel(
     deps: PostsDeps,
     synapse_public_key: String,
     channel: String,
+    before: Option<Uuid>,
+    limit: Option<u32>,
+    viewer: Option<String>,
 ) -> Result<Vec<Post>, ModulePostsError> {
+    let filter = posts_page_filter(Some(channel.clone()), before, limit);
     let mut metadata = std::collections::HashMap::new();
     metadata.insert("channel".to_string(), channel);
 
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "posts:list_posts_for_channel".to_string(),
         module_kind: Some("posts".to_string()),
         module_slug: None,
@@ -276,17 +333,37 @@ pub async fn list_remote_posts_for_channel(
         artifacts: None,
         metadata: Some(metadata),
         links: None,
-        data: None,
+        data: Some(filter.to_data()),
         expiration: None,
         agent_signature: None, // Read operations don't require signature
     };
 
     let cmd = CreateRemoteEventCommand {
-        synapse_public_key,
+        synapse_public_key: synapse_public_key.clone(),
         event: inner,
     };
 
     let events = deps.create_remote_event.execute(cmd).await?;
+    let counts = remote_comment_counts(
+        &deps,
+        &synapse_public_key,
+        CountCommentsRequest {
+            threads: events.iter().map(|e| e.id).collect(),
+            ..Default::default()
+        },
+    )
+    .await
+    .threads;
+    let reactions = remote_reaction_summaries(
+        deps.create_remote_event.as_ref(),
+        &synapse_public_key,
+        &SummarizeReactionsRequest {
+            targets: events.iter().map(|e| e.id).collect(),
+            viewer,
+        },
+    )
+    .await
+    .summaries;
 
     // Convert events to Posts
     let mut posts = Vec::new();
@@ -315,13 +392,494 @@ pub async fn list_remote_posts_for_channel(
             author_handle,
             author_avatar: "AvatarPath".to_string(),
             timestamp: "TimeStamp".to_string(),
-            content: event.content.unwrap_or_default(),
+            content: event.current_content().unwrap_or_default().to_string(),
             posted_in: event.module_slug.unwrap_or_default(),
             likes: 0,
-            comments: 0,
+            comments: comment_count(&counts, event.id),
             liked: false,
+            reactions: Vec::new(),
         });
     }
+    apply_reactions(&mut posts, reactions);
 
     Ok(posts)
 }
+
+// =============================================================================
+// Comments
+// =============================================================================
+
+/// Most threads and comments one `posts:count_comments` request may count.
+pub const MAX_THREAD_COMMENTS: u32 = 1000;
+
+/// Clamps a requested reply depth to `1..=MAX_COMMENT_DEPTH`.
+pub fn comment_depth(depth: Option<u32>) -> u32 {
+    depth.unwrap_or(DEFAULT_COMMENT_DEPTH).clamp(1, MAX_COMMENT_DEPTH)
+}
+
+/// The post or comment a comment replies to.
+pub fn parent_of(event: &Event) -> Option<Uuid> {
+    match event.target {
+        Some(ObjectRef::Event(parent)) => Some(parent),
+        _ => None,
+    }
+}
+
+/// The post whose thread a comment belongs to.
+pub fn thread_of(event: &Event) -> Option<Uuid> {
+    event
+        .metadata
+        .as_ref()
+        .and_then(|m| m.get(THREAD_METADATA_KEY))
+        .and_then(|thread| thread.parse().ok())
+}
+
+/// Checks that `parent` is a live post or comment stored on this Synapse,
+/// belonging to thread `thread` in `channel`.
+pub async fn validate_reply(
+    repo: &dyn EventRepository,
+    parent: Uuid,
+    thread: Uuid,
+    channel: Option<&str>,
+) -> Result<(), CoreError> {
+    let replied_to = repo
+        .find(parent)
+        .await?
+        .filter(|e| !e.is_expired(OffsetDateTime::now_utc()))
+        .ok_or_else(|| CoreError::NotFound(format!("post or comment {parent}")))?;
+    let replied_thread = match replied_to.event_type.as_str() {
+        "posts:create_post" => Some(replied_to.id),
+        "posts:create_comment" => thread_of(&replied_to),
+        _ => None,
+    };
+    if replied_thread != Some(thread) {
+        return Err(CoreError::Validation(format!(
+            "{parent} is not a post or comment of thread {thread}"
+        )));
+    }
+    if replied_to.module_slug.as_deref() != channel {
+        return Err(CoreError::Validation(
+            "comments must be posted in the channel of their thread".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a `posts:create_comment` event: it must target the post or comment
+/// it replies to and name it and its thread in the metadata.
+pub async fn validate_comment(repo: &dyn EventRepository, event: &Event) -> Result<(), CoreError> {
+    if event.content.as_deref().is_none_or(|c| c.trim().is_empty()) {
+        return Err(CoreError::Validation("comment must not be empty".to_string()));
+    }
+    let (Some(parent), Some(thread)) = (parent_of(event), thread_of(event)) else {
+        return Err(CoreError::Validation(
+            "comment must target a post or comment and name its thread".to_string(),
+        ));
+    };
+    if event.metadata.as_ref() != Some(&comment_metadata(parent, thread)) {
+        return Err(CoreError::Validation(format!(
+            "comment metadata must only name parent {parent} and thread {thread}"
+        )));
+    }
+    validate_reply(repo, parent, thread, event.module_slug.as_deref()).await
+}
+
+/// The comments answering `request`: one page of direct replies to its
+/// parent, oldest first, followed by their replies down to the requested
+/// depth.
+pub async fn comment_events(
+    repo: &dyn EventRepository,
+    request: &ListCommentsRequest,
+) -> Result<Vec<Event>, CoreError> {
+    let mut comments = repo
+        .retrieve(EventFilter {
+            event_type: Some("posts:create_comment".to_string()),
+            target: Some(ObjectRef::Event(request.parent)),
+            after: request.after.map(EventCursor::Id),
+            order: EventOrder::OldestFirst,
+            limit: Some(page_size(request.limit)),
+            ..Default::default()
+        })
+        .await?;
+    let depth = comment_depth(request.depth);
+
+    // Each deeper level is read in one query over the replies to the level above
+    let mut level: Vec<ObjectRef> = comments.iter().map(|c| ObjectRef::Event(c.id)).collect();
+    for _ in 1..depth {
+        if level.is_empty() {
+            break;
+        }
+        let replies = repo
+            .retrieve(EventFilter {
+                event_type: Some("posts:create_comment".to_string()),
+                targets: Some(level),
+                order: EventOrder::OldestFirst,
+                ..Default::default()
+            })
+            .await?;
+        level = replies.iter().map(|c| ObjectRef::Event(c.id)).collect();
+        comments.extend(replies);
+    }
+    Ok(comments)
+}
+
+/// Arranges `comments` into the reply tree under `parent`, keeping their
+/// order and stopping `depth` levels down.
+pub fn comment_tree(parent: Uuid, comments: &[(Event, Post)], depth: u32) -> Vec<CommentNode> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    comments
+        .iter()
+        .filter(|(event, _)| parent_of(event) == Some(parent))
+        .map(|(event, comment)| CommentNode {
+            comment: comment.clone(),
+            replies: comment_tree(event.id, comments, depth - 1),
+        })
+        .collect()
+}
+
+/// Counts the comments asked for by `request` in this Synapse's event log.
+pub async fn comment_counts(
+    repo: &dyn EventRepository,
+    request: &CountCommentsRequest,
+) -> Result<CommentCounts, CoreError> {
+    let count = async |key: &str, ids: &[Uuid]| {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let ids: Vec<String> = ids.iter().map(Uuid::to_string).collect();
+        repo.count_by_metadata("posts:create_comment", key, &ids)
+            .await
+    };
+    Ok(CommentCounts {
+        threads: count(THREAD_METADATA_KEY, &request.threads).await?,
+        parents: count(PARENT_METADATA_KEY, &request.parents).await?,
+    })
+}
+
+/// Comment counts of the threads of `posts`.
+async fn thread_counts(
+    repo: &dyn EventRepository,
+    posts: &[Event],
+) -> Result<HashMap<String, u64>, CoreError> {
+    let request = CountCommentsRequest {
+        threads: posts.iter().map(|e| e.id).collect(),
+        ..Default::default()
+    };
+    Ok(comment_counts(repo, &request).await?.threads)
+}
+
+/// Reactions held on `events`, as seen by `viewer`.
+async fn reaction_summaries(
+    deps: &PostsDeps,
+    events: &[Event],
+    viewer: Option<&str>,
+) -> Result<HashMap<Uuid, Vec<ReactionSummary>>, CoreError> {
+    let targets: Vec<Uuid> = events.iter().map(|e| e.id).collect();
+    Ok(deps.reactions.summaries(&targets, viewer).await?)
+}
+
+/// Sets `likes` and `liked` from the `LIKE_EMOJI` reactions of `post`.
+fn like(post: &mut Post) {
+    if let Some(like) = post.reactions.iter().find(|r| r.emoji == LIKE_EMOJI) {
+        post.likes = like.count;
+        post.liked = like.reacted;
+    }
+}
+
+/// Attaches `reactions` to the posts they are held on.
+fn apply_reactions(posts: &mut [Post], mut reactions: HashMap<Uuid, Vec<ReactionSummary>>) {
+    for post in posts.iter_mut() {
+        if let Ok(id) = post.id.parse::<Uuid>()
+            && let Some(held) = reactions.remove(&id)
+        {
+            post.reactions = held;
+            like(post);
+        }
+    }
+}
+
+fn comment_count(counts: &HashMap<String, u64>, id: Uuid) -> u32 {
+    counts
+        .get(&id.to_string())
+        .map_or(0, |&count| u32::try_from(count).unwrap_or(u32::MAX))
+}
+
+/// Display name and handle of `agent`, falling back to its truncated public
+/// key for agents without a profile here.
+async fn author_names(deps: &PostsDeps, agent: &str) -> (String, String) {
+    if let Ok(Some(profile)) = deps.profile_repo.get_profile(agent).await {
+        return (
+            profile.display_name.unwrap_or_else(|| "Unknown".to_string()),
+            profile.handle.unwrap_or_else(|| "unknown".to_string()),
+        );
+    }
+    let short_pk = if agent.len() > 8 {
+        format!("{}...", &agent[..8])
+    } else {
+        agent.to_string()
+    };
+    (short_pk.clone(), short_pk)
+}
+
+/// Pairs each comment event with its `Post` view, counting direct replies
+/// from `replies`.
+async fn comment_posts(
+    deps: &PostsDeps,
+    events: Vec<Event>,
+    replies: &HashMap<String, u64>,
+    mut reactions: HashMap<Uuid, Vec<ReactionSummary>>,
+) -> Vec<(Event, Post)> {
+    let mut comments = Vec::with_capacity(events.len());
+    for event in events {
+        let (author_name, author_handle) = author_names(deps, &event.agent).await;
+        let mut post = Post {
+            id: event.id.to_string(),
+            author_public_key: event.agent.clone(),
+            author_name,
+            author_handle,
+            author_avatar: "AvatarPath".to_string(),
+            timestamp: "TimeStamp".to_string(),
+            content: event.current_content().unwrap_or_default().to_string(),
+            posted_in: event.module_slug.clone().unwrap_or_default(),
+            likes: 0,
+            comments: comment_count(replies, event.id),
+            liked: false,
+            reactions: reactions.remove(&event.id).unwrap_or_default(),
+        };
+        like(&mut post);
+        comments.push((event, post));
+    }
+    comments
+}
+
+fn comment_command(request: CreateCommentRequest) -> CreateEventCommand {
+    CreateEventCommand {
+        id: request.id,
+        created_at: request.created_at,
+        event_type: "posts:create_comment".to_string(),
+        module_kind: Some("posts".to_string()),
+        module_slug: request.module_slug,
+        agent: request.agent,
+        target: Some(ObjectRef::Event(request.parent)),
+        previous: None,
+        content: Some(request.content),
+        artifacts: None,
+        metadata: Some(comment_metadata(request.parent, request.thread)),
+        links: None,
+        data: None,
+        expiration: None,
+        agent_signature: request.agent_signature,
+    }
+}
+
+/// A read-only request for a remote Synapse, carrying its query in `data`.
+fn remote_query(synapse_public_key: &str, event_type: &str, data: Vec<u8>) -> CreateRemoteEventCommand {
+    CreateRemoteEventCommand {
+        synapse_public_key: synapse_public_key.to_string(),
+        event: CreateEventCommand {
+            id: None,
+            created_at: None,
+            event_type: event_type.to_string(),
+            module_kind: Some("posts".to_string()),
+            module_slug: None,
+            agent: "guest".to_string(), // Read operations don't require specific identity
+            target: None,
+            previous: None,
+            content: None,
+            artifacts: None,
+            metadata: None,
+            links: None,
+            data: Some(data),
+            expiration: None,
+            agent_signature: None, // Read operations don't require signature
+        },
+    }
+}
+
+/// Lists the reply tree under a post or comment of this Synapse.
+pub async fn list_comments(
+    deps: PostsDeps,
+    request: ListCommentsRequest,
+) -> Result<Vec<CommentNode>, ModulePostsError> {
+    let events = comment_events(deps.repo.as_ref(), &request).await?;
+    let parents = CountCommentsRequest {
+        parents: events.iter().map(|e| e.id).collect(),
+        ..Default::default()
+    };
+    let replies = comment_counts(deps.repo.as_ref(), &parents).await?.parents;
+    let reactions = reaction_summaries(&deps, &events, request.viewer.as_deref()).await?;
+    let comments = comment_posts(&deps, events, &replies, reactions).await;
+    Ok(comment_tree(
+        request.parent,
+        &comments,
+        comment_depth(request.depth),
+    ))
+}
+
+pub async fn create_comment(
+    deps: PostsDeps,
+    request: CreateCommentRequest,
+) -> Result<Post, ModulePostsError> {
+    if request.content.trim().is_empty() {
+        return Err(ModulePostsError::BadRequest(
+            "comment must not be empty".to_string(),
+        ));
+    }
+    validate_reply(
+        deps.repo.as_ref(),
+        request.parent,
+        request.thread,
+        request.module_slug.as_deref(),
+    )
+    .await
+    .map_err(|e| ModulePostsError::BadRequest(e.to_string()))?;
+
+    let event = deps
+        .create_local_event
+        .execute(comment_command(request))
+        .await?;
+    let mut comments = comment_posts(&deps, vec![event], &HashMap::new(), HashMap::new()).await;
+    Ok(comments.remove(0).1)
+}
+
+/// Comment counts from a remote Synapse. Synapses that predate comments
+/// cannot answer and are treated as having none.
+async fn remote_comment_counts(
+    deps: &PostsDeps,
+    synapse_public_key: &str,
+    request: CountCommentsRequest,
+) -> CommentCounts {
+    let Ok(data) = serde_json::to_vec(&request) else {
+        return CommentCounts::default();
+    };
+    let cmd = remote_query(synapse_public_key, "posts:count_comments", data);
+    match deps.create_remote_event.execute(cmd).await {
+        Ok(events) => events
+            .iter()
+            .find(|e| e.event_type == "posts:comment_counts")
+            .and_then(|e| e.data.as_deref())
+            .and_then(|data| serde_json::from_slice(data).ok())
+            .unwrap_or_default(),
+        Err(e) => {
+            tracing::debug!("no comment counts from {synapse_public_key}: {e}");
+            CommentCounts::default()
+        }
+    }
+}
+
+/// Lists the reply tree under a post or comment of a remote synapse
+pub async fn list_remote_comments(
+    deps: PostsDeps,
+    synapse_public_key: String,
+    request: ListCommentsRequest,
+) -> Result<Vec<CommentNode>, ModulePostsError> {
+    let data = serde_json::to_vec(&request).map_err(|e| ModulePostsError::Internal(e.to_string()))?;
+    let cmd = remote_query(&synapse_public_key, "posts:list_comments", data);
+    let events: Vec<Event> = deps
+        .create_remote_event
+        .execute(cmd)
+        .await?
+        .into_iter()
+        .filter(|e| e.event_type == "posts:create_comment")
+        .collect();
+
+    let parents = CountCommentsRequest {
+        parents: events.iter().map(|e| e.id).collect(),
+        ..Default::default()
+    };
+    let replies = remote_comment_counts(&deps, &synapse_public_key, parents)
+        .await
+        .parents;
+    let reactions = remote_reaction_summaries(
+        deps.create_remote_event.as_ref(),
+        &synapse_public_key,
+        &SummarizeReactionsRequest {
+            targets: events.iter().map(|e| e.id).collect(),
+            viewer: request.viewer.clone(),
+        },
+    )
+    .await
+    .summaries;
+    let comments = comment_posts(&deps, events, &replies, reactions).await;
+    Ok(comment_tree(
+        request.parent,
+        &comments,
+        comment_depth(request.depth),
+    ))
+}
+
+/// Comment on a post of a remote synapse, which stores the comment
+pub async fn create_remote_comment(
+    deps: PostsDeps,
+    synapse_public_key: String,
+    request: CreateCommentRequest,
+) -> Result<Post, ModulePostsError> {
+    let cmd = CreateRemoteEventCommand {
+        synapse_public_key,
+        event: comment_command(request),
+    };
+    let events = deps.create_remote_event.execute(cmd).await?;
+
+    // The remote synapse should return the created comment
+    let Some(event) = events
+        .into_iter()
+        .find(|e| e.event_type == "posts:create_comment")
+    else {
+        return Err(ModulePostsError::Internal(
+            "Remote synapse did not return created comment".to_string(),
+        ));
+    };
+    let mut comments = comment_posts(&deps, vec![event], &HashMap::new(), HashMap::new()).await;
+    Ok(comments.remove(0).1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(parent: Uuid, thread: Uuid) -> (Event, Post) {
+        let event = Event::new()
+            .with_event_type("posts:create_comment")
+            .with_module_kind("posts")
+            .with_target(ObjectRef::Event(parent))
+            .with_metadata(comment_metadata(parent, thread))
+            .build();
+        let post = Post {
+            id: event.id.to_string(),
+            author_public_key: String::new(),
+            author_name: String::new(),
+            author_handle: String::new(),
+            author_avatar: String::new(),
+            timestamp: String::new(),
+            content: String::new(),
+            posted_in: String::new(),
+            likes: 0,
+            comments: 0,
+            liked: false,
+            reactions: Vec::new(),
+        };
+        (event, post)
+    }
+
+    #[test]
+    fn test_comment_tree_nests_replies_down_to_depth() {
+        let post = Uuid::new_v4();
+        let first = comment(post, post);
+        let second = comment(post, post);
+        let reply = comment(first.0.id, post);
+        let nested = comment(reply.0.id, post);
+        let comments = vec![first.clone(), second.clone(), reply.clone(), nested];
+
+        let tree = comment_tree(post, &comments, 2);
+
+        let ids: Vec<&str> = tree.iter().map(|n| n.comment.id.as_str()).collect();
+        assert_eq!(ids, [first.1.id.as_str(), second.1.id.as_str()]);
+        assert_eq!(tree[0].replies.len(), 1);
+        assert_eq!(tree[0].replies[0].comment.id, reply.1.id);
+        // The third level is cut off
+        assert!(tree[0].replies[0].replies.is_empty());
+        assert!(tree[1].replies.is_empty());
+    }
+}
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use synapse_application::events::{CreateLocalEventUseCase, CreateRemoteEventUseCase};
 use synapse_core::domain::events::Event;
 use synapse_core::domain::events::ObjectRef;
+use synapse_core::domain::events::reactions::ReactionSummary;
 use synapse_core::ports::profiles::profile_repository::{
     ProfileDiscovery, ProfilesDocStore, ProfilesRepository,
 };
@@ -25,6 +26,7 @@ pub struct PostsDeps {
     pub doc_store: Arc<dyn ProfilesDocStore>,
     pub profile_repo: Arc<dyn ProfilesRepository>,
     pub profile_discovery: Arc<dyn ProfileDiscovery>,
+    pub reactions: Arc<dyn synapse_core::ports::reactions::reaction_repository::ReactionsRepository>,
 }
 
 // =============================================================================
@@ -68,14 +70,36 @@ pub struct Post {
     pub timestamp: String,
     pub content: String,
     pub posted_in: String,
+    /// Reactions with `LIKE_EMOJI`.
     pub likes: u32,
     pub comments: u32,
+    /// Whether the viewer reacted with `LIKE_EMOJI`.
     pub liked: bool,
+    /// Every reaction held on the post, most used first.
+    #[serde(default)]
+    pub reactions: Vec<ReactionSummary>,
 }
 
+/// The reaction a post's like button adds.
+pub const LIKE_EMOJI: &str = "❤️";
+
+/// Default number of posts returned per page.
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Largest page a client (local or remote) may request.
+pub const MAX_PAGE_SIZE: u32 = 200;
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ListPostsRequest {
     pub event_type: String,
+    /// Return posts older than this post id.
+    #[serde(default)]
+    pub before: Option<Uuid>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Agent whose reactions are flagged on the returned posts.
+    #[serde(default)]
+    pub viewer: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -87,6 +111,14 @@ pub struct ListPostsResult {
 pub struct ListPostsForChannelRequest {
     pub event_type: String,
     pub channel: String,
+    /// Return posts older than this post id.
+    #[serde(default)]
+    pub before: Option<Uuid>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Agent whose reactions are flagged on the returned posts.
+    #[serde(default)]
+    pub viewer: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -97,6 +129,11 @@ pub struct ListPostsForChannelResult {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ListRemotePostsRequest {
     pub event_type: String,
+    /// Return posts older than this post id.
+    #[serde(default)]
+    pub before: Option<Uuid>,
+    #[serde(default)]
+    pub limit: Option<u32>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -106,7 +143,15 @@ pub struct ListRemotePostsResult {
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct CreatePostRequest {
+    /// Event id chosen by the client, so a retried request is not posted twice.
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    /// Creation time covered by `agent_signature`; kept by the Synapse as is.
+    #[serde(default)]
+    pub created_at: Option<OffsetDateTime>,
     pub event_type: String,
+    /// Replaced by the agent of the session the request is made with.
+    #[serde(default)]
     pub agent: String,
     pub module_kind: Option<String>,
     pub module_slug: Option<String>,
@@ -123,3 +168,105 @@ pub struct CreatePostRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent_signature: Option<String>,
 }
+
+// =============================================================================
+// Comments
+// =============================================================================
+
+/// Metadata key of a comment naming the post it belongs to, however deeply it
+/// is nested.
+pub const THREAD_METADATA_KEY: &str = "thread";
+
+/// Metadata key of a comment naming the post or comment it replies to; always
+/// the same as its `ObjectRef::Event` target.
+pub const PARENT_METADATA_KEY: &str = "parent";
+
+/// Reply levels returned when a request does not say.
+pub const DEFAULT_COMMENT_DEPTH: u32 = 3;
+
+/// Deepest reply tree a client (local or remote) may request.
+pub const MAX_COMMENT_DEPTH: u32 = 8;
+
+/// Query for the replies to a post or comment, carried in the data of
+/// `posts:list_comments` events.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ListCommentsRequest {
+    /// The post or comment whose replies are listed.
+    pub parent: Uuid,
+    /// Levels of replies to include, clamped to `1..=MAX_COMMENT_DEPTH`.
+    #[serde(default)]
+    pub depth: Option<u32>,
+    /// Return direct replies newer than this comment id.
+    #[serde(default)]
+    pub after: Option<Uuid>,
+    /// Direct replies per page.
+    #[serde(default)]
+    pub limit: Option<u32>,
+    /// Agent whose reactions are flagged on the returned comments.
+    #[serde(default)]
+    pub viewer: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CreateCommentRequest {
+    /// Event id chosen by the client, so a retried request is not posted twice.
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    /// Creation time covered by `agent_signature`; kept by the Synapse as is.
+    #[serde(default)]
+    pub created_at: Option<OffsetDateTime>,
+    /// Replaced by the agent of the session the request is made with.
+    #[serde(default)]
+    pub agent: String,
+    /// The post or comment replied to.
+    pub parent: Uuid,
+    /// The post the thread belongs to; `parent` itself for top-level comments.
+    pub thread: Uuid,
+    /// Channel of the post.
+    pub module_slug: Option<String>,
+    pub content: String,
+    /// Required when commenting on remote Synapses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_signature: Option<String>,
+}
+
+/// Metadata of a comment replying to `parent` in the thread of post `thread`.
+/// Clients signing a comment must sign this metadata.
+pub fn comment_metadata(parent: Uuid, thread: Uuid) -> HashMap<String, String> {
+    HashMap::from([
+        (PARENT_METADATA_KEY.to_string(), parent.to_string()),
+        (THREAD_METADATA_KEY.to_string(), thread.to_string()),
+    ])
+}
+
+/// Query of `posts:count_comments` events, carried in their data.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CountCommentsRequest {
+    /// Posts whose whole thread is counted.
+    #[serde(default)]
+    pub threads: Vec<Uuid>,
+    /// Posts or comments whose direct replies are counted.
+    #[serde(default)]
+    pub parents: Vec<Uuid>,
+}
+
+/// Comment counts keyed by post or comment id; ids without comments are left
+/// out.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct CommentCounts {
+    pub threads: HashMap<String, u64>,
+    pub parents: HashMap<String, u64>,
+}
+
+/// A comment and the replies to it, oldest first. `comment.comments` counts
+/// every direct reply, including those beyond the requested depth or page.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct CommentNode {
+    pub comment: Post,
+    pub replies: Vec<CommentNode>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ListCommentsResult {
+    pub comments: Vec<CommentNode>,
+}
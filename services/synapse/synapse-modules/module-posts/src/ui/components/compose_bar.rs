@@ -24,7 +24,8 @@ pub fn ComposeBar(
     #[prop(into, optional)]
     synapse_public_key: Option<String>,
 ) -> impl IntoView {
-    use module_auth::signing::sign_event_payload;
+    use module_auth::signing::{sign_event, with_key_history};
+    use synapse_core::domain::events::Event;
     use synapse_core::domain::profiles::Profile;
 
     let session_user_profile =
@@ -66,26 +67,31 @@ pub fn ComposeBar(
             let textarea_ref = textarea_ref.clone();
             let synapse_pk = synapse_public_key.clone();
 
-            // Sign the event payload with the user's private key
+            // Sign the event with the user's private key
             // This is required for remote posts and optional for local posts
-            let agent_signature = sign_event_payload(
-                "posts:create_post",
-                &agent,
-                Some(&post_content),
-                Some("posts"),
-                Some(&channel_slug),
+            let event = with_key_history(
+                Event::new()
+                    .with_event_type("posts:create_post")
+                    .with_module_kind("posts")
+                    .with_module_slug(channel_slug)
+                    .with_agent(agent)
+                    .with_content(post_content)
+                    .build(),
             );
+            let agent_signature = sign_event(&event);
 
             let request = CreatePostRequest {
-                event_type: "posts:create_post".to_string(),
-                agent,
-                module_kind: Some("posts".to_string()),
-                module_slug: Some(channel_slug),
+                id: Some(event.id),
+                created_at: Some(event.created_at),
+                event_type: event.event_type,
+                agent: event.agent,
+                module_kind: event.module_kind,
+                module_slug: event.module_slug,
                 target: None,
                 previous: None,
-                content: Some(post_content),
+                content: event.content,
                 artifacts: None,
-                metadata: None,
+                metadata: event.metadata,
                 links: None,
                 data: None,
                 expiration: None,
@@ -28,6 +28,7 @@ pub fn PostsFeed(
 
     let synapse_pk = synapse_public_key.clone();
     let synapse_pk_for_posts = synapse_public_key.clone();
+    let viewer = session_user_profile.public_key.clone();
 
     // Fetch posts module config from the server (local or remote)
     let config_resource = Resource::new(
@@ -71,22 +72,28 @@ pub fn PostsFeed(
             refresh.get(),
             synapse_pk_for_posts.clone(),
         ),
-        |(channel, _refresh_counter, synapse_pk): (Option<String>, usize, Option<String>)| async move {
-            // Wait for channel to be available (config loaded)
-            let channel = match channel {
-                Some(ch) if !ch.is_empty() => ch,
-                _ => return vec![],
-            };
-            match synapse_pk {
-                Some(pk) => list_remote_posts_for_channel_server(pk, channel)
+        move |(channel, _refresh_counter, synapse_pk): (Option<String>, usize, Option<String>)| {
+            let viewer = Some(viewer.clone());
+            async move {
+                // Wait for channel to be available (config loaded)
+                let channel = match channel {
+                    Some(ch) if !ch.is_empty() => ch,
+                    _ => return vec![],
+                };
+                match synapse_pk {
+                    Some(pk) => list_remote_posts_for_channel_server(pk, channel, None, None, viewer)
+                        .await
+                        .unwrap_or_default(),
+                    None => list_posts_for_channel_server(ListPostsForChannelRequest {
+                        event_type: "posts:create_post".to_string(),
+                        channel,
+                        before: None,
+                        limit: None,
+                        viewer,
+                    })
                     .await
                     .unwrap_or_default(),
-                None => list_posts_for_channel_server(ListPostsForChannelRequest {
-                    event_type: "posts:create_post".to_string(),
-                    channel,
-                })
-                .await
-                .unwrap_or_default(),
+                }
             }
         },
     );
@@ -46,6 +46,12 @@ impl From<synapse_core::errors::CoreError> for ModuleProfilesError {
             CoreError::RateLimited(_) => {
                 ModuleProfilesError::BadRequest("RateLimited error".to_string())
             }
+            CoreError::UnsupportedModule(_) => {
+                ModuleProfilesError::BadRequest("UnsupportedModule error".to_string())
+            }
+            CoreError::UnsupportedAction(_) => {
+                ModuleProfilesError::BadRequest("UnsupportedAction error".to_string())
+            }
             CoreError::Other(_) => ModuleProfilesError::Other("Other error".to_string()),
         }
     }
@@ -10,6 +10,7 @@ use crate::types::{
 use async_trait::async_trait;
 use automerge::{AutoCommit, ReadDoc, ScalarValue, Value, transaction::Transactable};
 use axum::{Json, extract::Path, http::StatusCode};
+use module_auth::session::AuthenticatedAgent;
 use std::sync::Arc;
 use synapse_application::events::{
     CreateEventCommand, CreateLocalEventUseCase, CreateRemoteEventCommand, CreateRemoteEventUseCase,
@@ -19,10 +20,12 @@ use synapse_core::{
     CoreError,
     domain::{
         events::{Event, ObjectRef},
+        modules::EventTypeSpec,
         profiles::Profile,
     },
     ports::{
         modules::Module,
+        persistence::UnitOfWork,
         profiles::profile_repository::{ProfilesDocStore, ProfilesRepository},
     },
 };
@@ -56,6 +59,14 @@ impl Module for ProfilesModule {
     fn version(&self) -> Result<String, CoreError> {
         Ok(self.version.clone())
     }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        vec![
+            EventTypeSpec::query("profiles:get_profile"),
+            // `data` carries the Automerge profile document
+            EventTypeSpec::command("profiles:set_profile")
+                .with_schema(serde_json::json!({ "type": "array", "items": { "type": "integer" } })),
+        ]
+    }
 
     async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
         match event.event_type.as_str() {
@@ -79,42 +90,56 @@ impl Module for ProfilesModule {
                 Ok(vec![reply])
             }
 
-            "profiles:set_profile" => {
-                let owner_pk = match &event.target {
-                    Some(ObjectRef::Agent(pk)) => pk.clone(),
-                    _ => return Err(CoreError::Validation("target agent required".into())),
-                };
-                if event.agent != owner_pk {
-                    return Err(CoreError::Authorization("owner mismatch".into()));
-                }
-                let mut doc = if let Some(bytes) = &event.data {
-                    ProfileDoc::from_bytes(bytes)
-                } else {
-                    ProfileDoc::new()
-                };
-                let new_bytes = {
-                    let mut d = doc;
-                    d.to_bytes()
-                };
-                self.doc_store
-                    .upsert_doc(&owner_pk, &new_bytes)
-                    .await
-                    .map_err(CoreError::from)?;
-                let reply = Event::new()
-                    .with_event_type("profiles:profile")
-                    .with_module_kind("profiles")
-                    .with_agent(owner_pk.clone())
-                    .with_target(ObjectRef::Agent(owner_pk))
-                    .with_data(new_bytes)
-                    .build();
-                Ok(vec![reply])
-            }
+            "profiles:set_profile" => set_profile(self.doc_store.as_ref(), event).await,
 
-            _ => Ok(vec![]),
+            _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
+        }
+    }
+
+    async fn handle_event_in(
+        &self,
+        event: &Event,
+        uow: &mut dyn UnitOfWork,
+    ) -> Result<Vec<Event>, CoreError> {
+        match event.event_type.as_str() {
+            "profiles:set_profile" => set_profile(uow.profile_docs(), event).await,
+            _ => self.handle_event(event).await,
         }
     }
 }
 
+/// Stores the profile document carried by a `profiles:set_profile` event.
+async fn set_profile(
+    doc_store: &dyn ProfilesDocStore,
+    event: &Event,
+) -> Result<Vec<Event>, CoreError> {
+    let owner_pk = match &event.target {
+        Some(ObjectRef::Agent(pk)) => pk.clone(),
+        _ => return Err(CoreError::Validation("target agent required".into())),
+    };
+    if event.agent != owner_pk {
+        return Err(CoreError::Authorization("owner mismatch".into()));
+    }
+    let mut doc = if let Some(bytes) = &event.data {
+        ProfileDoc::from_bytes(bytes)
+    } else {
+        ProfileDoc::new()
+    };
+    let new_bytes = doc.to_bytes();
+    doc_store
+        .upsert_doc(&owner_pk, &new_bytes)
+        .await
+        .map_err(CoreError::from)?;
+    let reply = Event::new()
+        .with_event_type("profiles:profile")
+        .with_module_kind("profiles")
+        .with_agent(owner_pk.clone())
+        .with_target(ObjectRef::Agent(owner_pk))
+        .with_data(new_bytes)
+        .build();
+    Ok(vec![reply])
+}
+
 pub fn routes<S>() -> axum::Router<S>
 where
     S: Clone + Send + Sync + 'static,
@@ -236,12 +261,18 @@ impl ProfileDoc {
             inner: AutoCommit::load(bytes).unwrap(),
         }
     }
+    /// Like `from_bytes`, but `None` if `bytes` is not an Automerge doc.
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        AutoCommit::load(bytes).ok().map(|inner| Self { inner })
+    }
 }
 
 pub async fn register(
     axum::extract::State(deps): axum::extract::State<ProfilesDeps>,
+    agent: AuthenticatedAgent,
     Json(body): Json<ProfileRegisterRequest>,
 ) -> Result<(StatusCode, Json<Profile>), ModuleProfilesError> {
+    agent.require(&body.public_key)?;
     let mut doc = ProfileDoc::new();
     doc.set_display_name(&body.display_name);
     doc.set_handle(&body.handle);
@@ -253,6 +284,8 @@ pub async fn register(
         .await
         .unwrap();
     let cmd = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "profiles:set_profile".into(),
         module_kind: Some("profiles".into()),
         agent: body.public_key.clone(),
@@ -292,6 +325,8 @@ async fn get_profile_remote_http(
 ) -> Result<(StatusCode, Json<Option<Vec<u8>>>), ModuleProfilesError> {
     let trimmed_pk = public_key.trim();
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "profiles:get_profile".into(),
         module_kind: Some("profiles".into()),
         agent: "local-agent".into(),
@@ -318,8 +353,10 @@ async fn get_profile_remote_http(
 async fn set_profile_http(
     axum::extract::State(deps): axum::extract::State<ProfilesDeps>,
     axum::extract::Path(public_key): axum::extract::Path<String>,
+    agent: AuthenticatedAgent,
     axum::Json(body): axum::Json<SetProfileRequest>,
 ) -> Result<(StatusCode, axum::Json<Event>), ModuleProfilesError> {
+    agent.require(&public_key)?;
     let existing_doc = deps.doc_store.get_doc(&public_key).await.unwrap().unwrap();
 
     let mut doc = ProfileDoc::from_bytes(&existing_doc);
@@ -348,9 +385,11 @@ async fn set_profile_http(
         .unwrap();
 
     let cmd = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "profiles:set_profile".into(),
         module_kind: Some("profiles".into()),
-        agent: public_key.clone(), // TODO: set real authenticated agent
+        agent: agent.agent,
         target: Some(ObjectRef::Agent(public_key.clone())),
         data: Some(bytes.clone()),
         ..Default::default()
@@ -6,57 +6,24 @@ use synapse_core::domain::profiles::Profile;
 
 #[cfg(feature = "ssr")]
 use crate::types::ProfilesDeps;
-#[cfg(feature = "ssr")]
-use module_auth::types::AuthDeps;
 
 #[server(GetSessionUserProfile, "/api/profiles")]
 pub async fn get_session_user_profile() -> Result<Option<Profile>, ServerFnError> {
     use crate::service::get_profile;
 
-    use axum::http::header;
     use leptos_axum::extract;
+    use module_auth::session::AuthenticatedAgent;
     use tracing::debug;
 
-    let headers: axum::http::HeaderMap = extract().await?;
-
-    let cookie_header = headers.get(header::COOKIE);
-    debug!("Cookie header: {:?}", cookie_header);
-
-    let session_id = cookie_header
-        .and_then(|value| value.to_str().ok())
-        .and_then(|cookies| {
-            debug!("Cookies string: {}", cookies);
-            cookies.split(';').find_map(|cookie| {
-                let trimmed = cookie.trim();
-                debug!("Checking cookie: '{}'", trimmed);
-                trimmed.strip_prefix("menexus_session=")
-            })
-        });
-
-    debug!("Extracted session_id: {:?}", session_id);
-
-    let Some(session_id) = session_id else {
-        debug!("No session_id found, returning None");
+    let agent: Option<AuthenticatedAgent> = extract().await?;
+    let Some(agent) = agent else {
+        debug!("No active session, returning None");
         return Ok(None);
     };
 
-    let auth_deps: AuthDeps = expect_context();
+    debug!("Found session for agent: {}", agent.agent);
     let profile_deps: ProfilesDeps = expect_context();
-
-    debug!("Looking up session with id: {}", session_id);
-    let session = auth_deps
-        .session_repo
-        .get_session(session_id.parse()?)
-        .await
-        .map_err(|e| ServerFnError::new(format!("Session lookup failed: {}", e)))?;
-
-    debug!("Found session for agent: {}", session.agent);
-    // let profile = profile_deps
-    //     .profile_repo
-    //     .get_profile(&session.agent)
-    //     .await
-    //     .map_err(|e| ServerFnError::new(format!("Profile lookup failed: {}", e)))?;
-    let profile = get_profile(profile_deps, session.agent).await.unwrap();
+    let profile = get_profile(profile_deps, agent.agent).await.unwrap();
 
     debug!("Profile result: {:?}", profile);
     Ok(profile)
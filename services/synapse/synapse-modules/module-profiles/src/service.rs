@@ -2,6 +2,7 @@
 // Copyright © 2025 Malifex LLC and contributors
 
 use crate::{errors::ModuleProfilesError, types::ProfilesDeps};
+use synapse_application::auth::keys::agent_of;
 use synapse_application::events::{CreateEventCommand, CreateRemoteEventCommand};
 use synapse_core::domain::events::ObjectRef;
 use synapse_core::domain::profiles::Profile;
@@ -10,6 +11,10 @@ pub async fn get_profile(
     deps: ProfilesDeps,
     agent_public_key: String,
 ) -> Result<Option<Profile>, ModuleProfilesError> {
+    // Profiles stay under the agent's first key; a key it rotated to finds it
+    let agent_public_key = agent_of(deps.keys.as_ref(), agent_public_key.trim())
+        .await?
+        .unwrap_or(agent_public_key);
     let profile = deps
         .profile_repo
         .get_profile(&agent_public_key)
@@ -21,6 +26,8 @@ pub async fn get_profile(
 
     let trimmed = agent_public_key.trim().to_string();
     let inner = CreateEventCommand {
+        id: None,
+        created_at: None,
         event_type: "profiles:get_profile".into(),
         module_kind: Some("profiles".into()),
         agent: "local-agent".into(), // TODO get synapse public key
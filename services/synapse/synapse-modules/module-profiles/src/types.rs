@@ -8,6 +8,8 @@ use std::sync::Arc;
 use synapse_application::events::{
     CreateEventCommand, CreateLocalEventUseCase, CreateRemoteEventCommand, CreateRemoteEventUseCase,
 };
+#[cfg(feature = "ssr")]
+use synapse_core::ports::keys::key_repository::KeyRepository;
 use synapse_core::ports::profiles::profile_repository::ProfileDiscovery;
 use synapse_core::{
     domain::profiles::Profile,
@@ -20,6 +22,7 @@ pub struct ProfilesDeps {
     pub doc_store: Arc<dyn ProfilesDocStore>,
     pub profile_repo: Arc<dyn ProfilesRepository>,
     pub profile_discovery: Arc<dyn ProfileDiscovery>,
+    pub keys: Arc<dyn KeyRepository>,
     pub create_local_event: Arc<dyn CreateLocalEventUseCase + Send + Sync>,
     pub create_remote_event: Arc<dyn CreateRemoteEventUseCase + Send + Sync>,
 }
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use synapse_core::CoreError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ModuleReactionsError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+    #[error("Internal server error: {0}")]
+    Internal(String),
+}
+
+impl From<CoreError> for ModuleReactionsError {
+    fn from(err: CoreError) -> Self {
+        match err {
+            CoreError::Validation(msg) | CoreError::Conflict(msg) => {
+                ModuleReactionsError::BadRequest(msg)
+            }
+            CoreError::Authentication(msg) | CoreError::Authorization(msg) => {
+                ModuleReactionsError::Forbidden(msg)
+            }
+            CoreError::NotFound(msg) => ModuleReactionsError::NotFound(msg),
+            CoreError::Transport(_) | CoreError::Timeout(_) | CoreError::Unavailable(_) => {
+                ModuleReactionsError::BadGateway(err.to_string())
+            }
+            CoreError::UnsupportedModule(_) | CoreError::UnsupportedAction(_) => {
+                ModuleReactionsError::BadGateway(err.to_string())
+            }
+            err => ModuleReactionsError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ModuleReactionsError {
+    fn into_response(self) -> axum::response::Response {
+        let (status, message) = match self {
+            ModuleReactionsError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ModuleReactionsError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+            ModuleReactionsError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            ModuleReactionsError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ModuleReactionsError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use module_auth::session::AuthenticatedAgent;
+use synapse_config::get_synapse_config;
+use synapse_core::{
+    CoreError,
+    domain::events::Event,
+    domain::events::reactions::{ADD_REACTION, REMOVE_REACTION},
+    domain::modules::EventTypeSpec,
+    ports::events::event_repository::EventRepository,
+    ports::modules::Module,
+    ports::reactions::reaction_repository::ReactionsRepository,
+};
+
+use crate::errors::ModuleReactionsError;
+use crate::service::{
+    add_reaction, add_remote_reaction, remove_reaction, remove_remote_reaction, summarize,
+    summarize_reactions, summarize_remote_reactions, validate_reaction,
+};
+use crate::types::{ReactRequest, ReactionSummaries, ReactionsDeps, SummarizeReactionsRequest};
+
+pub struct ReactionsModule {
+    kind: String,
+    version: String,
+    events: Arc<dyn EventRepository>,
+    reactions: Arc<dyn ReactionsRepository>,
+}
+
+impl ReactionsModule {
+    pub fn new(events: Arc<dyn EventRepository>, reactions: Arc<dyn ReactionsRepository>) -> Self {
+        Self {
+            kind: "reactions".to_string(),
+            version: "1.0.0".to_string(),
+            events,
+            reactions,
+        }
+    }
+}
+
+#[async_trait]
+impl Module for ReactionsModule {
+    fn kind(&self) -> Result<String, CoreError> {
+        Ok(self.kind.clone())
+    }
+    fn version(&self) -> Result<String, CoreError> {
+        Ok(self.version.clone())
+    }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        vec![
+            // `content` carries the emoji
+            EventTypeSpec::command(ADD_REACTION),
+            EventTypeSpec::command(REMOVE_REACTION),
+            EventTypeSpec::query("reactions:summarize").with_schema(serde_json::json!({
+                "type": "object",
+                "required": ["targets"],
+                "properties": {
+                    "targets": { "type": "array", "items": { "type": "string", "format": "uuid" } },
+                    "viewer": { "type": ["string", "null"] }
+                }
+            })),
+        ]
+    }
+    async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
+        match event.event_type.as_str() {
+            // Recording the event updates the reactions held on its target
+            ADD_REACTION | REMOVE_REACTION => {
+                validate_reaction(self.events.as_ref(), event).await?;
+                Ok(vec![event.clone()])
+            }
+            "reactions:summarize" => {
+                let data = event.data.as_deref().ok_or_else(|| {
+                    CoreError::Validation("reactions:summarize requires data".to_string())
+                })?;
+                let request: SummarizeReactionsRequest = serde_json::from_slice(data)
+                    .map_err(|e| CoreError::Validation(format!("invalid summary request: {e}")))?;
+                let summaries = summarize(self.reactions.as_ref(), &request).await?;
+                let summaries_bytes =
+                    serde_json::to_vec(&summaries).map_err(|e| CoreError::Other(e.to_string()))?;
+                let synapse_config = get_synapse_config()
+                    .map_err(|e| CoreError::Other(format!("Failed to get synapse config: {}", e)))?;
+                let res_event = Event::new()
+                    .with_event_type("reactions:summary")
+                    .with_module_kind("reactions")
+                    .with_agent(synapse_config.identity.public_key.clone())
+                    .with_data(summaries_bytes)
+                    .build();
+                Ok(vec![res_event])
+            }
+            _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
+        }
+    }
+}
+
+pub fn routes<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    ReactionsDeps: axum::extract::FromRef<S>,
+{
+    use axum::routing::{get, post};
+    axum::Router::new()
+        .route("/reactions", post(add_reaction_http))
+        .route("/reactions/remove", post(remove_reaction_http))
+        .route("/reactions/summaries", get(summarize_reactions_http))
+        .route(
+            "/synapses/{synapse_public_key}/reactions",
+            post(add_reaction_remote_http),
+        )
+        .route(
+            "/synapses/{synapse_public_key}/reactions/remove",
+            post(remove_reaction_remote_http),
+        )
+        .route(
+            "/synapses/{synapse_public_key}/reactions/summaries",
+            get(summarize_reactions_remote_http),
+        )
+}
+
+async fn add_reaction_http(
+    State(deps): State<ReactionsDeps>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<ReactRequest>,
+) -> Result<(StatusCode, Json<Event>), ModuleReactionsError> {
+    body.agent = agent.agent;
+    let event = add_reaction(deps, body).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+async fn remove_reaction_http(
+    State(deps): State<ReactionsDeps>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<ReactRequest>,
+) -> Result<(StatusCode, Json<Event>), ModuleReactionsError> {
+    body.agent = agent.agent;
+    let event = remove_reaction(deps, body).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+async fn summarize_reactions_http(
+    State(deps): State<ReactionsDeps>,
+    Json(body): Json<SummarizeReactionsRequest>,
+) -> Result<(StatusCode, Json<ReactionSummaries>), ModuleReactionsError> {
+    let summaries = summarize_reactions(deps, body).await?;
+    Ok((StatusCode::OK, Json(summaries)))
+}
+
+/// React to an event of a remote synapse
+async fn add_reaction_remote_http(
+    State(deps): State<ReactionsDeps>,
+    Path(synapse_public_key): Path<String>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<ReactRequest>,
+) -> Result<(StatusCode, Json<Event>), ModuleReactionsError> {
+    body.agent = agent.agent;
+    let event = add_remote_reaction(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Take back a reaction to an event of a remote synapse
+async fn remove_reaction_remote_http(
+    State(deps): State<ReactionsDeps>,
+    Path(synapse_public_key): Path<String>,
+    agent: AuthenticatedAgent,
+    Json(mut body): Json<ReactRequest>,
+) -> Result<(StatusCode, Json<Event>), ModuleReactionsError> {
+    body.agent = agent.agent;
+    let event = remove_remote_reaction(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::CREATED, Json(event)))
+}
+
+/// Reactions on events of a remote synapse
+async fn summarize_reactions_remote_http(
+    State(deps): State<ReactionsDeps>,
+    Path(synapse_public_key): Path<String>,
+    Json(body): Json<SummarizeReactionsRequest>,
+) -> Result<(StatusCode, Json<ReactionSummaries>), ModuleReactionsError> {
+    let summaries = summarize_remote_reactions(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::OK, Json(summaries)))
+}
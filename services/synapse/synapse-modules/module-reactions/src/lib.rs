@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! # Module Reactions
+//!
+//! Emoji reactions to any event, recorded as `reactions:add` and
+//! `reactions:remove` events and summarized per emoji.
+//!
+//! ## HTTP Routes
+//!
+//! - `POST /reactions` - React to a local event
+//! - `POST /reactions/remove` - Take a reaction back
+//! - `GET /reactions/summaries` - Reactions held on local events
+//! - The same under `/synapses/{synapse_public_key}/reactions` for events of
+//!   a remote Synapse, which stores the reactions
+
+#[cfg(feature = "ssr")]
+pub mod errors;
+
+#[cfg(feature = "ssr")]
+pub mod http;
+
+#[cfg(feature = "ssr")]
+pub mod service;
+
+#[cfg(any(feature = "ssr", feature = "hydrate"))]
+pub mod server_fns;
+
+pub mod types;
@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use leptos::prelude::*;
+use synapse_core::domain::events::Event;
+
+use crate::types::{ReactRequest, ReactionSummaries, SummarizeReactionsRequest};
+
+#[cfg(feature = "ssr")]
+use crate::types::ReactionsDeps;
+
+/// React to an event of this synapse
+#[server(AddReaction, "/api/reactions")]
+pub async fn add_reaction_server(request: ReactRequest) -> Result<Event, ServerFnError> {
+    use crate::service::add_reaction;
+    use module_auth::session::authenticated_agent;
+    let request = ReactRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: ReactionsDeps = expect_context();
+    add_reaction(deps, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Take back a reaction to an event of this synapse
+#[server(RemoveReaction, "/api/reactions")]
+pub async fn remove_reaction_server(request: ReactRequest) -> Result<Event, ServerFnError> {
+    use crate::service::remove_reaction;
+    use module_auth::session::authenticated_agent;
+    let request = ReactRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: ReactionsDeps = expect_context();
+    remove_reaction(deps, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+#[server(SummarizeReactions, "/api/reactions")]
+pub async fn summarize_reactions_server(
+    request: SummarizeReactionsRequest,
+) -> Result<ReactionSummaries, ServerFnError> {
+    use crate::service::summarize_reactions;
+    let deps: ReactionsDeps = expect_context();
+    summarize_reactions(deps, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+// =============================================================================
+// Remote Synapse Server Functions
+// =============================================================================
+
+/// React to an event of a remote synapse
+#[server(AddRemoteReaction, "/api/reactions")]
+pub async fn add_remote_reaction_server(
+    synapse_public_key: String,
+    request: ReactRequest,
+) -> Result<Event, ServerFnError> {
+    use crate::service::add_remote_reaction;
+    use module_auth::session::authenticated_agent;
+    let request = ReactRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: ReactionsDeps = expect_context();
+    add_remote_reaction(deps, synapse_public_key, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Take back a reaction to an event of a remote synapse
+#[server(RemoveRemoteReaction, "/api/reactions")]
+pub async fn remove_remote_reaction_server(
+    synapse_public_key: String,
+    request: ReactRequest,
+) -> Result<Event, ServerFnError> {
+    use crate::service::remove_remote_reaction;
+    use module_auth::session::authenticated_agent;
+    let request = ReactRequest {
+        agent: authenticated_agent().await?.agent,
+        ..request
+    };
+    let deps: ReactionsDeps = expect_context();
+    remove_remote_reaction(deps, synapse_public_key, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Reactions on events of a remote synapse
+#[server(SummarizeRemoteReactions, "/api/reactions")]
+pub async fn summarize_remote_reactions_server(
+    synapse_public_key: String,
+    request: SummarizeReactionsRequest,
+) -> Result<ReactionSummaries, ServerFnError> {
+    use crate::service::summarize_remote_reactions;
+    let deps: ReactionsDeps = expect_context();
+    summarize_remote_reactions(deps, synapse_public_key, request)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use synapse_application::events::{
+    CreateEventCommand, CreateRemoteEventCommand, CreateRemoteEventUseCase,
+};
+use synapse_core::CoreError;
+use synapse_core::domain::events::reactions::{
+    ADD_REACTION, REMOVE_REACTION, ReactionChange, validate_emoji,
+};
+use synapse_core::domain::events::{Event, ObjectRef};
+use synapse_core::ports::events::event_repository::EventRepository;
+use synapse_core::ports::reactions::reaction_repository::ReactionsRepository;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::errors::ModuleReactionsError;
+use crate::types::{
+    MAX_SUMMARY_TARGETS, ReactRequest, ReactionSummaries, ReactionsDeps,
+    SummarizeReactionsRequest,
+};
+
+/// Checks that `target` is a live event stored on this Synapse. Reactions are
+/// kept by the Synapse owning the event reacted to.
+pub async fn validate_target(events: &dyn EventRepository, target: Uuid) -> Result<(), CoreError> {
+    events
+        .find(target)
+        .await?
+        .filter(|e| !e.is_expired(OffsetDateTime::now_utc()))
+        .map(|_| ())
+        .ok_or_else(|| CoreError::NotFound(format!("event {target}")))
+}
+
+/// Checks a `reactions:add` or `reactions:remove` event.
+pub async fn validate_reaction(
+    events: &dyn EventRepository,
+    event: &Event,
+) -> Result<ReactionChange, CoreError> {
+    let change = ReactionChange::of(event)?
+        .ok_or_else(|| CoreError::UnsupportedAction(event.event_type.clone()))?;
+    validate_target(events, change.target).await?;
+    Ok(change)
+}
+
+/// Summarizes the reactions on the events of `request`.
+pub async fn summarize(
+    reactions: &dyn ReactionsRepository,
+    request: &SummarizeReactionsRequest,
+) -> Result<ReactionSummaries, CoreError> {
+    if request.targets.len() > MAX_SUMMARY_TARGETS {
+        return Err(CoreError::Validation(format!(
+            "at most {MAX_SUMMARY_TARGETS} events can be summarized at once"
+        )));
+    }
+    let summaries = reactions
+        .summaries(&request.targets, request.viewer.as_deref())
+        .await?;
+    Ok(ReactionSummaries { summaries })
+}
+
+fn reaction_command(event_type: &str, request: ReactRequest) -> CreateEventCommand {
+    CreateEventCommand {
+        id: request.id,
+        created_at: request.created_at,
+        event_type: event_type.to_string(),
+        module_kind: Some("reactions".to_string()),
+        module_slug: None,
+        agent: request.agent,
+        target: Some(ObjectRef::Event(request.target)),
+        previous: None,
+        content: Some(request.emoji),
+        artifacts: None,
+        metadata: None,
+        links: None,
+        data: None,
+        expiration: None,
+        agent_signature: request.agent_signature,
+    }
+}
+
+async fn react(
+    deps: ReactionsDeps,
+    event_type: &str,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    validate_emoji(&request.emoji)?;
+    validate_target(deps.events.as_ref(), request.target).await?;
+    let event = deps
+        .create_local_event
+        .execute(reaction_command(event_type, request))
+        .await?;
+    Ok(event)
+}
+
+/// React to an event of this synapse
+pub async fn add_reaction(
+    deps: ReactionsDeps,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    react(deps, ADD_REACTION, request).await
+}
+
+/// Take back a reaction to an event of this synapse
+pub async fn remove_reaction(
+    deps: ReactionsDeps,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    react(deps, REMOVE_REACTION, request).await
+}
+
+pub async fn summarize_reactions(
+    deps: ReactionsDeps,
+    request: SummarizeReactionsRequest,
+) -> Result<ReactionSummaries, ModuleReactionsError> {
+    Ok(summarize(deps.reactions.as_ref(), &request).await?)
+}
+
+// =============================================================================
+// Remote Synapse Service Functions
+// =============================================================================
+
+async fn react_remote(
+    deps: ReactionsDeps,
+    synapse_public_key: String,
+    event_type: &str,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    validate_emoji(&request.emoji)?;
+    let cmd = CreateRemoteEventCommand {
+        synapse_public_key,
+        event: reaction_command(event_type, request),
+    };
+    let events = deps.create_remote_event.execute(cmd).await?;
+
+    // The remote synapse echoes the reaction it stored
+    events
+        .into_iter()
+        .find(|e| e.event_type == event_type)
+        .ok_or_else(|| {
+            ModuleReactionsError::BadGateway(
+                "Remote synapse did not return the reaction".to_string(),
+            )
+        })
+}
+
+/// React to an event of a remote synapse, which stores the reaction
+pub async fn add_remote_reaction(
+    deps: ReactionsDeps,
+    synapse_public_key: String,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    react_remote(deps, synapse_public_key, ADD_REACTION, request).await
+}
+
+/// Take back a reaction to an event of a remote synapse
+pub async fn remove_remote_reaction(
+    deps: ReactionsDeps,
+    synapse_public_key: String,
+    request: ReactRequest,
+) -> Result<Event, ModuleReactionsError> {
+    react_remote(deps, synapse_public_key, REMOVE_REACTION, request).await
+}
+
+/// Reactions on events of a remote synapse. Synapses without the reactions
+/// module cannot answer and are treated as having none.
+pub async fn remote_reaction_summaries(
+    create_remote_event: &dyn CreateRemoteEventUseCase,
+    synapse_public_key: &str,
+    request: &SummarizeReactionsRequest,
+) -> ReactionSummaries {
+    let Ok(data) = serde_json::to_vec(request) else {
+        return ReactionSummaries::default();
+    };
+    let cmd = CreateRemoteEventCommand {
+        synapse_public_key: synapse_public_key.to_string(),
+        event: CreateEventCommand {
+            id: None,
+            created_at: None,
+            event_type: "reactions:summarize".to_string(),
+            module_kind: Some("reactions".to_string()),
+            module_slug: None,
+            agent: "guest".to_string(), // Read operations don't require specific identity
+            target: None,
+            previous: None,
+            content: None,
+            artifacts: None,
+            metadata: None,
+            links: None,
+            data: Some(data),
+            expiration: None,
+            agent_signature: None, // Read operations don't require signature
+        },
+    };
+    match create_remote_event.execute(cmd).await {
+        Ok(events) => events
+            .iter()
+            .find(|e| e.event_type == "reactions:summary")
+            .and_then(|e| e.data.as_deref())
+            .and_then(|data| serde_json::from_slice(data).ok())
+            .unwrap_or_default(),
+        Err(e) => {
+            tracing::debug!("no reaction summaries from {synapse_public_key}: {e}");
+            ReactionSummaries::default()
+        }
+    }
+}
+
+pub async fn summarize_remote_reactions(
+    deps: ReactionsDeps,
+    synapse_public_key: String,
+    request: SummarizeReactionsRequest,
+) -> Result<ReactionSummaries, ModuleReactionsError> {
+    Ok(remote_reaction_summaries(deps.create_remote_event.as_ref(), &synapse_public_key, &request).await)
+}
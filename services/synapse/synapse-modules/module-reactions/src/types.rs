@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use synapse_core::domain::events::reactions::ReactionSummary;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[cfg(feature = "ssr")]
+use std::sync::Arc;
+#[cfg(feature = "ssr")]
+use synapse_application::events::{CreateLocalEventUseCase, CreateRemoteEventUseCase};
+#[cfg(feature = "ssr")]
+use synapse_core::ports::{
+    events::event_repository::EventRepository,
+    reactions::reaction_repository::ReactionsRepository,
+};
+
+#[cfg(feature = "ssr")]
+#[derive(Clone)]
+pub struct ReactionsDeps {
+    pub events: Arc<dyn EventRepository>,
+    pub reactions: Arc<dyn ReactionsRepository>,
+    pub create_local_event: Arc<dyn CreateLocalEventUseCase + Send + Sync>,
+    pub create_remote_event: Arc<dyn CreateRemoteEventUseCase + Send + Sync>,
+}
+
+/// Most events summarized by one request.
+pub const MAX_SUMMARY_TARGETS: usize = 200;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReactRequest {
+    /// Event id chosen by the client, so a retried request is not applied twice.
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    /// Creation time covered by `agent_signature`; kept by the Synapse as is.
+    #[serde(default)]
+    pub created_at: Option<OffsetDateTime>,
+    /// Replaced by the agent of the session the request is made with.
+    #[serde(default)]
+    pub agent: String,
+    /// The event reacted to.
+    pub target: Uuid,
+    pub emoji: String,
+    /// Required when reacting to events of remote Synapses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_signature: Option<String>,
+}
+
+/// Query of `reactions:summarize` events, carried in their data.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SummarizeReactionsRequest {
+    pub targets: Vec<Uuid>,
+    /// Agent whose own reactions are flagged.
+    #[serde(default)]
+    pub viewer: Option<String>,
+}
+
+/// Reactions per event; events without reactions are left out.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ReactionSummaries {
+    pub summaries: HashMap<Uuid, Vec<ReactionSummary>>,
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use synapse_core::CoreError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ModuleSearchError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+    #[error("Internal server error: {0}")]
+    Internal(String),
+}
+
+impl From<CoreError> for ModuleSearchError {
+    fn from(err: CoreError) -> Self {
+        match err {
+            CoreError::Validation(msg) => ModuleSearchError::BadRequest(msg),
+            CoreError::Transport(_) | CoreError::Timeout(_) | CoreError::Unavailable(_) => {
+                ModuleSearchError::BadGateway(err.to_string())
+            }
+            CoreError::UnsupportedModule(_) | CoreError::UnsupportedAction(_) => {
+                ModuleSearchError::BadGateway(err.to_string())
+            }
+            err => ModuleSearchError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for ModuleSearchError {
+    fn into_response(self) -> axum::response::Response {
+        let (status, message) = match self {
+            ModuleSearchError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ModuleSearchError::BadGateway(msg) => (StatusCode::BAD_GATEWAY, msg),
+            ModuleSearchError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use synapse_config::get_synapse_config;
+use synapse_core::{
+    CoreError,
+    domain::events::Event,
+    domain::modules::EventTypeSpec,
+    domain::search::{SearchQuery, SearchResults},
+    ports::modules::Module,
+    ports::search::search_repository::SearchRepository,
+};
+
+use crate::errors::ModuleSearchError;
+use crate::service::{search, search_local, search_remote};
+use crate::types::SearchDeps;
+
+pub struct SearchModule {
+    kind: String,
+    version: String,
+    index: Arc<dyn SearchRepository>,
+}
+
+impl SearchModule {
+    pub fn new(index: Arc<dyn SearchRepository>) -> Self {
+        Self {
+            kind: "search".to_string(),
+            version: "1.0.0".to_string(),
+            index,
+        }
+    }
+}
+
+#[async_trait]
+impl Module for SearchModule {
+    fn kind(&self) -> Result<String, CoreError> {
+        Ok(self.kind.clone())
+    }
+    fn version(&self) -> Result<String, CoreError> {
+        Ok(self.version.clone())
+    }
+    fn event_types(&self) -> Vec<EventTypeSpec> {
+        vec![EventTypeSpec::query("search:query").with_schema(SearchQuery::schema())]
+    }
+    async fn handle_event(&self, event: &Event) -> Result<Vec<Event>, CoreError> {
+        match event.event_type.as_str() {
+            "search:query" => {
+                let query = SearchQuery::from_event(event)?;
+                let results = search(self.index.as_ref(), query).await?;
+                let results_bytes =
+                    serde_json::to_vec(&results).map_err(|e| CoreError::Other(e.to_string()))?;
+                let synapse_config = get_synapse_config()
+                    .map_err(|e| CoreError::Other(format!("Failed to get synapse config: {}", e)))?;
+                let res_event = Event::new()
+                    .with_event_type("search:results")
+                    .with_module_kind("search")
+                    .with_agent(synapse_config.identity.public_key.clone())
+                    .with_data(results_bytes)
+                    .build();
+                Ok(vec![res_event])
+            }
+            _ => Err(CoreError::UnsupportedAction(event.event_type.clone())),
+        }
+    }
+}
+
+pub fn routes<S>() -> axum::Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    SearchDeps: axum::extract::FromRef<S>,
+{
+    use axum::routing::get;
+    axum::Router::new()
+        .route("/search", get(search_http))
+        .route(
+            "/synapses/{synapse_public_key}/search",
+            get(search_remote_http),
+        )
+}
+
+async fn search_http(
+    State(deps): State<SearchDeps>,
+    Json(body): Json<SearchQuery>,
+) -> Result<(StatusCode, Json<SearchResults>), ModuleSearchError> {
+    let results = search_local(deps, body).await?;
+    Ok((StatusCode::OK, Json(results)))
+}
+
+/// Search a remote synapse
+async fn search_remote_http(
+    State(deps): State<SearchDeps>,
+    Path(synapse_public_key): Path<String>,
+    Json(body): Json<SearchQuery>,
+) -> Result<(StatusCode, Json<SearchResults>), ModuleSearchError> {
+    let results = search_remote(deps, synapse_public_key, body).await?;
+    Ok((StatusCode::OK, Json(results)))
+}
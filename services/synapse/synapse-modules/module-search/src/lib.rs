@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! # Module Search
+//!
+//! Full-text search over the events and profiles held by a Synapse, answered
+//! locally or through `search:query` events sent to remote Synapses.
+//!
+//! ## HTTP Routes
+//!
+//! - `GET /search` - Search this Synapse
+//! - `GET /synapses/{synapse_public_key}/search` - Search a remote Synapse
+
+#[cfg(feature = "ssr")]
+pub mod errors;
+
+#[cfg(feature = "ssr")]
+pub mod http;
+
+#[cfg(feature = "ssr")]
+pub mod service;
+
+#[cfg(any(feature = "ssr", feature = "hydrate"))]
+pub mod server_fns;
+
+pub mod types;
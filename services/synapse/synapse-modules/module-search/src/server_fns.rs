@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use leptos::prelude::*;
+
+use crate::types::{SearchQuery, SearchResults};
+
+#[cfg(feature = "ssr")]
+use crate::types::SearchDeps;
+
+/// Search this synapse
+#[server(Search, "/api/search")]
+pub async fn search_server(query: SearchQuery) -> Result<SearchResults, ServerFnError> {
+    use crate::service::search_local;
+    let deps: SearchDeps = expect_context();
+    search_local(deps, query)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
+
+/// Search a remote synapse
+#[server(SearchRemote, "/api/search")]
+pub async fn search_remote_server(
+    synapse_public_key: String,
+    query: SearchQuery,
+) -> Result<SearchResults, ServerFnError> {
+    use crate::service::search_remote;
+    let deps: SearchDeps = expect_context();
+    search_remote(deps, synapse_public_key, query)
+        .await
+        .map_err(|e| ServerFnError::new(e.to_string()))
+}
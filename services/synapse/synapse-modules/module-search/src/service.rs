@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use synapse_application::events::{CreateEventCommand, CreateRemoteEventCommand};
+use synapse_core::CoreError;
+use synapse_core::domain::search::{SearchQuery, SearchResults, SearchScope};
+use synapse_core::ports::search::search_repository::SearchRepository;
+
+use crate::errors::ModuleSearchError;
+use crate::types::SearchDeps;
+
+/// Runs one page of `query` against the index of this Synapse.
+pub async fn search(
+    index: &dyn SearchRepository,
+    query: SearchQuery,
+) -> Result<SearchResults, CoreError> {
+    let query = query.validated()?;
+    let events = match query.scope {
+        SearchScope::All | SearchScope::Events => index.search_events(&query).await?,
+        SearchScope::Profiles => Vec::new(),
+    };
+    let profiles = match query.scope {
+        SearchScope::All | SearchScope::Profiles => index.search_profiles(&query).await?,
+        SearchScope::Events => Vec::new(),
+    };
+    Ok(SearchResults::page(&query, events, profiles))
+}
+
+/// Search this synapse
+pub async fn search_local(
+    deps: SearchDeps,
+    query: SearchQuery,
+) -> Result<SearchResults, ModuleSearchError> {
+    Ok(search(deps.search.as_ref(), query).await?)
+}
+
+// =============================================================================
+// Remote Synapse Service Functions
+// =============================================================================
+
+/// Search a remote synapse
+pub async fn search_remote(
+    deps: SearchDeps,
+    synapse_public_key: String,
+    query: SearchQuery,
+) -> Result<SearchResults, ModuleSearchError> {
+    let query = query.validated()?;
+    let cmd = CreateRemoteEventCommand {
+        synapse_public_key,
+        event: CreateEventCommand {
+            id: None,
+            created_at: None,
+            event_type: "search:query".to_string(),
+            module_kind: Some("search".to_string()),
+            module_slug: None,
+            agent: "guest".to_string(), // Read operations don't require specific identity
+            target: None,
+            previous: None,
+            content: None,
+            artifacts: None,
+            metadata: None,
+            links: None,
+            data: Some(query.to_data()),
+            expiration: None,
+            agent_signature: None, // Read operations don't require signature
+        },
+    };
+    let events = deps.create_remote_event.execute(cmd).await?;
+
+    let data = events
+        .iter()
+        .find(|e| e.event_type == "search:results")
+        .and_then(|e| e.data.as_deref())
+        .ok_or_else(|| {
+            ModuleSearchError::BadGateway("Remote synapse did not return results".to_string())
+        })?;
+    serde_json::from_slice(data)
+        .map_err(|e| ModuleSearchError::BadGateway(format!("invalid search results: {e}")))
+}
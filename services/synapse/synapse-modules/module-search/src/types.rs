@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+pub use synapse_core::domain::search::{
+    EventHit, ProfileHit, SearchQuery, SearchResults, SearchScope, SearchSort, SearchTimeRange,
+};
+
+#[cfg(feature = "ssr")]
+use std::sync::Arc;
+#[cfg(feature = "ssr")]
+use synapse_application::events::CreateRemoteEventUseCase;
+#[cfg(feature = "ssr")]
+use synapse_core::ports::search::search_repository::SearchRepository;
+
+#[cfg(feature = "ssr")]
+#[derive(Clone)]
+pub struct SearchDeps {
+    pub search: Arc<dyn SearchRepository>,
+    pub create_remote_event: Arc<dyn CreateRemoteEventUseCase + Send + Sync>,
+}
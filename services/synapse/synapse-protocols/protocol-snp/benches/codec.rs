@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Compares the JSON and CBOR SNP encodings on reply batches resembling real
+//! traffic: posts with text and metadata, and profile documents carrying binary
+//! Automerge data.
+//!
+//! Run with `cargo bench -p protocol-snp`. Encoded sizes are printed before the
+//! timings.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use protocol_snp::{Destination, Encoding, SnpMessage, SnpPayload};
+use synapse_core::domain::events::Event;
+
+const BATCH_SIZES: [usize; 3] = [1, 50, 500];
+/// Size of the Automerge document carried by each profile event.
+const PROFILE_DOC_SIZE: usize = 2048;
+
+fn post(i: usize) -> Event {
+    let mut metadata = HashMap::new();
+    metadata.insert("channel".to_string(), "general".to_string());
+    metadata.insert("client".to_string(), "menexus-web".to_string());
+    Event::new()
+        .with_event_type("posts:create_post")
+        .with_module_kind("posts")
+        .with_module_slug("general")
+        .with_agent("Ag7k0dH5V2qZ1w9Xb3cR8yLmN4pT6uJ2sE5fK1aQ9zVx")
+        .with_content(format!("Post number {i}, with a sentence or two of text in it."))
+        .with_metadata(metadata)
+        .build()
+}
+
+fn profile(i: usize) -> Event {
+    Event::new()
+        .with_event_type("profiles:profile")
+        .with_module_kind("profiles")
+        .with_agent("Ag7k0dH5V2qZ1w9Xb3cR8yLmN4pT6uJ2sE5fK1aQ9zVx")
+        .with_data((0..PROFILE_DOC_SIZE).map(|b| (b * 31 + i) as u8).collect())
+        .build()
+}
+
+fn reply(events: Vec<Event>) -> SnpMessage {
+    SnpMessage::new(
+        Destination::Local,
+        SnpPayload::Reply {
+            ok: true,
+            events,
+            error: None,
+            code: None,
+        },
+    )
+}
+
+fn batches() -> Vec<(String, SnpMessage)> {
+    let mut batches = Vec::new();
+    for size in BATCH_SIZES {
+        batches.push((format!("posts/{size}"), reply((0..size).map(post).collect())));
+        batches.push((format!("profiles/{size}"), reply((0..size).map(profile).collect())));
+    }
+    batches
+}
+
+fn bench_codec(c: &mut Criterion) {
+    let batches = batches();
+    for (name, message) in &batches {
+        let json = Encoding::Json.encode(message).unwrap().len();
+        let cbor = Encoding::Cbor.encode(message).unwrap().len();
+        println!(
+            "{name}: json {json} bytes, cbor {cbor} bytes ({:.0}%)",
+            cbor as f64 * 100.0 / json as f64
+        );
+    }
+
+    let mut group = c.benchmark_group("snp_codec");
+    for (name, message) in &batches {
+        for encoding in Encoding::PREFERRED {
+            let encoded = encoding.encode(message).unwrap();
+            group.throughput(Throughput::Bytes(encoded.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("encode/{encoding:?}"), name),
+                message,
+                |b, message| b.iter(|| encoding.encode(black_box(message)).unwrap()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("decode/{encoding:?}"), name),
+                &encoded,
+                |b, encoded| b.iter(|| encoding.decode(black_box(encoded)).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_codec);
+criterion_main!(benches);
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+//! Wire encodings of SNP messages.
+//!
+//! Every SNP version is offered as a JSON stream protocol and, under its own
+//! protocol id, as a CBOR one. CBOR carries `Event.data` (Automerge documents,
+//! module configs) as byte strings instead of JSON arrays of integers. Peers
+//! agree on the encoding when they negotiate the stream protocol, so Synapses
+//! without CBOR support keep talking JSON.
+//!
+//! The encoding only affects the bytes on the wire: envelopes are always signed
+//! over `SnpMessage::signing_payload`.
+
+use std::io;
+
+use crate::SnpMessage;
+
+/// Suffix of the stream protocols carrying CBOR encoded messages.
+pub const CBOR_PROTOCOL_SUFFIX: &str = "/cbor";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    Cbor,
+}
+
+impl Encoding {
+    /// Encodings offered to peers, preferred first.
+    pub const PREFERRED: [Encoding; 2] = [Encoding::Cbor, Encoding::Json];
+
+    /// The encoding used on a stream opened with `protocol`.
+    pub fn of_protocol(protocol: &str) -> Self {
+        if protocol.ends_with(CBOR_PROTOCOL_SUFFIX) {
+            Encoding::Cbor
+        } else {
+            Encoding::Json
+        }
+    }
+
+    pub fn encode(self, message: &SnpMessage) -> io::Result<Vec<u8>> {
+        match self {
+            Encoding::Json => serde_json::to_vec(message).map_err(io::Error::other),
+            Encoding::Cbor => cbor4ii::serde::to_vec(Vec::new(), message)
+                .map_err(|e| io::Error::other(e.to_string())),
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> io::Result<SnpMessage> {
+        match self {
+            Encoding::Json => {
+                serde_json::from_slice(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Encoding::Cbor => cbor4ii::serde::from_slice(bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Destination, SNP_VERSION, SnpPayload, snp_protocol};
+    use synapse_core::domain::events::Event;
+
+    fn reply_with_data() -> SnpMessage {
+        let event = Event::new()
+            .with_event_type("profiles:profile")
+            .with_module_kind("profiles")
+            .with_agent("02abcdef")
+            .with_data((0..=255).cycle().take(4096).collect())
+            .build();
+        SnpMessage::new(
+            Destination::Local,
+            SnpPayload::Reply {
+                ok: true,
+                events: vec![event],
+                error: None,
+                code: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_cbor_round_trip_keeps_signing_payload() {
+        let message = reply_with_data();
+
+        let bytes = Encoding::Cbor.encode(&message).unwrap();
+        let decoded = Encoding::Cbor.decode(&bytes).unwrap();
+
+        assert_eq!(decoded.signing_payload(), message.signing_payload());
+        assert!(bytes.len() < Encoding::Json.encode(&message).unwrap().len() / 2);
+    }
+
+    #[test]
+    fn test_encoding_of_protocol() {
+        assert_eq!(
+            Encoding::of_protocol(&snp_protocol(SNP_VERSION, Encoding::Cbor)),
+            Encoding::Cbor
+        );
+        assert_eq!(
+            Encoding::of_protocol(&snp_protocol(SNP_VERSION, Encoding::Json)),
+            Encoding::Json
+        );
+    }
+}
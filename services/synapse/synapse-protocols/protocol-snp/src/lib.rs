@@ -1,11 +1,51 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 // Copyright © 2025 Malifex LLC and contributors
 
+pub mod codec;
+
+pub use codec::Encoding;
+
 use serde::{Deserialize, Serialize};
+use synapse_core::CoreError;
 use synapse_core::domain::events::Event;
+use synapse_core::domain::modules::ModuleCapability;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+/// Newest SNP wire version this Synapse speaks.
+pub const SNP_VERSION: &str = "1.1.0";
+
+/// Version every Synapse speaks, used until a peer's capabilities are known.
+pub const SNP_BASELINE_VERSION: &str = "1.0.0";
+
+/// SNP versions this Synapse accepts, newest first.
+///
+/// - `1.0.0`: initial protocol.
+/// - `1.1.0`: capability exchange and structured error codes in replies.
+pub const SUPPORTED_SNP_VERSIONS: &[&str] = &[SNP_VERSION, SNP_BASELINE_VERSION];
+
+/// Whether this Synapse accepts messages stamped with `version`.
+pub fn is_supported_version(version: &str) -> bool {
+    SUPPORTED_SNP_VERSIONS.contains(&version)
+}
+
+/// Picks the newest version supported by both this Synapse and a peer
+/// advertising `theirs`.
+pub fn negotiate_version<S: AsRef<str>>(theirs: &[S]) -> Option<&'static str> {
+    SUPPORTED_SNP_VERSIONS
+        .iter()
+        .copied()
+        .find(|ours| theirs.iter().any(|v| v.as_ref() == *ours))
+}
+
+/// Stream protocol carrying SNP requests at `version` in `encoding`.
+pub fn snp_protocol(version: &str, encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Json => format!("/menexus/snp/{version}"),
+        Encoding::Cbor => format!("/menexus/snp/{version}{}", codec::CBOR_PROTOCOL_SUFFIX),
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SnpMessage {
     pub version: String,
@@ -18,6 +58,96 @@ pub struct SnpMessage {
     pub signature: String,
 }
 
+impl SnpMessage {
+    /// Builds an unsigned message. `agent_public_key` and `signature` are filled in
+    /// by the sending Synapse when it signs the envelope.
+    pub fn new(destination: Destination, payload: SnpPayload) -> Self {
+        Self {
+            version: SNP_VERSION.to_string(),
+            id: Uuid::new_v4(),
+            correlation_id: Uuid::new_v4(),
+            destination,
+            agent_public_key: String::new(),
+            timestamp: OffsetDateTime::now_utc(),
+            payload,
+            signature: String::new(),
+        }
+    }
+
+    /// Builds an unsigned reply correlated with `request`, in the version the
+    /// request was made in when we support it.
+    ///
+    /// `1.0.0` replies never carry an error code: older Synapses would drop the
+    /// unknown field when decoding and then fail to verify the signature.
+    pub fn reply_to(request: &SnpMessage, destination: Destination, mut payload: SnpPayload) -> Self {
+        let version = if is_supported_version(&request.version) {
+            request.version.clone()
+        } else {
+            SNP_VERSION.to_string()
+        };
+        if version == SNP_BASELINE_VERSION
+            && let SnpPayload::Reply { code, .. } = &mut payload
+        {
+            *code = None;
+        }
+        Self {
+            version,
+            correlation_id: request.id,
+            ..Self::new(destination, payload)
+        }
+    }
+
+    /// Builds a failed reply to `request`.
+    pub fn error_reply(
+        request: &SnpMessage,
+        destination: Destination,
+        code: SnpErrorCode,
+        error: impl Into<String>,
+    ) -> Self {
+        Self::reply_to(
+            request,
+            destination,
+            SnpPayload::Reply {
+                ok: false,
+                events: vec![],
+                error: Some(error.into()),
+                code: Some(code),
+            },
+        )
+    }
+
+    /// Returns the canonical bytes covered by the envelope signature.
+    ///
+    /// Covers version, id, correlation_id, destination, timestamp and payload. The
+    /// fields are converted to a `serde_json::Value` first, whose object keys are
+    /// sorted, so the encoding does not depend on `HashMap` iteration order inside
+    /// event metadata.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let payload = SigningPayload {
+            version: &self.version,
+            id: self.id,
+            correlation_id: self.correlation_id,
+            destination: &self.destination,
+            timestamp: self.timestamp,
+            payload: &self.payload,
+        };
+        serde_json::to_value(&payload)
+            .and_then(|value| serde_json::to_vec(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Internal struct for creating the deterministic envelope signing payload
+#[derive(Serialize)]
+struct SigningPayload<'a> {
+    version: &'a str,
+    id: Uuid,
+    correlation_id: Uuid,
+    destination: &'a Destination,
+    timestamp: OffsetDateTime,
+    payload: &'a SnpPayload,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum SnpPayload {
     Command {
@@ -28,12 +158,166 @@ pub enum SnpPayload {
         ok: bool,
         events: Vec<Event>,
         error: Option<String>,
+        /// Machine-readable reason for a failed reply, since `1.1.0`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<SnpErrorCode>,
     },
 }
 
+/// Why a request failed, so peers can react without parsing `error`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SnpErrorCode {
+    /// The request was stamped with an SNP version we do not speak.
+    UnsupportedVersion,
+    /// The module named by the event is not installed on this Synapse.
+    UnknownModule,
+    /// The module is installed but does not handle the event type.
+    UnsupportedAction,
+    /// The envelope or agent signature was missing or invalid.
+    Unauthorized,
+    InvalidRequest,
+    Internal,
+}
+
+impl From<&CoreError> for SnpErrorCode {
+    fn from(err: &CoreError) -> Self {
+        match err {
+            CoreError::UnsupportedModule(_) => SnpErrorCode::UnknownModule,
+            CoreError::UnsupportedAction(_) => SnpErrorCode::UnsupportedAction,
+            CoreError::Authentication(_) | CoreError::Authorization(_) => {
+                SnpErrorCode::Unauthorized
+            }
+            CoreError::Validation(_) | CoreError::NotFound(_) | CoreError::Conflict(_) => {
+                SnpErrorCode::InvalidRequest
+            }
+            _ => SnpErrorCode::Internal,
+        }
+    }
+}
+
+/// Event type asking a Synapse for its [`SnpCapabilities`].
+pub const GET_CAPABILITIES: &str = "synapse:get_capabilities";
+/// Event type of the reply to [`GET_CAPABILITIES`]; its content is the JSON
+/// encoded [`SnpCapabilities`].
+pub const CAPABILITIES: &str = "synapse:capabilities";
+
+/// What a Synapse supports, exchanged when two Synapses connect. Synapses that
+/// predate the exchange answer without a capabilities event and are assumed to
+/// speak [`SNP_BASELINE_VERSION`] only.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SnpCapabilities {
+    /// Supported SNP versions, newest first.
+    pub versions: Vec<String>,
+    pub modules: Vec<ModuleCapability>,
+}
+
+impl SnpCapabilities {
+    /// Capabilities of this Synapse with `modules` installed.
+    pub fn local(modules: Vec<ModuleCapability>) -> Self {
+        Self {
+            versions: SUPPORTED_SNP_VERSIONS.iter().map(|v| v.to_string()).collect(),
+            modules,
+        }
+    }
+
+    /// Capabilities assumed for a Synapse that did not advertise any.
+    pub fn baseline() -> Self {
+        Self {
+            versions: vec![SNP_BASELINE_VERSION.to_string()],
+            modules: Vec::new(),
+        }
+    }
+
+    /// Whether the Synapse advertised `module_kind`. Synapses that advertised no
+    /// modules are given the benefit of the doubt.
+    pub fn supports_module(&self, module_kind: &str) -> bool {
+        self.modules.is_empty() || self.modules.iter().any(|m| m.kind == module_kind)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum Destination {
     Local,
     Synapse { id: String },
     Multicast { topic: String },
 }
+
+/// Prefix of the pub/sub topics a Synapse publishes its recorded events on.
+pub const EVENT_TOPIC_PREFIX: &str = "/menexus/events/1.0.0";
+
+/// Topic carrying events that `synapse_public_key` records for a module, or for
+/// a single slug of that module.
+pub fn event_topic(synapse_public_key: &str, module_kind: &str, module_slug: Option<&str>) -> String {
+    match module_slug {
+        Some(slug) => format!("{EVENT_TOPIC_PREFIX}/{synapse_public_key}/{module_kind}/{slug}"),
+        None => format!("{EVENT_TOPIC_PREFIX}/{synapse_public_key}/{module_kind}"),
+    }
+}
+
+/// Returns the public key of the Synapse allowed to publish on an event topic.
+pub fn event_topic_publisher(topic: &str) -> Option<&str> {
+    topic
+        .strip_prefix(EVENT_TOPIC_PREFIX)?
+        .strip_prefix('/')?
+        .split('/')
+        .next()
+        .filter(|pk| !pk.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_topic_publisher() {
+        let topic = event_topic("CAISIQ", "posts", Some("general"));
+
+        assert_eq!(topic, "/menexus/events/1.0.0/CAISIQ/posts/general");
+        assert_eq!(event_topic_publisher(&topic), Some("CAISIQ"));
+        assert_eq!(event_topic_publisher("/other/CAISIQ/posts"), None);
+    }
+
+    #[test]
+    fn test_negotiate_version() {
+        assert_eq!(negotiate_version(&["1.0.0", "1.1.0"]), Some("1.1.0"));
+        assert_eq!(negotiate_version(&["2.0.0", "1.0.0"]), Some("1.0.0"));
+        assert_eq!(negotiate_version(&["2.0.0"]), None);
+    }
+
+    #[test]
+    fn test_baseline_reply_omits_error_code() {
+        let event = Event::new()
+            .with_event_type("chess:list_games")
+            .with_module_kind("chess")
+            .build();
+        let mut request = SnpMessage::new(
+            Destination::Local,
+            SnpPayload::Command {
+                action: event.event_type.clone(),
+                event,
+            },
+        );
+        request.version = SNP_BASELINE_VERSION.to_string();
+        let reply = SnpMessage::error_reply(
+            &request,
+            Destination::Local,
+            SnpErrorCode::UnknownModule,
+            "module not installed: chess",
+        );
+
+        assert_eq!(reply.version, SNP_BASELINE_VERSION);
+        let json = serde_json::to_value(&reply.payload).unwrap();
+        assert!(json["Reply"].get("code").is_none());
+
+        request.version = SNP_VERSION.to_string();
+        let reply = SnpMessage::error_reply(
+            &request,
+            Destination::Local,
+            SnpErrorCode::UnknownModule,
+            "module not installed: chess",
+        );
+        let json = serde_json::to_value(&reply.payload).unwrap();
+        assert_eq!(json["Reply"]["code"], "unknown_module");
+    }
+}
@@ -14,17 +14,25 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::errors::AppError;
+use module_auth::session::AuthenticatedAgent;
 use crate::state::AppState;
 use synapse_application::events::{
     CreateEventCommand, CreateLocalEventUseCase, CreateRemoteEventCommand, CreateRemoteEventUseCase,
 };
 use synapse_core::domain::events::Event;
 use synapse_core::domain::events::ObjectRef;
+use synapse_core::domain::events::PublicKey;
+use synapse_core::domain::events::delegations::DELEGATION_KEY;
 
 #[derive(Deserialize)]
 struct CreateEventRequest {
+    /// Agent of events posted without a session by one of its delegates.
+    /// Replaced by the agent of the session otherwise.
+    #[serde(default)]
+    agent: Option<PublicKey>,
+    id: Option<Uuid>,
+    created_at: Option<OffsetDateTime>,
     event_type: String,
-    agent: String,
     module_kind: Option<String>,
     module_slug: Option<String>,
     target: Option<ObjectRef>,
@@ -35,6 +43,7 @@ struct CreateEventRequest {
     links: Option<Vec<String>>,
     data: Option<Vec<u8>>,
     expiration: Option<OffsetDateTime>,
+    agent_signature: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -72,13 +81,20 @@ pub fn routes() -> Router<AppState> {
 
 async fn create_local_event(
     State(_app): State<AppState>,
+    agent: Option<AuthenticatedAgent>,
     Json(body): Json<CreateEventRequest>,
 ) -> Result<(StatusCode, Json<LocalEventResult>), AppError> {
+    let agent = match agent {
+        Some(agent) => agent.agent,
+        None => delegated_agent(&body)?,
+    };
     let cmd = CreateEventCommand {
+        id: body.id,
+        created_at: body.created_at,
         event_type: body.event_type,
         module_kind: body.module_kind,
         module_slug: body.module_slug,
-        agent: body.agent,
+        agent,
         target: body.target,
         previous: body.previous,
         content: body.content,
@@ -87,7 +103,7 @@ async fn create_local_event(
         links: body.links,
         data: body.data,
         expiration: body.expiration,
-        ..Default::default()
+        agent_signature: body.agent_signature,
     };
     let created = _app.create_local_event.execute(cmd).await?;
     Ok((
@@ -96,16 +112,35 @@ async fn create_local_event(
     ))
 }
 
+/// The agent of an event posted without a session. Only delegates, such as
+/// bots and connected apps, post this way; ingest checks their signature and
+/// delegation token.
+fn delegated_agent(body: &CreateEventRequest) -> Result<PublicKey, AppError> {
+    let delegated = body
+        .metadata
+        .as_ref()
+        .is_some_and(|metadata| metadata.contains_key(DELEGATION_KEY));
+    match &body.agent {
+        Some(agent) if delegated && body.agent_signature.is_some() => Ok(agent.clone()),
+        _ => Err(AppError::Unauthorized(
+            "events without a session must be signed by a delegate of their agent".to_string(),
+        )),
+    }
+}
+
 async fn create_remote_event(
     State(_app): State<AppState>,
     Path(synapse_public_key): Path<String>,
+    agent: AuthenticatedAgent,
     Json(body): Json<CreateEventRequest>,
 ) -> Result<(StatusCode, Json<RemoteEventResult>), AppError> {
     let inner = CreateEventCommand {
+        id: body.id,
+        created_at: body.created_at,
         event_type: body.event_type,
         module_kind: body.module_kind,
         module_slug: body.module_slug,
-        agent: body.agent,
+        agent: agent.agent,
         target: body.target,
         previous: body.previous,
         content: body.content,
@@ -114,7 +149,7 @@ async fn create_remote_event(
         links: body.links,
         data: body.data,
         expiration: body.expiration,
-        ..Default::default()
+        agent_signature: body.agent_signature,
     };
 
     let cmd = CreateRemoteEventCommand {
@@ -16,6 +16,7 @@ pub mod health;
 pub mod modules;
 pub mod peers;
 pub mod settings;
+pub mod subscriptions;
 pub mod synapses;
 
 pub fn routes() -> Router<AppState> {
@@ -23,4 +24,5 @@ pub fn routes() -> Router<AppState> {
         .merge(events::routes())
         .merge(federation::routes())
         .merge(health::routes())
+        .merge(subscriptions::routes())
 }
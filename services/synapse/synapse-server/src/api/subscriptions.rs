@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+// Copyright © 2025 Malifex LLC and contributors
+
+use std::convert::Infallible;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+    routing::{get, post},
+};
+use futures::{Stream, stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::state::AppState;
+use module_auth::session::AuthenticatedAgent;
+use synapse_core::ports::events::event_repository::{EventCursor, EventFilter};
+
+/// Query string accepted by the subscription endpoint; every field narrows the stream.
+#[derive(Deserialize, Default)]
+struct SubscribeQuery {
+    event_type: Option<String>,
+    module_kind: Option<String>,
+    module_slug: Option<String>,
+    agent: Option<String>,
+    previous: Option<Uuid>,
+}
+
+impl From<SubscribeQuery> for EventFilter {
+    fn from(query: SubscribeQuery) -> Self {
+        EventFilter {
+            event_type: query.event_type,
+            module_kind: query.module_kind,
+            module_slug: query.module_slug,
+            agent: query.agent,
+            previous: query.previous,
+            ..Default::default()
+        }
+    }
+}
+
+/// Body of a request to follow or unfollow a module on a remote Synapse.
+#[derive(Deserialize)]
+struct RemoteSubscriptionRequest {
+    module_kind: String,
+    module_slug: Option<String>,
+    /// Replay events recorded after this one before following live events.
+    since: Option<Uuid>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/events/subscribe", get(subscribe_events))
+        .route(
+            "/synapses/{synapse_public_key}/subscriptions",
+            post(follow_synapse).delete(unfollow_synapse),
+        )
+}
+
+/// Streams newly recorded events matching the query as Server-Sent Events.
+///
+/// Each message is an `event` carrying `{ "origin", "event" }`, where `origin`
+/// is the public key of the followed Synapse that published the event, or null
+/// for events recorded here. If the client
+/// falls behind the broadcast buffer a `lagged` message with the number of
+/// skipped events is sent instead, and the client should re-fetch via `list_*`.
+async fn subscribe_events(
+    State(app): State<AppState>,
+    agent: AuthenticatedAgent,
+    Query(query): Query<SubscribeQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, AppError> {
+    debug!("Agent {} subscribed to events", agent.agent);
+
+    let filter = EventFilter::from(query);
+    let rx = app.event_broadcast.subscribe();
+
+    let stream = stream::unfold((rx, filter), |(mut rx, filter)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if filter.matches(&event.event) => {
+                    let message = SseEvent::default()
+                        .event("event")
+                        .json_data(&event)
+                        .unwrap_or_else(|_| SseEvent::default().event("error"));
+                    return Some((Ok(message), (rx, filter)));
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(skipped)) => {
+                    let message = SseEvent::default().event("lagged").data(skipped.to_string());
+                    return Some((Ok(message), (rx, filter)));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Follows apply to the whole Synapse, so only its moderators manage them.
+fn require_moderator(app: &AppState, agent: &AuthenticatedAgent) -> Result<(), AppError> {
+    if app.moderators.contains(&agent.agent) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "only moderators may change follows".to_string(),
+        ))
+    }
+}
+
+/// Follows a module (or one slug of it) on a remote Synapse. Its events are
+/// relayed to `/events/subscribe` with the Synapse as `origin`.
+async fn follow_synapse(
+    State(app): State<AppState>,
+    agent: AuthenticatedAgent,
+    Path(synapse_public_key): Path<String>,
+    Json(request): Json<RemoteSubscriptionRequest>,
+) -> Result<StatusCode, AppError> {
+    require_moderator(&app, &agent)?;
+    debug!(
+        "Agent {} followed {}/{} on {synapse_public_key}",
+        agent.agent,
+        request.module_kind,
+        request.module_slug.as_deref().unwrap_or("*")
+    );
+    app.event_subscriptions
+        .subscribe(
+            synapse_public_key,
+            request.module_kind,
+            request.module_slug,
+            request.since.map(EventCursor::Id),
+        )
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unfollow_synapse(
+    State(app): State<AppState>,
+    agent: AuthenticatedAgent,
+    Path(synapse_public_key): Path<String>,
+    Json(request): Json<RemoteSubscriptionRequest>,
+) -> Result<StatusCode, AppError> {
+    require_moderator(&app, &agent)?;
+    app.event_subscriptions
+        .unsubscribe(synapse_public_key, request.module_kind, request.module_slug)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    Ok(StatusCode::NO_CONTENT)
+}
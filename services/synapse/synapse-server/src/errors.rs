@@ -9,6 +9,8 @@ use thiserror::Error;
 pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
     #[error("Forbidden: {0}")]
     Forbidden(String),
     #[error("Not found: {0}")]
@@ -36,6 +38,12 @@ impl From<synapse_core::errors::CoreError> for AppError {
             CoreError::Timeout(_) => AppError::BadRequest("Timeout error".to_string()),
             CoreError::Unavailable(_) => AppError::BadRequest("Unavailable error".to_string()),
             CoreError::RateLimited(_) => AppError::BadRequest("RateLimited error".to_string()),
+            CoreError::UnsupportedModule(_) => {
+                AppError::BadRequest("UnsupportedModule error".to_string())
+            }
+            CoreError::UnsupportedAction(_) => {
+                AppError::BadRequest("UnsupportedAction error".to_string())
+            }
             CoreError::Other(_) => AppError::Other("Other error".to_string()),
         }
     }
@@ -45,6 +53,7 @@ impl axum::response::IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
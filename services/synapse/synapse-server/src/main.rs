@@ -16,8 +16,15 @@ use adapter_libp2p::initialize_p2p;
 
 use adapter_postgres::auth_repository::PostgresAuthRepository;
 use adapter_postgres::crypto_repository::PostgresCryptoRepository;
+use adapter_postgres::delegations_repository::PostgresDelegationRepository;
 use adapter_postgres::events_repository::PostgresEventsRepository;
+use adapter_postgres::keys_repository::PostgresKeyRepository;
+use adapter_postgres::peers_repository::PostgresPeerStore;
+use adapter_postgres::subscriptions_repository::PostgresSubscriptionStore;
 use adapter_postgres::profiles_repository::{PostgresProfilesDocStore, PostgresProfilesRepository};
+use adapter_postgres::reactions_repository::PostgresReactionsRepository;
+use adapter_postgres::search_repository::PostgresSearchRepository;
+use adapter_postgres::unit_of_work::PostgresUnitOfWorkFactory;
 use adapter_postgres::{create_pool, migrate};
 use client_web::app::Shell;
 use dashmap::DashMap;
@@ -36,15 +43,25 @@ use module_posts::http::routes as module_posts_routes;
 use module_posts::types::PostsDeps;
 use module_profiles::http::{ProfilesModule, routes as module_profiles_routes};
 use module_profiles::types::ProfilesDeps;
+use module_reactions::http::{ReactionsModule, routes as module_reactions_routes};
+use module_reactions::types::ReactionsDeps;
+use module_search::http::{SearchModule, routes as module_search_routes};
+use module_search::types::SearchDeps;
 use std::env;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use synapse_application::events::CreateLocalEventUseCase;
 use synapse_application::events::event_service::EventIngestService;
 use synapse_application::events::event_service::{LocalEventService, RemoteEventService};
+use synapse_application::auth::cleanup::prune_auth_records;
+use synapse_application::events::expiration::prune_expired_events;
+use synapse_application::events::publication::publish_local_events;
 use synapse_application::modules::InMemoryModuleRegistry;
-use synapse_application::profiles::profile_service::ProfileDiscoveryTransport;
+use synapse_application::profiles::profile_service::{
+    ProfileDiscoveryTransport, republish_profiles,
+};
 use synapse_config::get_synapse_config;
+use synapse_core::ports::federation::EventSubscriptions;
 use synapse_core::ports::modules::ModuleRegistry;
 use synapse_core::ports::profiles::profile_repository::ProfileDiscovery;
 use tower_http::trace::TraceLayer;
@@ -55,6 +72,9 @@ impl axum::extract::FromRef<AppState> for AuthDeps {
         AuthDeps {
             crypto_repo: app.crypto_repo.clone(),
             session_repo: app.session_repo.clone(),
+            delegations: app.delegation_repo.clone(),
+            keys: app.key_repo.clone(),
+            create_local_event: app.create_local_event.clone(),
         }
     }
 }
@@ -65,6 +85,7 @@ impl axum::extract::FromRef<AppState> for ProfilesDeps {
             doc_store: app.profile_doc_store.clone(),
             profile_repo: app.profile_repo.clone(),
             profile_discovery: app.profile_discovery.clone(),
+            keys: app.key_repo.clone(),
             create_local_event: app.create_local_event.clone(),
             create_remote_event: app.create_remote_event.clone(),
         }
@@ -80,6 +101,27 @@ impl axum::extract::FromRef<AppState> for PostsDeps {
             doc_store: app.profile_doc_store.clone(),
             profile_repo: app.profile_repo.clone(),
             profile_discovery: app.profile_discovery.clone(),
+            reactions: app.reactions_repo.clone(),
+        }
+    }
+}
+
+impl axum::extract::FromRef<AppState> for ReactionsDeps {
+    fn from_ref(app: &AppState) -> Self {
+        ReactionsDeps {
+            events: app.event_repo.clone(),
+            reactions: app.reactions_repo.clone(),
+            create_local_event: app.create_local_event.clone(),
+            create_remote_event: app.create_remote_event.clone(),
+        }
+    }
+}
+
+impl axum::extract::FromRef<AppState> for SearchDeps {
+    fn from_ref(app: &AppState) -> Self {
+        SearchDeps {
+            search: app.search_repo.clone(),
+            create_remote_event: app.create_remote_event.clone(),
         }
     }
 }
@@ -121,13 +163,25 @@ async fn main() -> anyhow::Result<()> {
     let event_repo = Arc::new(PostgresEventsRepository::new(pool.clone()));
     let crypto_repo = Arc::new(PostgresCryptoRepository::new(pool.clone()));
     let session_repo = Arc::new(PostgresAuthRepository::new(pool.clone()));
-    let ingest = Arc::new(EventIngestService::new(
-        event_repo.clone(),
-        module_registry.clone(),
-    ));
+    let delegation_repo = Arc::new(PostgresDelegationRepository::new(pool.clone()));
+    let key_repo = Arc::new(PostgresKeyRepository::new(pool.clone()));
+    let units = Arc::new(PostgresUnitOfWorkFactory::new(pool.clone()));
+    let config = get_synapse_config()?;
+    let ingest = Arc::new(
+        EventIngestService::new(event_repo.clone(), module_registry.clone(), units)
+            .with_moderators(config.moderators.clone())
+            .with_delegations(delegation_repo.clone())
+            .with_keys(key_repo.clone()),
+    );
 
     let profile_repo = Arc::new(PostgresProfilesRepository::new(pool.clone()));
     let profile_doc_store = Arc::new(PostgresProfilesDocStore::new(pool.clone()));
+    let reactions_repo = Arc::new(PostgresReactionsRepository::new(pool.clone()));
+    let search_repo = Arc::new(PostgresSearchRepository::new(pool.clone()));
+    let indexed = search_repo.index_profiles().await?;
+    if indexed > 0 {
+        info!("Indexed {indexed} profiles for search");
+    }
 
     module_registry.register(Arc::new(CoreModule::new(event_repo.clone())))?;
     module_registry.register(Arc::new(AuthModule::new(
@@ -139,32 +193,66 @@ async fn main() -> anyhow::Result<()> {
         profile_doc_store.clone(),
     )))?;
     module_registry.register(Arc::new(PostsModule::new(event_repo.clone())))?;
+    module_registry.register(Arc::new(ReactionsModule::new(
+        event_repo.clone(),
+        reactions_repo.clone(),
+    )))?;
+    module_registry.register(Arc::new(SearchModule::new(search_repo.clone())))?;
 
     let known_peers = Arc::new(DashMap::<String, String>::new());
+    let peer_store = Arc::new(PostgresPeerStore::new(pool.clone()));
+    let subscription_store = Arc::new(PostgresSubscriptionStore::new(pool.clone()));
 
-    let config = get_synapse_config()?;
-    let transport =
-        Arc::new(initialize_p2p(config.clone(), ingest.clone(), known_peers.clone()).await?);
+    let transport = Arc::new(
+        initialize_p2p(
+            config.clone(),
+            ingest.clone(),
+            known_peers.clone(),
+            peer_store.clone(),
+            subscription_store.clone(),
+        )
+        .await?,
+    );
 
     let profile_discovery = Arc::new(ProfileDiscoveryTransport::new(transport.clone()));
+    tokio::spawn(republish_profiles(
+        profile_doc_store.clone(),
+        profile_discovery.clone(),
+    ));
 
     let create_local_event: Arc<dyn CreateLocalEventUseCase + Send + Sync> =
         Arc::new(LocalEventService::new(ingest.clone()));
 
     let create_remote_event = Arc::new(RemoteEventService::new(transport.clone()));
 
+    tokio::spawn(prune_expired_events(event_repo.clone()));
+    tokio::spawn(prune_auth_records(session_repo.clone(), crypto_repo.clone()));
+
+    let event_subscriptions: Arc<dyn EventSubscriptions + Send + Sync> = transport.clone();
+    tokio::spawn(publish_local_events(
+        ingest.broadcast(),
+        event_subscriptions.clone(),
+    ));
+
     let leptos_options = client_web::leptos_options();
 
     let state = AppState {
         event_repo: event_repo.clone(),
         crypto_repo: crypto_repo.clone(),
         session_repo: session_repo.clone(),
+        delegation_repo: delegation_repo.clone(),
+        key_repo: key_repo.clone(),
         profile_doc_store: profile_doc_store.clone(),
         profile_repo: profile_repo.clone(),
         profile_discovery: profile_discovery.clone(),
+        reactions_repo: reactions_repo.clone(),
+        search_repo: search_repo.clone(),
         create_local_event,
         create_remote_event,
         known_peers: known_peers.clone(),
+        event_broadcast: ingest.broadcast(),
+        event_subscriptions,
+        moderators: config.moderators.clone(),
         leptos_options: leptos_options.clone(),
     };
 
@@ -172,6 +260,8 @@ async fn main() -> anyhow::Result<()> {
     let posts_deps = PostsDeps::from_ref(&state);
     let auth_deps = AuthDeps::from_ref(&state);
     let profile_deps = ProfilesDeps::from_ref(&state);
+    let reactions_deps = ReactionsDeps::from_ref(&state);
+    let search_deps = SearchDeps::from_ref(&state);
 
     let routes = generate_route_list({
         let opts = leptos_options.clone();
@@ -182,6 +272,8 @@ async fn main() -> anyhow::Result<()> {
         .merge(module_core_routes::<AppState>())
         .merge(module_posts_routes::<AppState>())
         .merge(module_profiles_routes::<AppState>())
+        .merge(module_reactions_routes::<AppState>())
+        .merge(module_search_routes::<AppState>())
         .leptos_routes_with_context(
             &state,
             routes,
@@ -192,6 +284,8 @@ async fn main() -> anyhow::Result<()> {
                     provide_context(posts_deps.clone());
                     provide_context(auth_deps.clone());
                     provide_context(profile_deps.clone());
+                    provide_context(reactions_deps.clone());
+                    provide_context(search_deps.clone());
                 }
             },
             {
@@ -202,6 +296,10 @@ async fn main() -> anyhow::Result<()> {
         .fallback(file_and_error_handler::<AppState, _>(|options| {
             view! { <Shell options=options.clone()/> }
         }))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::authenticate,
+        ))
         .with_state(state)
         .layer(TraceLayer::new_for_http());
 
@@ -1,2 +1,23 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
-// Copyright © 2025 Malifex LLC and contributors
\ No newline at end of file
+// Copyright © 2025 Malifex LLC and contributors
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use module_auth::session::authenticate_session;
+use module_auth::types::AuthDeps;
+
+/// Resolves the session cookie of every request. Requests with an active
+/// session carry its `AuthenticatedAgent` in their extensions for handlers
+/// and server functions to extract; the others go through unauthenticated.
+pub async fn authenticate(
+    State(deps): State<AuthDeps>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(agent) = authenticate_session(deps.session_repo.as_ref(), request.headers()).await
+    {
+        request.extensions_mut().insert(agent);
+    }
+    next.run(request).await
+}
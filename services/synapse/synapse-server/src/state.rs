@@ -4,13 +4,20 @@
 use dashmap::DashMap;
 use std::sync::Arc;
 use synapse_application::events::CreateLocalEventUseCase;
+use synapse_application::events::broadcast::EventBroadcast;
 use synapse_application::events::CreateRemoteEventUseCase;
 use synapse_application::profiles::profile_service::ProfileDiscoveryTransport;
+use synapse_core::domain::events::PublicKey;
 use synapse_core::ports::auth::SessionRepository;
 use synapse_core::ports::crypto::CryptoRepository;
+use synapse_core::ports::delegations::delegation_repository::DelegationRepository;
 use synapse_core::ports::events::event_repository::EventRepository;
+use synapse_core::ports::federation::EventSubscriptions;
+use synapse_core::ports::keys::key_repository::KeyRepository;
 use synapse_core::ports::profiles::profile_repository::ProfilesDocStore;
 use synapse_core::ports::profiles::profile_repository::ProfilesRepository;
+use synapse_core::ports::reactions::reaction_repository::ReactionsRepository;
+use synapse_core::ports::search::search_repository::SearchRepository;
 
 use leptos::config::LeptosOptions;
 use synapse_core::ports::profiles::profile_repository::ProfileDiscovery;
@@ -20,11 +27,19 @@ pub struct AppState {
     pub event_repo: Arc<dyn EventRepository + Send + Sync>,
     pub crypto_repo: Arc<dyn CryptoRepository + Send + Sync>,
     pub session_repo: Arc<dyn SessionRepository + Send + Sync>,
+    pub delegation_repo: Arc<dyn DelegationRepository + Send + Sync>,
+    pub key_repo: Arc<dyn KeyRepository + Send + Sync>,
     pub profile_doc_store: Arc<dyn ProfilesDocStore + Send + Sync>,
     pub profile_repo: Arc<dyn ProfilesRepository + Send + Sync>,
     pub profile_discovery: Arc<dyn ProfileDiscovery + Send + Sync>,
+    pub reactions_repo: Arc<dyn ReactionsRepository + Send + Sync>,
+    pub search_repo: Arc<dyn SearchRepository + Send + Sync>,
     pub create_local_event: Arc<dyn CreateLocalEventUseCase + Send + Sync>,
     pub create_remote_event: Arc<dyn CreateRemoteEventUseCase + Send + Sync>,
     pub known_peers: Arc<DashMap<String, String>>,
+    pub event_broadcast: EventBroadcast,
+    pub event_subscriptions: Arc<dyn EventSubscriptions + Send + Sync>,
+    /// Agents allowed to manage Synapse-wide settings such as follows.
+    pub moderators: Vec<PublicKey>,
     pub leptos_options: LeptosOptions,
 }